@@ -1,17 +1,18 @@
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
+    bpf_loader_upgradeable,
     commitment_config::CommitmentConfig,
     instruction::{Instruction, AccountMeta},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
-    system_instruction,
     system_program,
 };
 use std::str::FromStr;
-use borsh::{BorshSerialize, BorshDeserialize};
-use unity_vault::user::state::{UserRole, KycData};
-use unity_vault::user::instructions::UserProfileParams;
+use unity_vault::user::state::{
+    find_kyc_registry_address, find_user_profile_address, hash_backup_code, UserRole, KycParams,
+};
+use unity_vault::user::instructions::{TwoFactorParams, UserProfileParams};
 use unity_vault::{Instruction as ProgramInstruction, UserInstruction};
 
 #[tokio::main]
@@ -25,7 +26,7 @@ async fn main() {
 
     // Generate keypairs
     let payer = Keypair::new();
-    let user_profile = Keypair::new();
+    let (user_profile, _) = find_user_profile_address(&program_id, &payer.pubkey());
 
     // Request airdrop
     let airdrop_amount = 1_000_000_000; // 1 SOL
@@ -48,38 +49,25 @@ async fn main() {
         role: UserRole::User,
     };
 
-    // Calculate minimum rent-exempt balance for the account
-    let account_size = 1126; // Size of UserProfile account (1,126 bytes)
-    let rent = client.get_minimum_balance_for_rent_exemption(account_size).unwrap();
-
-    // Create the account first
-    let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &user_profile.pubkey(),
-        rent,
-        account_size as u64,
-        &program_id,
-    );
-
     // Create the user profile instruction
     let create_profile_ix = Instruction::new_with_borsh(
         program_id,
         &ProgramInstruction::User(UserInstruction::CreateUserProfile(create_profile_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
     );
 
-    // Add both instructions to the transaction
+    // The program creates the profile account at its PDA
     let mut transaction = Transaction::new_with_payer(
-        &[create_account_ix, create_profile_ix],
+        &[create_profile_ix],
         Some(&payer.pubkey()),
     );
 
     // Sign and send the transaction
-    transaction.sign(&[&payer, &user_profile], client.get_latest_blockhash().unwrap());
+    transaction.sign(&[&payer], client.get_latest_blockhash().unwrap());
     
     match client.send_and_confirm_transaction(&transaction) {
         Ok(signature) => println!("User Profile Created! Signature: {}", signature),
@@ -97,7 +85,7 @@ async fn main() {
         program_id,
         &ProgramInstruction::User(UserInstruction::UpdateUserProfile(update_profile_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
         ],
     );
@@ -114,15 +102,24 @@ async fn main() {
         Err(err) => eprintln!("Error updating user profile: {}", err),
     }
 
-    // Enable two-factor authentication
+    // Enable two-factor authentication. Only hashes go on chain; the secret
+    // and backup codes stay with the user.
+    let backup_code_salt = Keypair::new().pubkey().to_bytes();
+    let two_factor_params = TwoFactorParams {
+        secret_hash: solana_sdk::hash::hash(b"test_secret").to_bytes(),
+        backup_code_salt,
+        backup_code_hashes: ["code1", "code2"]
+            .iter()
+            .map(|code| hash_backup_code(&backup_code_salt, code.as_bytes()))
+            .collect(),
+        second_factor: None,
+    };
+
     let two_factor_ix = Instruction::new_with_borsh(
         program_id,
-        &ProgramInstruction::User(UserInstruction::EnableTwoFactor {
-            secret: "test_secret".to_string(),
-            backup_codes: vec!["code1".to_string(), "code2".to_string()],
-        }),
+        &ProgramInstruction::User(UserInstruction::EnableTwoFactor(two_factor_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
         ],
     );
@@ -139,24 +136,50 @@ async fn main() {
         Err(err) => eprintln!("Error enabling two-factor: {}", err),
     }
 
-    // Verify KYC
-    let kyc_data = KycData {
+    // Verify KYC. Only a verifier registered in the KYC registry can attest;
+    // here the payer, as the upgrade authority, sets up the registry and
+    // registers itself.
+    let (kyc_registry, _) = find_kyc_registry_address(&program_id);
+    let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    let init_registry_ix = Instruction::new_with_borsh(
+        program_id,
+        &ProgramInstruction::User(UserInstruction::InitKycRegistry {
+            verification_validity: 365 * 24 * 60 * 60,
+        }),
+        vec![
+            AccountMeta::new(kyc_registry, false),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+
+    let add_verifier_ix = Instruction::new_with_borsh(
+        program_id,
+        &ProgramInstruction::User(UserInstruction::AddKycVerifier(payer.pubkey())),
+        vec![
+            AccountMeta::new(kyc_registry, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
+    );
+
+    let kyc_params = KycParams {
         document_type: "Passport".to_string(),
         document_number: "123456789".to_string(),
-        verified_at: 1234567890,
     };
 
     let verify_kyc_ix = Instruction::new_with_borsh(
         program_id,
-        &ProgramInstruction::User(UserInstruction::VerifyKyc(kyc_data)),
+        &ProgramInstruction::User(UserInstruction::VerifyKyc(kyc_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
-            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(user_profile, false),
+            AccountMeta::new_readonly(kyc_registry, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
         ],
     );
 
     let mut verify_kyc_transaction = Transaction::new_with_payer(
-        &[verify_kyc_ix],
+        &[init_registry_ix, add_verifier_ix, verify_kyc_ix],
         Some(&payer.pubkey()),
     );
 
@@ -166,4 +189,4 @@ async fn main() {
         Ok(signature) => println!("KYC Verified! Signature: {}", signature),
         Err(err) => eprintln!("Error verifying KYC: {}", err),
     }
-}
\ No newline at end of file
+}
@@ -2,9 +2,10 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
 };
+use crate::error::UnityVaultError;
+use crate::utils::load_account;
 use crate::community::state::Community;
 
 pub struct CreateCommunityContext<'a> {
@@ -25,9 +26,8 @@ pub struct SuspendCommunityContext<'a> {
 
 impl<'a> CreateCommunityContext<'a> {
     pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify community is not initialized
-        let community_data = Community::unpack(&self.community.data.borrow())?;
-        if community_data.is_initialized {
+        // Verify community has not been created yet
+        if !self.community.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
         
@@ -46,12 +46,9 @@ impl<'a> CreateCommunityContext<'a> {
 }
 
 impl<'a> UpdateCommunityContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify community is initialized
-        let community_data = Community::unpack(&self.community.data.borrow())?;
-        if !community_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify community is an initialized Community owned by this program
+        let community_data = load_account::<Community>(self.community, program_id)?;
         
         // Verify authority is signer
         if !self.authority.is_signer {
@@ -60,7 +57,7 @@ impl<'a> UpdateCommunityContext<'a> {
         
         // Verify authority matches
         if community_data.authority != *self.authority.key {
-            return Err(ProgramError::IllegalOwner);
+            return Err(UnityVaultError::CommunityAuthorityMismatch.into());
         }
         
         Ok(())
@@ -68,12 +65,9 @@ impl<'a> UpdateCommunityContext<'a> {
 }
 
 impl<'a> SuspendCommunityContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify community is initialized
-        let community_data = Community::unpack(&self.community.data.borrow())?;
-        if !community_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify community is an initialized Community owned by this program
+        let community_data = load_account::<Community>(self.community, program_id)?;
         
         // Verify authority is signer
         if !self.authority.is_signer {
@@ -82,7 +76,7 @@ impl<'a> SuspendCommunityContext<'a> {
         
         // Verify authority matches
         if community_data.authority != *self.authority.key {
-            return Err(ProgramError::IllegalOwner);
+            return Err(UnityVaultError::CommunityAuthorityMismatch.into());
         }
         
         Ok(())
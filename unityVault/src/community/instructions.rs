@@ -7,12 +7,16 @@ use solana_program::{
     pubkey::Pubkey,
     sysvar::Sysvar,
 };
-use crate::community::state::{Community, CommunityParams, CommunityStatus};
-use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+use crate::community::state::{
+    find_community_address, Community, CommunityParams, CommunityStatus, COMMUNITY_SEED,
+};
+use crate::utils::{create_pda_account, load_account};
 
 pub fn create_community(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
+    nonce: u64,
     params: CommunityParams,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -31,9 +35,28 @@ pub fn create_community(
         return Err(ProgramError::IncorrectProgramId);
     }
     
+    params.validate()?;
+    
+    // Verify community is the authority's PDA for this nonce
+    let (community_key, bump) = find_community_address(program_id, authority.key, nonce);
+    if community_key != *community.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the community account
+    create_pda_account(
+        authority,
+        community,
+        system_program,
+        program_id,
+        Community::LEN,
+        &[COMMUNITY_SEED, authority.key.as_ref(), &nonce.to_le_bytes(), &[bump]],
+    )?;
+    
     // Create and initialize community
     let community_data = Community {
         is_initialized: true,
+        bump,
         authority: *authority.key,
         name: params.name,
         description: params.description,
@@ -52,7 +75,7 @@ pub fn create_community(
 }
 
 pub fn update_community(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     params: CommunityParams,
 ) -> ProgramResult {
@@ -67,11 +90,13 @@ pub fn update_community(
     }
     
     // Verify authority matches
-    let mut community_data = Community::unpack_from_slice(&community.data.borrow())?;
+    let mut community_data = load_account::<Community>(community, program_id)?;
     if community_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+        return Err(UnityVaultError::CommunityAuthorityMismatch.into());
     }
     
+    params.validate()?;
+    
     // Update community
     community_data.name = params.name;
     community_data.description = params.description;
@@ -86,7 +111,7 @@ pub fn update_community(
 }
 
 pub fn suspend_community(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -100,9 +125,9 @@ pub fn suspend_community(
     }
     
     // Verify authority matches
-    let mut community_data = Community::unpack_from_slice(&community.data.borrow())?;
+    let mut community_data = load_account::<Community>(community, program_id)?;
     if community_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+        return Err(UnityVaultError::CommunityAuthorityMismatch.into());
     }
     
     // Suspend community
@@ -118,61 +143,41 @@ pub fn suspend_community(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_program::clock::Epoch;
-    use std::cell::RefCell;
+    use crate::test_utils::{create_empty_account, create_program_account, create_test_account, setup};
     
-    fn create_test_account<'a>(lamports: u64, owner: &'a Pubkey, data: &'a mut [u8]) -> AccountInfo<'a> {
-        static KEY: Pubkey = Pubkey::new_from_array([0; 32]);
-        static mut LAMPORTS: u64 = 0;
-        unsafe {
-            LAMPORTS = lamports;
-            AccountInfo::new(
-                &KEY,
-                false,
-                true,
-                &mut LAMPORTS,
-                data,
-                owner,
-                false,
-                Epoch::default(),
-            )
+    fn create_community_account<'a>(program_id: Pubkey, authority: Pubkey) -> AccountInfo<'a> {
+        let mut data = vec![0; Community::LEN];
+        Community {
+            is_initialized: true,
+            bump: 255,
+            authority,
+            name: "Test Community".to_string(),
+            description: "Test Description".to_string(),
+            rules: "Test Rules".to_string(),
+            is_private: false,
+            status: CommunityStatus::Active,
+            member_count: 1,
+            created_at: 0,
+            updated_at: 0,
         }
+        .pack_into_slice(&mut data);
+        create_test_account(Pubkey::new_unique(), program_id, false, data)
     }
 
     #[test]
     fn test_create_community() {
+        setup(1_700_000_000);
         let program_id = Pubkey::new_unique();
         let authority = Pubkey::new_unique();
-        let system_program_id = solana_program::system_program::id();
         
         // Create community account
-        let mut community_data = vec![0; Community::LEN];
-        let community_account = create_test_account(
-            1000000000,
-            &program_id,
-            &mut community_data
-        );
-        
-        // Create authority account
-        let mut authority_data = vec![];
-        let authority_account = create_test_account(
-            1000000000,
-            &authority,
-            &mut authority_data
-        );
-        
-        // Create system program account
-        let mut system_program_data = vec![];
-        let system_program_account = create_test_account(
-            0,
-            &system_program_id,
-            &mut system_program_data
-        );
+        let (community, bump) = find_community_address(&program_id, &authority, 7);
+        let community_account = create_empty_account(community);
         
         let accounts = vec![
             community_account.clone(),
-            authority_account,
-            system_program_account,
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
+            create_program_account(solana_program::system_program::id()),
         ];
         
         let params = CommunityParams {
@@ -182,11 +187,12 @@ mod tests {
             is_private: false,
         };
         
-        assert!(create_community(&program_id, &accounts, params).is_ok());
+        assert!(create_community(&program_id, &accounts, 7, params).is_ok());
         
-        let community_data = RefCell::new(community_data);
-        let community = Community::unpack(&community_data.borrow()).unwrap();
+        assert_eq!(*community_account.owner, program_id);
+        let community = Community::unpack(&community_account.data.borrow()).unwrap();
         assert!(community.is_initialized);
+        assert_eq!(community.bump, bump);
         assert_eq!(community.authority, authority);
         assert_eq!(community.name, "Test Community");
         assert_eq!(community.description, "Test Description");
@@ -198,28 +204,16 @@ mod tests {
 
     #[test]
     fn test_update_community() {
+        setup(1_700_000_000);
         let program_id = Pubkey::new_unique();
         let authority = Pubkey::new_unique();
         
         // Create initial community
-        let mut community_data = vec![0; Community::LEN];
-        let community_account = create_test_account(
-            1000000000,
-            &program_id,
-            &mut community_data
-        );
-        
-        // Create authority account
-        let mut authority_data = vec![];
-        let authority_account = create_test_account(
-            1000000000,
-            &authority,
-            &mut authority_data
-        );
+        let community_account = create_community_account(program_id, authority);
         
         let accounts = vec![
             community_account.clone(),
-            authority_account,
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
         ];
         
         let params = CommunityParams {
@@ -231,44 +225,69 @@ mod tests {
         
         assert!(update_community(&program_id, &accounts, params).is_ok());
         
-        let community_data = RefCell::new(community_data);
-        let community = Community::unpack(&community_data.borrow()).unwrap();
+        let community = Community::unpack(&community_account.data.borrow()).unwrap();
         assert_eq!(community.name, "New Name");
         assert_eq!(community.description, "New Description");
         assert_eq!(community.rules, "New Rules");
         assert!(community.is_private);
     }
 
+    #[test]
+    fn test_community_fields_must_fit_layout() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let long_params = || CommunityParams {
+            name: "Test Community".to_string(),
+            description: "Test Description".to_string(),
+            rules: "a".repeat(1001),
+            is_private: false,
+        };
+        
+        let community_account = create_empty_account(find_community_address(&program_id, &authority, 0).0);
+        let accounts = vec![
+            community_account.clone(),
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(create_community(&program_id, &accounts, 0, long_params()), Err(ProgramError::InvalidArgument));
+        assert!(community_account.data_is_empty());
+        
+        let community_account = create_community_account(program_id, authority);
+        let accounts = vec![
+            community_account.clone(),
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
+        ];
+        assert_eq!(update_community(&program_id, &accounts, long_params()), Err(ProgramError::InvalidArgument));
+        
+        let mut params = long_params();
+        params.rules = "a".repeat(1000);
+        params.name = "a".repeat(101);
+        assert_eq!(update_community(&program_id, &accounts, params.clone()), Err(ProgramError::InvalidArgument));
+        params.name = "a".repeat(100);
+        params.description = "a".repeat(500);
+        update_community(&program_id, &accounts, params).unwrap();
+        let community = Community::unpack(&community_account.data.borrow()).unwrap();
+        assert_eq!((community.name.len(), community.description.len(), community.rules.len()), (100, 500, 1000));
+    }
+
     #[test]
     fn test_suspend_community() {
+        setup(1_700_000_000);
         let program_id = Pubkey::new_unique();
         let authority = Pubkey::new_unique();
         
         // Create initial community
-        let mut community_data = vec![0; Community::LEN];
-        let community_account = create_test_account(
-            1000000000,
-            &program_id,
-            &mut community_data
-        );
-        
-        // Create authority account
-        let mut authority_data = vec![];
-        let authority_account = create_test_account(
-            1000000000,
-            &authority,
-            &mut authority_data
-        );
+        let community_account = create_community_account(program_id, authority);
         
         let accounts = vec![
             community_account.clone(),
-            authority_account,
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
         ];
         
         assert!(suspend_community(&program_id, &accounts).is_ok());
         
-        let community_data = RefCell::new(community_data);
-        let community = Community::unpack(&community_data.borrow()).unwrap();
+        let community = Community::unpack(&community_account.data.borrow()).unwrap();
         assert_eq!(community.status, CommunityStatus::Suspended);
     }
-} 
\ No newline at end of file
+}
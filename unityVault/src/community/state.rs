@@ -4,6 +4,17 @@ use solana_program::{
     pubkey::Pubkey,
 };
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::utils::{AccountType, ProgramAccount};
+
+/// Seed for a community PDA: `["community", authority, nonce]`.
+pub const COMMUNITY_SEED: &[u8] = b"community";
+
+pub fn find_community_address(program_id: &Pubkey, authority: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COMMUNITY_SEED, authority.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum CommunityRole {
@@ -30,6 +41,7 @@ pub struct CommunityParams {
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Community {
     pub is_initialized: bool,
+    pub bump: u8,
     pub authority: Pubkey,
     pub name: String,
     pub description: String,
@@ -41,8 +53,20 @@ pub struct Community {
     pub updated_at: i64,
 }
 
+impl CommunityParams {
+    /// Checks that the string fields fit the community layout.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.name.len() > 100 || self.description.len() > 500 || self.rules.len() > 1000 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
 impl Community {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // authority
         4 + 100 + // name (max 100 chars)
         4 + 500 + // description (max 500 chars)
@@ -56,6 +80,10 @@ impl Community {
 
 impl Sealed for Community {}
 
+impl ProgramAccount for Community {
+    const ACCOUNT_TYPE: AccountType = AccountType::Community;
+}
+
 impl IsInitialized for Community {
     fn is_initialized(&self) -> bool {
         self.is_initialized
@@ -68,10 +96,18 @@ impl Pack for Community {
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         // Pack is_initialized
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
         // Pack authority
         dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
         offset += 32;
@@ -116,12 +152,17 @@ impl Pack for Community {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         // Unpack is_initialized
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
         // Unpack authority
         let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| ProgramError::InvalidAccountData)?;
@@ -173,6 +214,7 @@ impl Pack for Community {
         
         Ok(Community {
             is_initialized,
+            bump,
             authority,
             name,
             description,
@@ -0,0 +1,239 @@
+use num_derive::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
+
+/// Errors returned by the Unity Vault program.
+///
+/// Each variant is surfaced as `ProgramError::Custom(code)`. Codes are grouped
+/// per module and must never be renumbered, since clients decode them from
+/// failed transaction logs:
+///
+/// - 0..100: common
+/// - 100..200: user
+/// - 200..300: governance
+/// - 300..400: community
+/// - 400..500: lending
+/// - 500..600: tokenization
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error, FromPrimitive)]
+pub enum UnityVaultError {
+    // Common
+    #[error("Arithmetic overflow")]
+    MathOverflow = 0,
+    #[error("Account address does not match its program-derived address")]
+    InvalidAccountAddress,
+    #[error("Account is not owned by this program")]
+    InvalidAccountOwner,
+    #[error("Account holds a different account type")]
+    InvalidAccountType,
+    #[error("Account data has the wrong size for its type")]
+    InvalidAccountSize,
+
+    // User
+    #[error("Signer is not the user profile authority")]
+    UserAuthorityMismatch = 100,
+    #[error("Signer is not the KYC registry admin")]
+    KycRegistryAdminMismatch,
+    #[error("Signer is not a registered KYC verifier")]
+    KycVerifierNotRegistered,
+    #[error("KYC verifier is already registered")]
+    KycVerifierAlreadyRegistered,
+    #[error("KYC registry has no room for another verifier")]
+    KycRegistryFull,
+    #[error("KYC verification has not expired")]
+    KycNotExpired,
+    #[error("Two-factor authentication is already enabled")]
+    TwoFactorAlreadyEnabled,
+    #[error("Two-factor authentication is not enabled")]
+    TwoFactorNotEnabled,
+    #[error("Too many two-factor backup codes")]
+    TooManyBackupCodes,
+    #[error("Backup code does not match any unused code")]
+    InvalidBackupCode,
+    #[error("Operation requires the second-factor key's signature")]
+    SecondFactorRequired,
+    #[error("Signer is not the program's upgrade authority")]
+    NotUpgradeAuthority,
+
+    // Governance
+    #[error("Signer is not the proposal authority")]
+    ProposalAuthorityMismatch = 200,
+    #[error("Proposal is not active")]
+    ProposalNotActive,
+    #[error("Proposal is not a draft")]
+    ProposalNotDraft,
+    #[error("Proposal has not passed")]
+    ProposalNotPassed,
+    #[error("Proposal is already finalized")]
+    ProposalAlreadyFinalized,
+    #[error("Proposal voting period has ended")]
+    VotingPeriodEnded,
+    #[error("Proposal voting period has not ended")]
+    VotingPeriodNotEnded,
+    #[error("Voter has already voted on this proposal")]
+    AlreadyVoted,
+    #[error("Proposal voting has not been finalized")]
+    ProposalNotFinalized,
+    #[error("Account belongs to a different realm")]
+    RealmMismatch,
+    #[error("Mint is not the realm's governing token mint")]
+    GoverningMintMismatch,
+    #[error("Voter has no deposited governing tokens")]
+    NoVotingPower,
+    #[error("Governing tokens are locked by unrelinquished votes")]
+    GoverningTokensLocked,
+    #[error("Withdrawal exceeds the deposited governing tokens")]
+    InsufficientGoverningTokens,
+    #[error("Signer is not the delegation's delegate")]
+    DelegateMismatch,
+    #[error("Delegation is revoked or expired")]
+    DelegationNotActive,
+    #[error("Account does not match the proposal action")]
+    ActionAccountMismatch,
+    #[error("Proposal action has already been executed")]
+    ActionAlreadyExecuted,
+    #[error("Hold-up time after voting has not elapsed")]
+    HoldUpTimeNotElapsed,
+    #[error("Action target is not governed by the realm")]
+    ActionTargetNotGoverned,
+    #[error("Signer is not the realm authority")]
+    RealmAuthorityMismatch,
+    #[error("Signer is not a member of the realm council")]
+    NotCouncilMember,
+    #[error("Veto lacks the council signatures the realm requires")]
+    NotEnoughVetoSigners,
+    #[error("Proposal's hold-up time, and with it the veto window, has ended")]
+    VetoPeriodEnded,
+    #[error("Vote does not match the proposal's ballot type")]
+    BallotTypeMismatch,
+    #[error("Proposal has no room for another option")]
+    ProposalOptionsFull,
+    #[error("Proposal settings are outside its category's bounds")]
+    CategoryRequirementsNotMet,
+    #[error("Proposer has not deposited enough governing tokens for the category")]
+    ProposerNotEligible,
+    #[error("Proposal is already linked to another version")]
+    ProposalVersionLinked,
+    #[error("Only Treasury proposals can spend from the treasury")]
+    NotTreasuryProposal,
+    #[error("Treasury ledger has no room for another mint")]
+    TreasuryLedgerFull,
+    #[error("Invoked instruction names the realm's governing token vault")]
+    ActionTouchesRealmVault,
+    #[error("Mint has not been added to the treasury ledger")]
+    TreasuryMintNotAllowed,
+    #[error("Invoked instruction needs the treasury's signature")]
+    ActionSignsForTreasury,
+
+    // Community
+    #[error("Signer is not the community authority")]
+    CommunityAuthorityMismatch = 300,
+
+    // Lending
+    #[error("Loan amount is below the pool minimum")]
+    LoanAmountBelowMinimum = 400,
+    #[error("Loan amount is above the pool maximum")]
+    LoanAmountAboveMaximum,
+    #[error("Signer is not the loan borrower")]
+    LoanBorrowerMismatch,
+    #[error("Token vault is not the pool's vault or not owned by the pool")]
+    TokenVaultMismatch,
+    #[error("Loan does not belong to this lending pool")]
+    LoanPoolMismatch,
+    #[error("Loan is not active")]
+    LoanNotActive,
+    #[error("Share mint is not the pool's share mint")]
+    ShareMintMismatch,
+    #[error("Not enough unlent liquidity in the pool")]
+    InsufficientLiquidity,
+    #[error("Loan is not past its due time and grace period")]
+    LoanNotOverdue,
+    #[error("Loan is neither defaulted nor undercollateralized")]
+    LoanNotLiquidatable,
+    #[error("Loan exceeds the pool's maximum loan-to-value")]
+    LoanToValueTooHigh,
+    #[error("Collateral vault is not the pool's collateral vault")]
+    CollateralVaultMismatch,
+    #[error("Signer is not the lending pool authority")]
+    LendingPoolAuthorityMismatch,
+    #[error("Interest rate model parameters are out of range")]
+    InvalidInterestRateModel,
+    #[error("Pool requires the borrower's user profile")]
+    BorrowerProfileRequired,
+    #[error("Borrower's KYC verification is missing or expired")]
+    BorrowerNotVerified,
+    #[error("Borrower's user profile is suspended or banned")]
+    BorrowerNotActive,
+    #[error("Pool does not lend to the borrower's credit tier")]
+    CreditTierNotAccepted,
+    #[error("Loan amount is above the borrower's credit limit")]
+    LoanAboveCreditLimit,
+    #[error("Pool has outstanding shares but no liquidity behind them")]
+    PoolInsolvent,
+    #[error("Account is not the borrower's token account for the pool's collateral mint")]
+    BorrowerCollateralAccountMismatch,
+
+    // Tokenization
+    #[error("Mint authority is not the program-derived address for this mint")]
+    InvalidMintAuthority = 500,
+    #[error("Mint does not match the token info")]
+    MintMismatch,
+    #[error("Token account is not the creator's associated token account")]
+    TokenAccountMismatch,
+    #[error("On-chain mint state does not match the token info")]
+    MintStateMismatch,
+    #[error("Token is paused or frozen")]
+    TokenNotActive,
+    #[error("Burn amount exceeds the recorded supply")]
+    BurnExceedsSupply,
+}
+
+impl From<UnityVaultError> for ProgramError {
+    fn from(e: UnityVaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for UnityVaultError {
+    fn type_of() -> &'static str {
+        "UnityVaultError"
+    }
+}
+
+impl PrintProgramError for UnityVaultError {
+    fn print<E>(&self)
+    where
+        E: 'static
+            + std::error::Error
+            + DecodeError<E>
+            + PrintProgramError
+            + num_traits::FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::FromPrimitive;
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        assert_eq!(ProgramError::from(UnityVaultError::MathOverflow), ProgramError::Custom(0));
+        assert_eq!(ProgramError::from(UnityVaultError::UserAuthorityMismatch), ProgramError::Custom(100));
+        assert_eq!(ProgramError::from(UnityVaultError::ProposalNotActive), ProgramError::Custom(201));
+        assert_eq!(ProgramError::from(UnityVaultError::CommunityAuthorityMismatch), ProgramError::Custom(300));
+        assert_eq!(ProgramError::from(UnityVaultError::LoanAmountBelowMinimum), ProgramError::Custom(400));
+        assert_eq!(ProgramError::from(UnityVaultError::InvalidMintAuthority), ProgramError::Custom(500));
+    }
+
+    #[test]
+    fn test_error_codes_decode() {
+        assert_eq!(UnityVaultError::from_u32(401), Some(UnityVaultError::LoanAmountAboveMaximum));
+        assert_eq!(UnityVaultError::from_u32(99), None);
+    }
+}
@@ -4,11 +4,14 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
 };
-use crate::governance::state::Proposal;
+use crate::error::UnityVaultError;
+use crate::utils::load_account;
+use crate::governance::state::{find_token_owner_record_address, find_vote_record_address, Proposal, Realm};
 use std::convert::TryFrom;
 
 pub struct CreateProposalContext<'a> {
     pub proposal: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
 }
@@ -19,11 +22,13 @@ impl<'a> TryFrom<&'a [AccountInfo<'a>]> for CreateProposalContext<'a> {
     fn try_from(accounts: &'a [AccountInfo<'a>]) -> Result<Self, Self::Error> {
         let account_info_iter = &mut accounts.iter();
         let proposal = next_account_info(account_info_iter)?;
+        let realm = next_account_info(account_info_iter)?;
         let authority = next_account_info(account_info_iter)?;
         let system_program = next_account_info(account_info_iter)?;
 
         Ok(CreateProposalContext {
             proposal,
+            realm,
             authority,
             system_program,
         })
@@ -38,16 +43,21 @@ pub struct UpdateProposalContext<'a> {
 pub struct VoteProposalContext<'a> {
     pub proposal: &'a AccountInfo<'a>,
     pub voter: &'a AccountInfo<'a>,
+    pub vote_record: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
 }
 
 impl<'a> CreateProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
-        // Verify proposal account is not initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
-        if proposal_data.is_initialized {
+        // Verify proposal account has not been created yet
+        if !self.proposal.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
         
+        // Verify realm is an initialized Realm owned by this program
+        load_account::<Realm>(self.realm, program_id)?;
+        
         // Verify authority is signer
         if !self.authority.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
@@ -64,11 +74,8 @@ impl<'a> CreateProposalContext<'a> {
 
 impl<'a> UpdateProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
-        // Verify proposal account is initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
-        if !proposal_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+        // Verify proposal is an initialized Proposal owned by this program
+        let proposal_data = load_account::<Proposal>(self.proposal, program_id)?;
         
         // Verify authority is signer
         if !self.authority.is_signer {
@@ -77,7 +84,7 @@ impl<'a> UpdateProposalContext<'a> {
         
         // Verify authority matches
         if proposal_data.authority != *self.authority.key {
-            return Err(ProgramError::IllegalOwner);
+            return Err(UnityVaultError::ProposalAuthorityMismatch.into());
         }
         
         Ok(())
@@ -86,17 +93,31 @@ impl<'a> UpdateProposalContext<'a> {
 
 impl<'a> VoteProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
-        // Verify proposal account is initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
-        if !proposal_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+        // Verify proposal is an initialized Proposal owned by this program
+        let proposal_data = load_account::<Proposal>(self.proposal, program_id)?;
         
         // Verify voter is signer
         if !self.voter.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
         
+        // Verify vote record is the voter's PDA for this proposal
+        if find_vote_record_address(program_id, self.proposal.key, self.voter.key).0 != *self.vote_record.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
+        // Verify token owner record is the voter's record in the proposal's realm
+        if find_token_owner_record_address(program_id, &proposal_data.realm, self.voter.key).0
+            != *self.token_owner_record.key
+        {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
+        // Verify system program
+        if self.system_program.key != &solana_program::system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
         Ok(())
     }
 } 
\ No newline at end of file
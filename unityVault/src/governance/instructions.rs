@@ -2,67 +2,139 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::Sysvar,
 };
-use crate::governance::state::{Proposal, ProposalParams, ProposalStatus, VoteType};
-use crate::governance::context::{CreateProposalContext, UpdateProposalContext, VoteProposalContext};
+use spl_token::state::Mint;
+use crate::community::state::{Community, CommunityStatus};
+use crate::error::UnityVaultError;
+use crate::governance::state::{
+    find_delegation_address, find_governance_config_address, find_proposal_action_address, find_proposal_address, find_proposal_options_address,
+    find_realm_address, find_realm_vault_address, find_token_owner_record_address, find_treasury_address, find_treasury_ledger_address,
+    find_vote_record_address, Action, ActionAccountMeta, BallotType, CategoryConfig, Delegation, DelegationStatus, GovernanceConfig,
+    Proposal, ProposalAction, ProposalCategory, ProposalOption, ProposalOptions, ProposalParams, ProposalStatus,
+    Realm, RealmConfig, TokenOwnerRecord, TreasuryFlow, TreasuryLedger, VoteRecord, VoteType, DELEGATION_SEED, GOVERNANCE_CONFIG_SEED,
+    MAX_COUNCIL_MEMBERS, MAX_OPTION_LABEL_LEN, MAX_PROPOSAL_OPTIONS, PROPOSAL_ACTION_SEED, PROPOSAL_CATEGORY_COUNT,
+    PROPOSAL_OPTIONS_SEED, PROPOSAL_SEED, REALM_SEED, REALM_VAULT_SEED, TOKEN_OWNER_RECORD_SEED, TREASURY_LEDGER_SEED,
+    TREASURY_SEED, VOTE_RECORD_SEED,
+};
+use crate::lending::state::LendingPool;
+use crate::math::{TryMath, BPS};
+use crate::tokenization::state::{TokenInfo, TokenStatus};
+use crate::utils::{close_account, create_pda_account, load_account, resize_account};
 
-pub fn create_proposal(
-    _program_id: &Pubkey,
+/// Creates a realm for `governing_mint`, with a vault PDA that holds the
+/// tokens members deposit to vote.
+pub fn init_realm(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    params: ProposalParams,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
+    let governing_mint = next_account_info(account_info_iter)?;
+    let token_vault = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
     
     // Verify authority is signer
     if !authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
     
-    // Verify system program
+    // Verify system and token programs
     if system_program.key != &solana_program::system_program::id() {
         return Err(ProgramError::IncorrectProgramId);
     }
+    if token_program.key != &spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
     
-    // Create and initialize proposal
-    let mut proposal_data = Proposal {
+    // Verify governing mint is an SPL mint
+    if governing_mint.owner != &spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Mint::unpack(&governing_mint.data.borrow())?;
+    
+    // Verify realm and vault are the PDAs for this authority and mint
+    let (realm_key, bump) = find_realm_address(program_id, authority.key, governing_mint.key);
+    if realm_key != *realm.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let (token_vault_key, token_vault_bump) = find_realm_vault_address(program_id, &realm_key);
+    if token_vault_key != *token_vault.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the realm account
+    create_pda_account(
+        authority,
+        realm,
+        system_program,
+        program_id,
+        Realm::LEN,
+        &[REALM_SEED, authority.key.as_ref(), governing_mint.key.as_ref(), &[bump]],
+    )?;
+    
+    // Create the vault as a token account owned by the realm PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            token_vault.key,
+            Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[authority.clone(), token_vault.clone(), system_program.clone()],
+        &[&[REALM_VAULT_SEED, realm_key.as_ref(), &[token_vault_bump]]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            token_vault.key,
+            governing_mint.key,
+            &realm_key,
+        )?,
+        &[token_vault.clone(), governing_mint.clone()],
+    )?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let realm_data = Realm {
         is_initialized: true,
+        bump,
         authority: *authority.key,
-        title: params.title,
-        description: params.description,
-        status: ProposalStatus::Draft,
-        voting_duration: params.voting_duration,
-        min_votes: params.min_votes,
-        min_approval_percentage: params.min_approval_percentage,
-        yes_votes: 0,
-        no_votes: 0,
-        abstain_votes: 0,
-        created_at: Clock::get()?.unix_timestamp,
-        updated_at: Clock::get()?.unix_timestamp,
-        executed_at: 0,
+        governing_mint: *governing_mint.key,
+        token_vault: token_vault_key,
+        hold_up_time: 0,
+        council: Vec::new(),
+        veto_threshold: 0,
+        created_at: current_time,
+        updated_at: current_time,
     };
     
     // Pack the data into the account
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    realm_data.pack_into_slice(&mut realm.data.borrow_mut());
     
     Ok(())
 }
 
-pub fn update_proposal(
-    _program_id: &Pubkey,
+/// Sets the realm's hold-up time and veto council. Only the realm authority
+/// may change them.
+pub fn set_realm_config(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    params: ProposalParams,
+    config: RealmConfig,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
     
     // Verify authority is signer
@@ -71,72 +143,3906 @@ pub fn update_proposal(
     }
     
     // Verify authority matches
-    let mut proposal_data = Proposal::unpack_from_slice(&proposal.data.borrow())?;
-    if proposal_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+    let mut realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.authority != *authority.key {
+        return Err(UnityVaultError::RealmAuthorityMismatch.into());
     }
     
-    // Update proposal
-    proposal_data.title = params.title;
-    proposal_data.description = params.description;
-    proposal_data.voting_duration = params.voting_duration;
-    proposal_data.min_votes = params.min_votes;
-    proposal_data.min_approval_percentage = params.min_approval_percentage;
-    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    // Council members are distinct, and a veto cannot need more of them
+    // than there are
+    let has_duplicates = config
+        .council
+        .iter()
+        .enumerate()
+        .any(|(i, member)| config.council[..i].contains(member));
+    if config.hold_up_time < 0
+        || config.council.len() > MAX_COUNCIL_MEMBERS
+        || has_duplicates
+        || config.veto_threshold as usize > config.council.len()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    realm_data.hold_up_time = config.hold_up_time;
+    realm_data.council = config.council;
+    realm_data.veto_threshold = config.veto_threshold;
+    realm_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    realm_data.pack_into_slice(&mut realm.data.borrow_mut());
     
     Ok(())
 }
 
-pub fn vote_proposal(
-    _program_id: &Pubkey,
+/// Creates the realm's governance config with the requirements for each
+/// proposal category, indexed by `ProposalCategory`. Only the realm
+/// authority may create it, and proposals cannot be created in the realm
+/// until it exists.
+pub fn init_governance_config(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    vote_type: VoteType,
+    categories: [CategoryConfig; PROPOSAL_CATEGORY_COUNT],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let proposal = next_account_info(account_info_iter)?;
-    let voter = next_account_info(account_info_iter)?;
+    let governance_config = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
     
-    // Verify voter is signer
-    if !voter.is_signer {
+    // Verify authority is signer
+    if !authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
     
-    // Get proposal data
-    let mut proposal_data = Proposal::unpack_from_slice(&proposal.data.borrow())?;
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
     
-    // Verify proposal is active
-    if proposal_data.status != ProposalStatus::Active {
-        return Err(ProgramError::InvalidAccountData);
+    // Verify authority matches
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.authority != *authority.key {
+        return Err(UnityVaultError::RealmAuthorityMismatch.into());
     }
     
-    // Update vote counts
-    match vote_type {
-        VoteType::Yes => proposal_data.yes_votes += 1,
-        VoteType::No => proposal_data.no_votes += 1,
-        VoteType::Abstain => proposal_data.abstain_votes += 1,
+    if !categories.iter().all(CategoryConfig::is_valid) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify governance config is the realm's config PDA
+    let (governance_config_key, bump) = find_governance_config_address(program_id, realm.key);
+    if governance_config_key != *governance_config.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    if !governance_config.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
     }
     
-    // Update proposal status if voting period has ended
+    // Create the governance config account
+    create_pda_account(
+        authority,
+        governance_config,
+        system_program,
+        program_id,
+        GovernanceConfig::LEN,
+        &[GOVERNANCE_CONFIG_SEED, realm.key.as_ref(), &[bump]],
+    )?;
+    
     let current_time = Clock::get()?.unix_timestamp;
-    if current_time >= proposal_data.created_at + proposal_data.voting_duration {
-        let total_votes = proposal_data.yes_votes + proposal_data.no_votes + proposal_data.abstain_votes;
-        let approval_percentage = (proposal_data.yes_votes as f64 / total_votes as f64) * 100.0;
-        
-        if total_votes >= proposal_data.min_votes && approval_percentage >= proposal_data.min_approval_percentage as f64 {
-            proposal_data.status = ProposalStatus::Passed;
-        } else {
-            proposal_data.status = ProposalStatus::Rejected;
+    let governance_config_data = GovernanceConfig {
+        is_initialized: true,
+        bump,
+        realm: *realm.key,
+        categories,
+        created_at: current_time,
+        updated_at: current_time,
+    };
+    
+    // Pack the data into the account
+    governance_config_data.pack_into_slice(&mut governance_config.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Replaces the requirements for one proposal category. Only the realm
+/// authority may change them; existing proposals are unaffected.
+pub fn set_category_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    category: ProposalCategory,
+    config: CategoryConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let governance_config = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.authority != *authority.key {
+        return Err(UnityVaultError::RealmAuthorityMismatch.into());
+    }
+    
+    // Verify governance config belongs to the realm
+    let mut governance_config_data = load_account::<GovernanceConfig>(governance_config, program_id)?;
+    if governance_config_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    
+    if !config.is_valid() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    governance_config_data.categories[category as usize] = config;
+    governance_config_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    governance_config_data.pack_into_slice(&mut governance_config.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Creates the ledger recording what flows into and out of the realm's
+/// treasury. Only the realm authority may create it, and the treasury takes
+/// no deposits until it exists.
+pub fn init_treasury_ledger(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let treasury_ledger = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify authority matches
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.authority != *authority.key {
+        return Err(UnityVaultError::RealmAuthorityMismatch.into());
+    }
+    
+    // Verify treasury ledger is the realm's ledger PDA
+    let (treasury_ledger_key, bump) = find_treasury_ledger_address(program_id, realm.key);
+    if treasury_ledger_key != *treasury_ledger.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    if !treasury_ledger.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    // Create the treasury ledger account
+    create_pda_account(
+        authority,
+        treasury_ledger,
+        system_program,
+        program_id,
+        TreasuryLedger::LEN,
+        &[TREASURY_LEDGER_SEED, realm.key.as_ref(), &[bump]],
+    )?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let treasury_ledger_data = TreasuryLedger {
+        is_initialized: true,
+        bump,
+        realm: *realm.key,
+        // SOL always has the first slot, so mints cannot crowd it out
+        flows: vec![TreasuryFlow { mint: None, inflow: 0, outflow: 0 }],
+        created_at: current_time,
+        updated_at: current_time,
+    };
+    
+    // Pack the data into the account
+    treasury_ledger_data.pack_into_slice(&mut treasury_ledger.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Deposits `amount` lamports into the realm's treasury, or with a `mint`
+/// the ledger tracks, `amount` tokens into a token account the treasury owns,
+/// and records the inflow in the treasury ledger. Anyone may deposit.
+pub fn deposit_to_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Option<Pubkey>,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let realm = next_account_info(account_info_iter)?;
+    let treasury_ledger = next_account_info(account_info_iter)?;
+    let depositor = next_account_info(account_info_iter)?;
+    
+    // Verify depositor is signer
+    if !depositor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify treasury ledger belongs to the realm
+    let mut treasury_ledger_data = load_account::<TreasuryLedger>(treasury_ledger, program_id)?;
+    if treasury_ledger_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Record the inflow, which fails for mints the ledger does not track
+    treasury_ledger_data.record_inflow(mint, amount)?;
+    treasury_ledger_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    let (treasury_key, _) = find_treasury_address(program_id, realm.key);
+    match mint {
+        None => {
+            let treasury = next_account_info(account_info_iter)?;
+            let system_program = next_account_info(account_info_iter)?;
+            if *treasury.key != treasury_key {
+                return Err(UnityVaultError::InvalidAccountAddress.into());
+            }
+            if system_program.key != &solana_program::system_program::id() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            
+            invoke(
+                &system_instruction::transfer(depositor.key, treasury.key, amount),
+                &[depositor.clone(), treasury.clone(), system_program.clone()],
+            )?;
+        }
+        Some(mint) => {
+            let source = next_account_info(account_info_iter)?;
+            let treasury_token_account = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+            if token_program.key != &spl_token::id() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            
+            // Verify the destination is a treasury token account for the mint
+            if treasury_token_account.owner != &spl_token::id() {
+                return Err(UnityVaultError::InvalidAccountOwner.into());
+            }
+            let treasury_token_account_data =
+                spl_token::state::Account::unpack(&treasury_token_account.data.borrow())?;
+            if treasury_token_account_data.mint != mint || treasury_token_account_data.owner != treasury_key {
+                return Err(UnityVaultError::InvalidAccountAddress.into());
+            }
+            
+            invoke(
+                &spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    source.key,
+                    treasury_token_account.key,
+                    depositor.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    source.clone(),
+                    treasury_token_account.clone(),
+                    depositor.clone(),
+                    token_program.clone(),
+                ],
+            )?;
         }
     }
     
-    proposal_data.updated_at = current_time;
+    // Pack the updated data
+    treasury_ledger_data.pack_into_slice(&mut treasury_ledger.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Adds `mint` to the treasury ledger, letting the treasury take deposits of
+/// it and spend it. Only the realm authority may add mints.
+pub fn add_treasury_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let treasury_ledger = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.authority != *authority.key {
+        return Err(UnityVaultError::RealmAuthorityMismatch.into());
+    }
+    
+    // Verify treasury ledger belongs to the realm
+    let mut treasury_ledger_data = load_account::<TreasuryLedger>(treasury_ledger, program_id)?;
+    if treasury_ledger_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    
+    treasury_ledger_data.add_mint(mint)?;
+    treasury_ledger_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    treasury_ledger_data.pack_into_slice(&mut treasury_ledger.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Deposits `amount` governing tokens into the realm's vault, adding them to
+/// the owner's voting power.
+pub fn deposit_governing_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let realm = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    let owner = next_account_info(account_info_iter)?;
+    let owner_token_account = next_account_info(account_info_iter)?;
+    let token_vault = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    // Verify owner is signer
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system and token programs
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if token_program.key != &spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify token vault is the realm's vault
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.token_vault != *token_vault.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify token owner record is the owner's PDA for this realm
+    let (token_owner_record_key, bump) = find_token_owner_record_address(program_id, realm.key, owner.key);
+    if token_owner_record_key != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Create the token owner record on the first deposit
+    let mut token_owner_record_data = if token_owner_record.data_is_empty() {
+        create_pda_account(
+            owner,
+            token_owner_record,
+            system_program,
+            program_id,
+            TokenOwnerRecord::LEN,
+            &[TOKEN_OWNER_RECORD_SEED, realm.key.as_ref(), owner.key.as_ref(), &[bump]],
+        )?;
+        TokenOwnerRecord {
+            is_initialized: true,
+            bump,
+            realm: *realm.key,
+            owner: *owner.key,
+            deposited_amount: 0,
+            outstanding_votes: 0,
+            created_at: current_time,
+            updated_at: current_time,
+        }
+    } else {
+        load_account::<TokenOwnerRecord>(token_owner_record, program_id)?
+    };
+    
+    // Move the tokens into the vault
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            owner_token_account.key,
+            token_vault.key,
+            owner.key,
+            &[],
+            amount,
+        )?,
+        &[
+            owner_token_account.clone(),
+            token_vault.clone(),
+            owner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    
+    token_owner_record_data.deposited_amount = token_owner_record_data.deposited_amount.try_add(amount)?;
+    token_owner_record_data.updated_at = current_time;
+    
+    // Pack the updated data
+    token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Withdraws `amount` deposited governing tokens. Fails while any of the
+/// owner's votes are unrelinquished.
+pub fn withdraw_governing_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let realm = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    let owner = next_account_info(account_info_iter)?;
+    let owner_token_account = next_account_info(account_info_iter)?;
+    let token_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    // Verify owner is signer
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify token program
+    if token_program.key != &spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify token vault is the realm's vault
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.token_vault != *token_vault.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Verify token owner record is the owner's PDA for this realm
+    if find_token_owner_record_address(program_id, realm.key, owner.key).0 != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+    
+    if token_owner_record_data.outstanding_votes > 0 {
+        return Err(UnityVaultError::GoverningTokensLocked.into());
+    }
+    if amount > token_owner_record_data.deposited_amount {
+        return Err(UnityVaultError::InsufficientGoverningTokens.into());
+    }
+    
+    token_owner_record_data.deposited_amount = token_owner_record_data.deposited_amount.try_sub(amount)?;
+    token_owner_record_data.updated_at = Clock::get()?.unix_timestamp;
+    token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    
+    // Return the tokens from the vault, signed by the realm PDA
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            token_vault.key,
+            owner_token_account.key,
+            realm.key,
+            &[],
+            amount,
+        )?,
+        &[
+            token_vault.clone(),
+            owner_token_account.clone(),
+            realm.clone(),
+            token_program.clone(),
+        ],
+        &[&realm_data.signer_seeds()],
+    )?;
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+pub fn create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nonce: u64,
+    params: ProposalParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let governance_config = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify realm is an initialized Realm owned by this program
+    load_account::<Realm>(realm, program_id)?;
+    
+    params.validate()?;
+    if params.quorum_bps > BPS || params.min_approval_percentage > 100 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify the proposal meets its category's requirements
+    let category_config = load_category_config(program_id, governance_config, realm.key, params.category)?;
+    if !category_config.permits(&params) {
+        return Err(UnityVaultError::CategoryRequirementsNotMet.into());
+    }
+    
+    // Verify the proposer has deposited enough governing tokens in the realm
+    if find_token_owner_record_address(program_id, realm.key, authority.key).0 != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+    if token_owner_record_data.deposited_amount < category_config.min_proposer_deposit {
+        return Err(UnityVaultError::ProposerNotEligible.into());
+    }
+    
+    // Verify proposal is the authority's PDA for this nonce
+    let (proposal_key, bump) = find_proposal_address(program_id, authority.key, nonce);
+    if proposal_key != *proposal.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the proposal account
+    create_pda_account(
+        authority,
+        proposal,
+        system_program,
+        program_id,
+        Proposal::LEN,
+        &[PROPOSAL_SEED, authority.key.as_ref(), &nonce.to_le_bytes(), &[bump]],
+    )?;
+    
+    // Create and initialize proposal
+    let proposal_data = Proposal {
+        is_initialized: true,
+        bump,
+        authority: *authority.key,
+        realm: *realm.key,
+        category: params.category,
+        title: params.title,
+        description: params.description,
+        status: ProposalStatus::Draft,
+        voting_duration: params.voting_duration,
+        voting_starts_at: 0,
+        quorum_bps: params.quorum_bps,
+        min_approval_percentage: params.min_approval_percentage,
+        ballot: params.ballot,
+        option_rule: params.option_rule,
+        yes_votes: 0,
+        no_votes: 0,
+        abstain_votes: 0,
+        action_count: 0,
+        actions_executed: 0,
+        content_hash: [0; 32],
+        version: 1,
+        previous_version: None,
+        next_version: None,
+        hold_up_time: 0,
+        council: Vec::new(),
+        veto_threshold: 0,
+        created_at: Clock::get()?.unix_timestamp,
+        updated_at: Clock::get()?.unix_timestamp,
+        executed_at: 0,
+    };
+    
+    // Pack the data into the account
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Rewrites a draft proposal. Once voting starts its content is fixed, and a
+/// change needs a new proposal linked with `supersede_proposal`.
+pub fn update_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: ProposalParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let governance_config = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(UnityVaultError::ProposalNotDraft.into());
+    }
+    
+    // The category and ballot type are fixed, since the proposer's
+    // eligibility was checked for the category and options may already
+    // have been added
+    params.validate()?;
+    if params.quorum_bps > BPS
+        || params.min_approval_percentage > 100
+        || params.category != proposal_data.category
+        || params.ballot != proposal_data.ballot
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify the updated proposal still meets its category's requirements
+    let category_config = load_category_config(program_id, governance_config, &proposal_data.realm, params.category)?;
+    if !category_config.permits(&params) {
+        return Err(UnityVaultError::CategoryRequirementsNotMet.into());
+    }
+    
+    // Update proposal
+    proposal_data.title = params.title;
+    proposal_data.description = params.description;
+    proposal_data.voting_duration = params.voting_duration;
+    proposal_data.quorum_bps = params.quorum_bps;
+    proposal_data.min_approval_percentage = params.min_approval_percentage;
+    proposal_data.option_rule = params.option_rule;
+    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Appends `action` to a draft yes/no proposal, to be executed `hold_up_time`
+/// seconds after voting ends if the proposal passes.
+pub fn insert_proposal_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hold_up_time: i64,
+    action: Action,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let proposal_action = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify authority matches
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(UnityVaultError::ProposalNotDraft.into());
+    }
+    if hold_up_time < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Actions run when a proposal passes, which a multiple-choice proposal
+    // does if any of its options does, so only yes/no proposals carry them
+    if proposal_data.ballot != BallotType::YesNo {
+        return Err(UnityVaultError::BallotTypeMismatch.into());
+    }
+    action.validate()?;
+    if let Action::Invoke { accounts, .. } = &action {
+        check_invoke_accounts(program_id, &proposal_data.realm, accounts)?;
+    }
+    
+    // Only Treasury proposals spend from the treasury
+    if matches!(action, Action::TreasuryTransfer { .. }) && proposal_data.category != ProposalCategory::Treasury {
+        return Err(UnityVaultError::NotTreasuryProposal.into());
+    }
+    
+    // Verify proposal action is the PDA for the proposal's next index
+    let index = proposal_data.action_count;
+    let (proposal_action_key, bump) = find_proposal_action_address(program_id, proposal.key, index);
+    if proposal_action_key != *proposal_action.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the proposal action account
+    create_pda_account(
+        authority,
+        proposal_action,
+        system_program,
+        program_id,
+        ProposalAction::LEN,
+        &[PROPOSAL_ACTION_SEED, proposal.key.as_ref(), &index.to_le_bytes(), &[bump]],
+    )?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let proposal_action_data = ProposalAction {
+        is_initialized: true,
+        bump,
+        proposal: *proposal.key,
+        index,
+        hold_up_time,
+        action,
+        executed_at: 0,
+    };
+    proposal_data.action_count = index.checked_add(1).ok_or(UnityVaultError::MathOverflow)?;
+    proposal_data.updated_at = current_time;
+    
+    // Pack proposal action and proposal
+    proposal_action_data.pack_into_slice(&mut proposal_action.data.borrow_mut());
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Adds an option labelled `label` to a draft multiple-choice proposal,
+/// creating its options account with the first option and growing it by one
+/// option after that.
+pub fn add_proposal_option(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    label: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let proposal_options = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify authority matches
+    let proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(UnityVaultError::ProposalNotDraft.into());
+    }
+    if proposal_data.ballot == BallotType::YesNo {
+        return Err(UnityVaultError::BallotTypeMismatch.into());
+    }
+    if label.is_empty() || label.len() > MAX_OPTION_LABEL_LEN {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify proposal options is the proposal's options PDA
+    let (proposal_options_key, bump) = find_proposal_options_address(program_id, proposal.key);
+    if proposal_options_key != *proposal_options.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    let mut proposal_options_data = if proposal_options.data_is_empty() {
+        // Create the options account sized for its first option
+        create_pda_account(
+            authority,
+            proposal_options,
+            system_program,
+            program_id,
+            ProposalOptions::space(1),
+            &[PROPOSAL_OPTIONS_SEED, proposal.key.as_ref(), &[bump]],
+        )?;
+        
+        ProposalOptions {
+            is_initialized: true,
+            bump,
+            proposal: *proposal.key,
+            total_weight: 0,
+            options: Vec::new(),
+        }
+    } else {
+        let proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+        if proposal_options_data.options.len() >= MAX_PROPOSAL_OPTIONS {
+            return Err(UnityVaultError::ProposalOptionsFull.into());
+        }
+        
+        // Make room for one more option
+        resize_account(
+            proposal_options,
+            authority,
+            system_program,
+            ProposalOptions::space(proposal_options_data.options.len() + 1),
+        )?;
+        proposal_options_data
+    };
+    
+    proposal_options_data.options.push(ProposalOption {
+        label,
+        votes: 0,
+        passed: false,
+    });
+    
+    // Pack the updated data
+    proposal_options_data.pack_into_slice(&mut proposal_options.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Opens a draft proposal for voting, starting its voting period now, and
+/// records the hash of its content. Multiple-choice proposals also take
+/// their options account and need at least two options. Every proposal
+/// action follows, in index order.
+pub fn activate_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(UnityVaultError::ProposalNotDraft.into());
+    }
+    if proposal_data.voting_duration <= 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut option_labels = Vec::new();
+    if proposal_data.ballot != BallotType::YesNo {
+        let proposal_options = next_account_info(account_info_iter)?;
+        if find_proposal_options_address(program_id, proposal.key).0 != *proposal_options.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        let proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+        if proposal_options_data.options.len() < 2 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        option_labels = proposal_options_data.options.into_iter().map(|option| option.label).collect();
+    }
+    
+    // Verify every action of the proposal is passed, in order
+    let mut actions = Vec::with_capacity(proposal_data.action_count as usize);
+    for index in 0..proposal_data.action_count {
+        let proposal_action = next_account_info(account_info_iter)?;
+        let proposal_action_data = load_account::<ProposalAction>(proposal_action, program_id)?;
+        if proposal_action_data.proposal != *proposal.key || proposal_action_data.index != index {
+            return Err(UnityVaultError::ActionAccountMismatch.into());
+        }
+        actions.push(proposal_action_data);
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    proposal_data.content_hash = proposal_data.hash_content(&option_labels, &actions)?;
+    proposal_data.status = ProposalStatus::Active;
+    proposal_data.voting_starts_at = current_time;
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Casts the voter's one vote on an active proposal, weighted by their
+/// deposited governing tokens and recorded in their vote record for it. A
+/// direct vote replaces a vote a delegate already cast for the voter.
+pub fn vote_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vote_type: VoteType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let voter = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify voter is signer
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Get proposal data
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Multiple-choice ballots are voted on with vote_options
+    if proposal_data.ballot != BallotType::YesNo {
+        return Err(UnityVaultError::BallotTypeMismatch.into());
+    }
+    
+    // Verify proposal is active and still within its voting period
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time >= proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodEnded.into());
+    }
+    
+    // Verify vote record is the voter's PDA for this proposal
+    let (vote_record_key, bump) = find_vote_record_address(program_id, proposal.key, voter.key);
+    if vote_record_key != *vote_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Verify token owner record is the voter's record in the proposal's realm
+    if find_token_owner_record_address(program_id, &proposal_data.realm, voter.key).0 != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+    if token_owner_record_data.deposited_amount == 0 {
+        return Err(UnityVaultError::NoVotingPower.into());
+    }
+    
+    let vote_record_data = if vote_record.data_is_empty() {
+        // Create the vote record
+        create_pda_account(
+            voter,
+            vote_record,
+            system_program,
+            program_id,
+            VoteRecord::LEN,
+            &[VOTE_RECORD_SEED, proposal.key.as_ref(), voter.key.as_ref(), &[bump]],
+        )?;
+        
+        // Lock the voter's deposit until the vote is relinquished
+        token_owner_record_data.outstanding_votes = token_owner_record_data.outstanding_votes.try_add(1)?;
+        token_owner_record_data.updated_at = current_time;
+        
+        VoteRecord {
+            is_initialized: true,
+            bump,
+            proposal: *proposal.key,
+            voter: *voter.key,
+            vote_type,
+            choices: 0,
+            weight: token_owner_record_data.deposited_amount,
+            delegate: None,
+            timestamp: current_time,
+        }
+    } else {
+        // Only a vote cast by a delegate can be overridden this way
+        let mut vote_record_data = load_account::<VoteRecord>(vote_record, program_id)?;
+        if vote_record_data.delegate.is_none() {
+            return Err(UnityVaultError::AlreadyVoted.into());
+        }
+        proposal_data.remove_vote(vote_record_data.vote_type, vote_record_data.weight)?;
+        
+        vote_record_data.vote_type = vote_type;
+        vote_record_data.weight = token_owner_record_data.deposited_amount;
+        vote_record_data.delegate = None;
+        vote_record_data.timestamp = current_time;
+        vote_record_data
+    };
+    
+    // Update vote counts
+    proposal_data.add_vote(vote_type, vote_record_data.weight)?;
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    vote_record_data.pack_into_slice(&mut vote_record.data.borrow_mut());
+    token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Moves the voter's existing vote to `vote_type` while voting is still open.
+pub fn change_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vote_type: VoteType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let voter = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    
+    // Verify voter is signer
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Get proposal data
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Multiple-choice votes are changed with change_vote_options
+    if proposal_data.ballot != BallotType::YesNo {
+        return Err(UnityVaultError::BallotTypeMismatch.into());
+    }
+    
+    // Verify proposal is active and still within its voting period
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time >= proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodEnded.into());
+    }
+    
+    // Verify vote record is the voter's PDA for this proposal
+    if find_vote_record_address(program_id, proposal.key, voter.key).0 != *vote_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut vote_record_data = load_account::<VoteRecord>(vote_record, program_id)?;
+    
+    // Move the vote between tallies
+    proposal_data.remove_vote(vote_record_data.vote_type, vote_record_data.weight)?;
+    proposal_data.add_vote(vote_type, vote_record_data.weight)?;
+    proposal_data.updated_at = current_time;
+    
+    // The voter now stands behind the vote, even if a delegate cast it
+    vote_record_data.vote_type = vote_type;
+    vote_record_data.delegate = None;
+    vote_record_data.timestamp = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    vote_record_data.pack_into_slice(&mut vote_record.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Checks `choices` against a multiple-choice `ballot` of `option_count`
+/// options and returns them as a bitmask: distinct existing options, exactly
+/// one on a single-choice ballot and at least one on an approval ballot.
+fn option_choice_bits(ballot: BallotType, option_count: usize, choices: &[u8]) -> Result<u16, ProgramError> {
+    let choice_count_valid = match ballot {
+        BallotType::YesNo => return Err(UnityVaultError::BallotTypeMismatch.into()),
+        BallotType::SingleChoice => choices.len() == 1,
+        BallotType::Approval => !choices.is_empty(),
+    };
+    if !choice_count_valid {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut choice_bits: u16 = 0;
+    for &choice in choices {
+        if choice as usize >= option_count || choice_bits & (1 << choice) != 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        choice_bits |= 1 << choice;
+    }
+    Ok(choice_bits)
+}
+
+/// Casts the voter's one vote on an active multiple-choice proposal for the
+/// option indexes in `choices`: exactly one on a single-choice ballot, any
+/// number on an approval ballot. Each chosen option gets the voter's full
+/// deposited weight. As with `vote_proposal`, a direct vote replaces choices
+/// a delegate already cast for the voter.
+pub fn vote_options(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    choices: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let proposal_options = next_account_info(account_info_iter)?;
+    let voter = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify voter is signer
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify proposal is active and still within its voting period
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time >= proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodEnded.into());
+    }
+    
+    // Verify proposal options is the proposal's options PDA
+    if find_proposal_options_address(program_id, proposal.key).0 != *proposal_options.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+    let choice_bits = option_choice_bits(proposal_data.ballot, proposal_options_data.options.len(), &choices)?;
+    
+    // Verify vote record is the voter's PDA for this proposal
+    let (vote_record_key, bump) = find_vote_record_address(program_id, proposal.key, voter.key);
+    if vote_record_key != *vote_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Verify token owner record is the voter's record in the proposal's realm
+    if find_token_owner_record_address(program_id, &proposal_data.realm, voter.key).0 != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+    if token_owner_record_data.deposited_amount == 0 {
+        return Err(UnityVaultError::NoVotingPower.into());
+    }
+    
+    let vote_record_data = if vote_record.data_is_empty() {
+        // Create the vote record
+        create_pda_account(
+            voter,
+            vote_record,
+            system_program,
+            program_id,
+            VoteRecord::LEN,
+            &[VOTE_RECORD_SEED, proposal.key.as_ref(), voter.key.as_ref(), &[bump]],
+        )?;
+        
+        // Lock the voter's deposit until the vote is relinquished
+        token_owner_record_data.outstanding_votes = token_owner_record_data.outstanding_votes.try_add(1)?;
+        token_owner_record_data.updated_at = current_time;
+        
+        VoteRecord {
+            is_initialized: true,
+            bump,
+            proposal: *proposal.key,
+            voter: *voter.key,
+            vote_type: VoteType::Yes,
+            choices: choice_bits,
+            weight: token_owner_record_data.deposited_amount,
+            delegate: None,
+            timestamp: current_time,
+        }
+    } else {
+        // Only choices cast by a delegate can be overridden this way
+        let mut vote_record_data = load_account::<VoteRecord>(vote_record, program_id)?;
+        if vote_record_data.delegate.is_none() {
+            return Err(UnityVaultError::AlreadyVoted.into());
+        }
+        proposal_options_data.remove_vote(vote_record_data.choices, vote_record_data.weight)?;
+        
+        vote_record_data.choices = choice_bits;
+        vote_record_data.weight = token_owner_record_data.deposited_amount;
+        vote_record_data.delegate = None;
+        vote_record_data.timestamp = current_time;
+        vote_record_data
+    };
+    
+    // Update option tallies
+    proposal_options_data.add_vote(choice_bits, vote_record_data.weight)?;
+    
+    // Pack the updated data
+    proposal_options_data.pack_into_slice(&mut proposal_options.data.borrow_mut());
+    vote_record_data.pack_into_slice(&mut vote_record.data.borrow_mut());
+    token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Moves the voter's existing vote on a multiple-choice proposal to the
+/// option indexes in `choices` while voting is still open.
+pub fn change_vote_options(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    choices: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let proposal_options = next_account_info(account_info_iter)?;
+    let voter = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    
+    // Verify voter is signer
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    let proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify proposal is active and still within its voting period
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time >= proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodEnded.into());
+    }
+    
+    // Verify proposal options is the proposal's options PDA
+    if find_proposal_options_address(program_id, proposal.key).0 != *proposal_options.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+    let choice_bits = option_choice_bits(proposal_data.ballot, proposal_options_data.options.len(), &choices)?;
+    
+    // Verify vote record is the voter's PDA for this proposal
+    if find_vote_record_address(program_id, proposal.key, voter.key).0 != *vote_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut vote_record_data = load_account::<VoteRecord>(vote_record, program_id)?;
+    
+    // Move the vote between options
+    proposal_options_data.remove_vote(vote_record_data.choices, vote_record_data.weight)?;
+    proposal_options_data.add_vote(choice_bits, vote_record_data.weight)?;
+    
+    // The voter now stands behind the vote, even if a delegate cast it
+    vote_record_data.choices = choice_bits;
+    vote_record_data.delegate = None;
+    vote_record_data.timestamp = current_time;
+    
+    // Pack the updated data
+    proposal_options_data.pack_into_slice(&mut proposal_options.data.borrow_mut());
+    vote_record_data.pack_into_slice(&mut vote_record.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Delegates the signer's voting power in a realm to `delegate` until
+/// `expires_at` (0 for no expiry), replacing any earlier delegation.
+pub fn delegate_votes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expires_at: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let realm = next_account_info(account_info_iter)?;
+    let delegation = next_account_info(account_info_iter)?;
+    let delegator = next_account_info(account_info_iter)?;
+    let delegate = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify delegator is signer
+    if !delegator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify realm is an initialized Realm owned by this program
+    load_account::<Realm>(realm, program_id)?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if delegate.key == delegator.key || (expires_at != 0 && expires_at <= current_time) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify delegation is the delegator's PDA for this realm
+    let (delegation_key, bump) = find_delegation_address(program_id, realm.key, delegator.key);
+    if delegation_key != *delegation.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the delegation on first use
+    let created_at = if delegation.data_is_empty() {
+        create_pda_account(
+            delegator,
+            delegation,
+            system_program,
+            program_id,
+            Delegation::LEN,
+            &[DELEGATION_SEED, realm.key.as_ref(), delegator.key.as_ref(), &[bump]],
+        )?;
+        current_time
+    } else {
+        load_account::<Delegation>(delegation, program_id)?.created_at
+    };
+    
+    let delegation_data = Delegation {
+        is_initialized: true,
+        bump,
+        realm: *realm.key,
+        delegator: *delegator.key,
+        delegate: *delegate.key,
+        status: DelegationStatus::Active,
+        expires_at,
+        created_at,
+        updated_at: current_time,
+    };
+    
+    // Pack the data into the account
+    delegation_data.pack_into_slice(&mut delegation.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Revokes the signer's delegation. Votes the delegate already cast stand.
+pub fn revoke_delegation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let delegation = next_account_info(account_info_iter)?;
+    let delegator = next_account_info(account_info_iter)?;
+    
+    // Verify delegator is signer
+    if !delegator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify delegation is the delegator's PDA
+    let mut delegation_data = load_account::<Delegation>(delegation, program_id)?;
+    if find_delegation_address(program_id, &delegation_data.realm, delegator.key).0 != *delegation.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    if delegation_data.status != DelegationStatus::Active {
+        return Err(UnityVaultError::DelegationNotActive.into());
+    }
+    
+    delegation_data.status = DelegationStatus::Revoked;
+    delegation_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    delegation_data.pack_into_slice(&mut delegation.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Casts `vote_type` for every delegator passed after the fixed accounts, as
+/// `[delegation, token_owner_record, vote_record]` triples. Delegators who
+/// already voted, or have nothing deposited, are skipped. The delegate pays
+/// the vote records' rent, which goes to each delegator when they relinquish.
+pub fn vote_as_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vote_type: VoteType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    
+    // Get proposal data
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Multiple-choice ballots are voted on with vote_options_as_delegate
+    if proposal_data.ballot != BallotType::YesNo {
+        return Err(UnityVaultError::BallotTypeMismatch.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let weight = cast_delegated_votes(
+        program_id,
+        proposal,
+        &proposal_data,
+        account_info_iter.as_slice(),
+        vote_type,
+        0,
+        current_time,
+    )?;
+    
+    // Update vote counts
+    proposal_data.add_vote(vote_type, weight)?;
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Casts the option indexes in `choices` on a multiple-choice proposal for
+/// every delegator passed after the fixed accounts, as with
+/// `vote_as_delegate`.
+pub fn vote_options_as_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    choices: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let proposal_options = next_account_info(account_info_iter)?;
+    
+    let proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify proposal options is the proposal's options PDA
+    if find_proposal_options_address(program_id, proposal.key).0 != *proposal_options.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+    let choice_bits = option_choice_bits(proposal_data.ballot, proposal_options_data.options.len(), &choices)?;
+    
+    let weight = cast_delegated_votes(
+        program_id,
+        proposal,
+        &proposal_data,
+        account_info_iter.as_slice(),
+        VoteType::Yes,
+        choice_bits,
+        Clock::get()?.unix_timestamp,
+    )?;
+    
+    // Update option tallies
+    proposal_options_data.add_vote(choice_bits, weight)?;
+    
+    // Pack the updated data
+    proposal_options_data.pack_into_slice(&mut proposal_options.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Records `vote_type` and `choices` for each delegator in `accounts`, laid
+/// out as `[delegate, system_program]` followed by the delegator triples.
+/// Returns the total weight cast, for the caller to add to its tallies.
+fn cast_delegated_votes<'a>(
+    program_id: &Pubkey,
+    proposal: &AccountInfo<'a>,
+    proposal_data: &Proposal,
+    accounts: &[AccountInfo<'a>],
+    vote_type: VoteType,
+    choices: u16,
+    current_time: i64,
+) -> Result<u64, ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    
+    let delegate = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify delegate is signer
+    if !delegate.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify proposal is active and still within its voting period
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    if current_time >= proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodEnded.into());
+    }
+    
+    let delegator_accounts = account_info_iter.as_slice();
+    if !delegator_accounts.len().is_multiple_of(3) {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    
+    let mut total_weight: u64 = 0;
+    for delegator_accounts in delegator_accounts.chunks_exact(3) {
+        let delegation = &delegator_accounts[0];
+        let token_owner_record = &delegator_accounts[1];
+        let vote_record = &delegator_accounts[2];
+        
+        // Verify the delegation is an active grant to the signer in this realm
+        let delegation_data = load_account::<Delegation>(delegation, program_id)?;
+        if delegation_data.realm != proposal_data.realm {
+            return Err(UnityVaultError::RealmMismatch.into());
+        }
+        if delegation_data.delegate != *delegate.key {
+            return Err(UnityVaultError::DelegateMismatch.into());
+        }
+        if !delegation_data.is_active(current_time) {
+            return Err(UnityVaultError::DelegationNotActive.into());
+        }
+        let delegator = delegation_data.delegator;
+        
+        // Verify token owner record and vote record are the delegator's
+        if find_token_owner_record_address(program_id, &proposal_data.realm, &delegator).0 != *token_owner_record.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        let (vote_record_key, bump) = find_vote_record_address(program_id, proposal.key, &delegator);
+        if vote_record_key != *vote_record.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        if !vote_record.data_is_empty() {
+            continue;
+        }
+        let mut token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+        if token_owner_record_data.deposited_amount == 0 {
+            continue;
+        }
+        
+        // Create the delegator's vote record
+        create_pda_account(
+            delegate,
+            vote_record,
+            system_program,
+            program_id,
+            VoteRecord::LEN,
+            &[VOTE_RECORD_SEED, proposal.key.as_ref(), delegator.as_ref(), &[bump]],
+        )?;
+        
+        let vote_record_data = VoteRecord {
+            is_initialized: true,
+            bump,
+            proposal: *proposal.key,
+            voter: delegator,
+            vote_type,
+            choices,
+            weight: token_owner_record_data.deposited_amount,
+            delegate: Some(*delegate.key),
+            timestamp: current_time,
+        };
+        total_weight = total_weight.try_add(vote_record_data.weight)?;
+        
+        // Lock the delegator's deposit until the vote is relinquished
+        token_owner_record_data.outstanding_votes = token_owner_record_data.outstanding_votes.try_add(1)?;
+        token_owner_record_data.updated_at = current_time;
+        
+        vote_record_data.pack_into_slice(&mut vote_record.data.borrow_mut());
+        token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    }
+    
+    Ok(total_weight)
+}
+
+/// Closes the voter's vote record once the proposal's outcome is settled,
+/// returning its rent to the voter and unlocking their governing tokens. The
+/// vote stays in the proposal's tallies.
+pub fn relinquish_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let voter = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    let token_owner_record = next_account_info(account_info_iter)?;
+    
+    // Verify voter is signer
+    if !voter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify voting on the proposal is over
+    let proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if matches!(proposal_data.status, ProposalStatus::Draft | ProposalStatus::Active) {
+        return Err(UnityVaultError::ProposalNotFinalized.into());
+    }
+    
+    // Verify vote record is the voter's PDA for this proposal
+    if find_vote_record_address(program_id, proposal.key, voter.key).0 != *vote_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    load_account::<VoteRecord>(vote_record, program_id)?;
+    
+    // Verify token owner record is the voter's record in the proposal's realm
+    if find_token_owner_record_address(program_id, &proposal_data.realm, voter.key).0 != *token_owner_record.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    let mut token_owner_record_data = load_account::<TokenOwnerRecord>(token_owner_record, program_id)?;
+    token_owner_record_data.outstanding_votes = token_owner_record_data.outstanding_votes.try_sub(1)?;
+    token_owner_record_data.updated_at = Clock::get()?.unix_timestamp;
+    token_owner_record_data.pack_into_slice(&mut token_owner_record.data.borrow_mut());
+    
+    close_account(vote_record, voter)
+}
+
+/// Tallies an active proposal once its voting period has ended, marking it
+/// `Passed` or `Rejected`. Quorum is measured against the governing mint's
+/// current supply. Multiple-choice proposals also take their options account;
+/// they pass if any option passes under the proposal's option rule. Anyone
+/// may crank this.
+pub fn finalize_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    let governing_mint = next_account_info(account_info_iter)?;
+    
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify realm is the proposal's realm and the mint is its governing mint
+    if proposal_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    if realm_data.governing_mint != *governing_mint.key || governing_mint.owner != &spl_token::id() {
+        return Err(UnityVaultError::GoverningMintMismatch.into());
+    }
+    let supply = Mint::unpack(&governing_mint.data.borrow())?.supply;
+    if proposal_data.status != ProposalStatus::Active {
+        return Err(UnityVaultError::ProposalNotActive.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time < proposal_data.voting_ends_at()? {
+        return Err(UnityVaultError::VotingPeriodNotEnded.into());
+    }
+    
+    let approved = if proposal_data.ballot == BallotType::YesNo {
+        proposal_data.is_approved(supply)?
+    } else {
+        let proposal_options = next_account_info(account_info_iter)?;
+        if find_proposal_options_address(program_id, proposal.key).0 != *proposal_options.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        let mut proposal_options_data = ProposalOptions::load(proposal_options, program_id)?;
+        let approved = proposal_options_data.finalize(
+            supply,
+            proposal_data.quorum_bps,
+            proposal_data.min_approval_percentage,
+            proposal_data.option_rule,
+        )?;
+        proposal_options_data.pack_into_slice(&mut proposal_options.data.borrow_mut());
+        approved
+    };
+    
+    proposal_data.status = if approved {
+        // Later changes to the realm's settings do not reach a passed proposal
+        proposal_data.snapshot_realm_config(&realm_data);
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    };
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Withdraws a draft or active proposal. Only its authority may cancel it.
+pub fn cancel_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    if proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    
+    if !matches!(proposal_data.status, ProposalStatus::Draft | ProposalStatus::Active) {
+        return Err(UnityVaultError::ProposalAlreadyFinalized.into());
+    }
+    
+    proposal_data.status = ProposalStatus::Cancelled;
+    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Links a new draft proposal as the next version of `proposal`, for changes
+/// to a proposal that can no longer be edited. A draft or active proposal
+/// that is superseded is cancelled, so the old version cannot pass. Both
+/// proposals must have the same authority and realm, and each version has at
+/// most one successor.
+pub fn supersede_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let new_proposal = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches both proposals
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    let mut new_proposal_data = load_account::<Proposal>(new_proposal, program_id)?;
+    if proposal_data.authority != *authority.key || new_proposal_data.authority != *authority.key {
+        return Err(UnityVaultError::ProposalAuthorityMismatch.into());
+    }
+    if proposal_data.realm != new_proposal_data.realm {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    
+    // Verify the new proposal is an unlinked draft and the old one has no
+    // successor yet
+    if new_proposal_data.status != ProposalStatus::Draft {
+        return Err(UnityVaultError::ProposalNotDraft.into());
+    }
+    if proposal.key == new_proposal.key
+        || proposal_data.next_version.is_some()
+        || new_proposal_data.previous_version.is_some()
+    {
+        return Err(UnityVaultError::ProposalVersionLinked.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if matches!(proposal_data.status, ProposalStatus::Draft | ProposalStatus::Active) {
+        proposal_data.status = ProposalStatus::Cancelled;
+    }
+    proposal_data.next_version = Some(*new_proposal.key);
+    proposal_data.updated_at = current_time;
+    new_proposal_data.version = proposal_data.version.checked_add(1).ok_or(UnityVaultError::MathOverflow)?;
+    new_proposal_data.previous_version = Some(*proposal.key);
+    new_proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    new_proposal_data.pack_into_slice(&mut new_proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Executes the actions of a passed proposal passed after the proposal and
+/// realm, each as its proposal action account followed by the accounts the
+/// action uses. Once every action has executed the proposal is marked
+/// `Executed`, so actions can be executed over several transactions. Anyone
+/// may crank this.
+pub fn execute_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify realm is the proposal's realm
+    if proposal_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    let realm_data = load_account::<Realm>(realm, program_id)?;
+    
+    if proposal_data.status != ProposalStatus::Passed {
+        return Err(UnityVaultError::ProposalNotPassed.into());
+    }
+    
+    // Nothing executes until the hold-up time the proposal passed with, the
+    // council's window to veto, has passed
+    let current_time = Clock::get()?.unix_timestamp;
+    let voting_ends_at = proposal_data.voting_ends_at()?;
+    if current_time < voting_ends_at.try_add(proposal_data.hold_up_time)? {
+        return Err(UnityVaultError::HoldUpTimeNotElapsed.into());
+    }
+    
+    let mut remaining_accounts = account_info_iter.as_slice();
+    while let Some((proposal_action, rest)) = remaining_accounts.split_first() {
+        // Verify the action belongs to this proposal and is due
+        let mut proposal_action_data = load_account::<ProposalAction>(proposal_action, program_id)?;
+        if proposal_action_data.proposal != *proposal.key {
+            return Err(UnityVaultError::ActionAccountMismatch.into());
+        }
+        if proposal_action_data.executed_at != 0 {
+            return Err(UnityVaultError::ActionAlreadyExecuted.into());
+        }
+        if current_time < voting_ends_at.try_add(proposal_action_data.hold_up_time)? {
+            return Err(UnityVaultError::HoldUpTimeNotElapsed.into());
+        }
+        
+        let account_count = proposal_action_data.action.account_count();
+        if rest.len() < account_count {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (action_accounts, rest) = rest.split_at(account_count);
+        execute_action(
+            program_id,
+            realm,
+            &realm_data,
+            proposal_data.category,
+            &proposal_action_data.action,
+            action_accounts,
+            current_time,
+        )?;
+        
+        proposal_action_data.executed_at = current_time;
+        proposal_action_data.pack_into_slice(&mut proposal_action.data.borrow_mut());
+        proposal_data.actions_executed = proposal_data.actions_executed
+            .checked_add(1)
+            .ok_or(UnityVaultError::MathOverflow)?;
+        remaining_accounts = rest;
+    }
+    
+    if proposal_data.actions_executed == proposal_data.action_count {
+        proposal_data.status = ProposalStatus::Executed;
+        proposal_data.executed_at = current_time;
+    }
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Vetoes a passed proposal during its hold-up time. The council members
+/// vetoing sign and are passed after the proposal and realm; at least the
+/// veto threshold of them are needed. The council, threshold and hold-up time
+/// are those the realm had when the proposal passed.
+pub fn veto_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposal = next_account_info(account_info_iter)?;
+    let realm = next_account_info(account_info_iter)?;
+    
+    let mut proposal_data = load_account::<Proposal>(proposal, program_id)?;
+    
+    // Verify realm is the proposal's realm
+    if proposal_data.realm != *realm.key {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    
+    if proposal_data.status != ProposalStatus::Passed {
+        return Err(UnityVaultError::ProposalNotPassed.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time >= proposal_data.voting_ends_at()?.try_add(proposal_data.hold_up_time)? {
+        return Err(UnityVaultError::VetoPeriodEnded.into());
+    }
+    
+    // Verify every remaining account is a signing council member, counting
+    // each member once
+    let mut vetoers: Vec<&Pubkey> = Vec::new();
+    for council_member in account_info_iter {
+        if !council_member.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !proposal_data.is_council_member(council_member.key) {
+            return Err(UnityVaultError::NotCouncilMember.into());
+        }
+        if !vetoers.contains(&council_member.key) {
+            vetoers.push(council_member.key);
+        }
+    }
+    
+    // A threshold of 0 disables the veto
+    if proposal_data.veto_threshold == 0 || vetoers.len() < proposal_data.veto_threshold as usize {
+        return Err(UnityVaultError::NotEnoughVetoSigners.into());
+    }
+    
+    proposal_data.status = ProposalStatus::Vetoed;
+    proposal_data.updated_at = current_time;
+    
+    // Pack the updated data
+    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Loads the requirements for `category` from the realm's governance config.
+fn load_category_config(
+    program_id: &Pubkey,
+    governance_config: &AccountInfo,
+    realm: &Pubkey,
+    category: ProposalCategory,
+) -> Result<CategoryConfig, ProgramError> {
+    let governance_config_data = load_account::<GovernanceConfig>(governance_config, program_id)?;
+    if governance_config_data.realm != *realm {
+        return Err(UnityVaultError::RealmMismatch.into());
+    }
+    Ok(*governance_config_data.category(category))
+}
+
+/// Rejects invoked instructions that name the realm's governing token vault
+/// or need the treasury's signature. The realm PDA signs every invoke and
+/// also owns the vault, so such an instruction could move members' deposited
+/// tokens; the treasury only spends through `TreasuryTransfer`, which records
+/// the outflow in its ledger.
+fn check_invoke_accounts(program_id: &Pubkey, realm: &Pubkey, accounts: &[ActionAccountMeta]) -> ProgramResult {
+    let (token_vault, _) = find_realm_vault_address(program_id, realm);
+    if accounts.iter().any(|meta| meta.pubkey == token_vault) {
+        return Err(UnityVaultError::ActionTouchesRealmVault.into());
+    }
+    let (treasury, _) = find_treasury_address(program_id, realm);
+    if accounts.iter().any(|meta| meta.pubkey == treasury && meta.is_signer) {
+        return Err(UnityVaultError::ActionSignsForTreasury.into());
+    }
+    Ok(())
+}
+
+/// Performs one proposal action on `accounts`, as laid out by
+/// `Action::account_count`.
+fn execute_action(
+    program_id: &Pubkey,
+    realm: &AccountInfo,
+    realm_data: &Realm,
+    category: ProposalCategory,
+    action: &Action,
+    accounts: &[AccountInfo],
+    current_time: i64,
+) -> ProgramResult {
+    match action {
+        Action::UpdateLendingPool { lending_pool, param } => {
+            if accounts[0].key != lending_pool {
+                return Err(UnityVaultError::ActionAccountMismatch.into());
+            }
+            let mut lending_pool_data = load_account::<LendingPool>(&accounts[0], program_id)?;
+            if lending_pool_data.authority != *realm.key {
+                return Err(UnityVaultError::ActionTargetNotGoverned.into());
+            }
+            
+            lending_pool_data.set_param(*param)?;
+            lending_pool_data.updated_at = current_time;
+            LendingPool::pack(lending_pool_data, &mut accounts[0].data.borrow_mut())
+        }
+        Action::PauseToken { token_info } => {
+            if accounts[0].key != token_info {
+                return Err(UnityVaultError::ActionAccountMismatch.into());
+            }
+            let mut token_info_data = load_account::<TokenInfo>(&accounts[0], program_id)?;
+            if token_info_data.creator != *realm.key {
+                return Err(UnityVaultError::ActionTargetNotGoverned.into());
+            }
+            
+            // Frozen tokens stay frozen
+            if token_info_data.status == TokenStatus::Active {
+                token_info_data.status = TokenStatus::Paused;
+            }
+            token_info_data.updated_at = current_time;
+            token_info_data.pack_into_slice(&mut accounts[0].data.borrow_mut());
+            Ok(())
+        }
+        Action::SuspendCommunity { community } => {
+            if accounts[0].key != community {
+                return Err(UnityVaultError::ActionAccountMismatch.into());
+            }
+            let mut community_data = load_account::<Community>(&accounts[0], program_id)?;
+            if community_data.authority != *realm.key {
+                return Err(UnityVaultError::ActionTargetNotGoverned.into());
+            }
+            
+            community_data.status = CommunityStatus::Suspended;
+            community_data.updated_at = current_time;
+            community_data.pack_into_slice(&mut accounts[0].data.borrow_mut());
+            Ok(())
+        }
+        Action::TreasuryTransfer { mint, destination, amount } => {
+            if category != ProposalCategory::Treasury {
+                return Err(UnityVaultError::NotTreasuryProposal.into());
+            }
+            
+            let treasury = &accounts[0];
+            let (treasury_key, treasury_bump) = find_treasury_address(program_id, realm.key);
+            if *treasury.key != treasury_key {
+                return Err(UnityVaultError::InvalidAccountAddress.into());
+            }
+            let treasury_seeds: &[&[u8]] = &[TREASURY_SEED, realm.key.as_ref(), &[treasury_bump]];
+            
+            // Record the outflow in the realm's treasury ledger
+            let treasury_ledger = &accounts[1];
+            let mut treasury_ledger_data = load_account::<TreasuryLedger>(treasury_ledger, program_id)?;
+            if treasury_ledger_data.realm != *realm.key {
+                return Err(UnityVaultError::RealmMismatch.into());
+            }
+            treasury_ledger_data.record_outflow(*mint, *amount)?;
+            treasury_ledger_data.updated_at = current_time;
+            treasury_ledger_data.pack_into_slice(&mut treasury_ledger.data.borrow_mut());
+            
+            match mint {
+                None => {
+                    let destination_account = &accounts[2];
+                    let system_program = &accounts[3];
+                    if destination_account.key != destination {
+                        return Err(UnityVaultError::ActionAccountMismatch.into());
+                    }
+                    if system_program.key != &solana_program::system_program::id() {
+                        return Err(ProgramError::IncorrectProgramId);
+                    }
+                    
+                    invoke_signed(
+                        &system_instruction::transfer(treasury.key, destination, *amount),
+                        &[treasury.clone(), destination_account.clone(), system_program.clone()],
+                        &[treasury_seeds],
+                    )
+                }
+                Some(mint) => {
+                    let source = &accounts[2];
+                    let destination_account = &accounts[3];
+                    let token_program = &accounts[4];
+                    if destination_account.key != destination {
+                        return Err(UnityVaultError::ActionAccountMismatch.into());
+                    }
+                    if token_program.key != &spl_token::id() {
+                        return Err(ProgramError::IncorrectProgramId);
+                    }
+                    
+                    // Verify source is a treasury token account for the mint
+                    if source.owner != &spl_token::id() {
+                        return Err(UnityVaultError::ActionAccountMismatch.into());
+                    }
+                    let source_data = spl_token::state::Account::unpack(&source.data.borrow())?;
+                    if source_data.mint != *mint || source_data.owner != treasury_key {
+                        return Err(UnityVaultError::ActionAccountMismatch.into());
+                    }
+                    
+                    invoke_signed(
+                        &spl_token::instruction::transfer(
+                            &spl_token::id(),
+                            source.key,
+                            destination,
+                            &treasury_key,
+                            &[],
+                            *amount,
+                        )?,
+                        &[source.clone(), destination_account.clone(), treasury.clone(), token_program.clone()],
+                        &[treasury_seeds],
+                    )
+                }
+            }
+        }
+        Action::Invoke { program_id: target_program_id, accounts: action_accounts, data } => {
+            if accounts[0].key != target_program_id {
+                return Err(UnityVaultError::ActionAccountMismatch.into());
+            }
+            if action_accounts.iter().zip(&accounts[1..]).any(|(meta, account)| meta.pubkey != *account.key) {
+                return Err(UnityVaultError::ActionAccountMismatch.into());
+            }
+            check_invoke_accounts(program_id, realm.key, action_accounts)?;
+            
+            let instruction = Instruction {
+                program_id: *target_program_id,
+                accounts: action_accounts
+                    .iter()
+                    .map(|meta| AccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: data.clone(),
+            };
+            
+            // The realm signs for the invoked program
+            let realm_seeds = realm_data.signer_seeds();
+            invoke_signed(&instruction, accounts, &[&realm_seeds])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance::state::{OptionRule, MAX_TREASURY_MINTS};
+    use crate::lending::state::{InterestRateModel, LendingPoolParam, CREDIT_TIER_COUNT};
+    use crate::math::Decimal;
+    use crate::test_utils::{
+        create_empty_account, create_mint_account, create_program_account, create_test_account, create_token_account,
+        set_clock, setup, token_balance,
+    };
+    
+    const DAY: i64 = 24 * 60 * 60;
+    
+    /// Governing token supply of every test realm.
+    const SUPPLY: u64 = 1_000;
+    
+    struct TestRealm<'a> {
+        realm: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        governing_mint: AccountInfo<'a>,
+        token_vault: AccountInfo<'a>,
+        governance_config: AccountInfo<'a>,
+        treasury_ledger: AccountInfo<'a>,
+    }
+    
+    struct TestMember<'a> {
+        owner: AccountInfo<'a>,
+        token_account: AccountInfo<'a>,
+        token_owner_record: AccountInfo<'a>,
+    }
+    
+    fn proposal_params() -> ProposalParams {
+        ProposalParams {
+            title: "Raise the loan cap".to_string(),
+            description: "Raise the maximum loan amount to 2,000,000".to_string(),
+            voting_duration: 3 * DAY,
+            quorum_bps: 2_000, // 200 of the 1,000 supply
+            min_approval_percentage: 60,
+            category: ProposalCategory::ParameterChange,
+            ballot: BallotType::YesNo,
+            option_rule: OptionRule::HighestAboveThreshold,
+        }
+    }
+    
+    fn create_realm<'a>(program_id: &Pubkey) -> TestRealm<'a> {
+        let authority = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let governing_mint = create_mint_account(Pubkey::new_unique(), *authority.key, SUPPLY, 6);
+        let (realm, _) = find_realm_address(program_id, authority.key, governing_mint.key);
+        let accounts = vec![
+            create_empty_account(realm),
+            authority,
+            governing_mint,
+            create_empty_account(find_realm_vault_address(program_id, &realm).0),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(spl_token::id()),
+        ];
+        init_realm(program_id, &accounts).unwrap();
+        
+        let config = vec![
+            create_empty_account(find_governance_config_address(program_id, &realm).0),
+            accounts[0].clone(),
+            accounts[1].clone(),
+            accounts[4].clone(),
+        ];
+        init_governance_config(program_id, &config, [category_config(); PROPOSAL_CATEGORY_COUNT]).unwrap();
+        let mut ledger = config.clone();
+        ledger[0] = create_empty_account(find_treasury_ledger_address(program_id, &realm).0);
+        init_treasury_ledger(program_id, &ledger).unwrap();
+        
+        TestRealm {
+            realm: accounts[0].clone(),
+            authority: accounts[1].clone(),
+            governing_mint: accounts[2].clone(),
+            token_vault: accounts[3].clone(),
+            governance_config: config[0].clone(),
+            treasury_ledger: ledger[0].clone(),
+        }
+    }
+    
+    /// Requirements of every category in a test realm, which `proposal_params`
+    /// meets.
+    fn category_config() -> CategoryConfig {
+        CategoryConfig {
+            min_quorum_bps: 1_000,
+            min_approval_percentage: 50,
+            min_voting_duration: DAY,
+            max_voting_duration: 7 * DAY,
+            min_proposer_deposit: 10,
+        }
+    }
+    
+    /// Accounts for `deposit_governing_tokens`, or for `withdraw_governing_tokens`
+    /// without the system program.
+    fn deposit_accounts<'a>(realm: &TestRealm<'a>, member: &TestMember<'a>, withdraw: bool) -> Vec<AccountInfo<'a>> {
+        let mut accounts = vec![
+            realm.realm.clone(),
+            member.token_owner_record.clone(),
+            member.owner.clone(),
+            member.token_account.clone(),
+            realm.token_vault.clone(),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(spl_token::id()),
+        ];
+        if withdraw {
+            accounts.remove(5);
+        }
+        accounts
+    }
+    
+    /// A wallet that has deposited `deposit` governing tokens into the realm.
+    fn create_member<'a>(program_id: &Pubkey, realm: &TestRealm<'a>, deposit: u64) -> TestMember<'a> {
+        let owner = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let member = TestMember {
+            token_account: create_token_account(Pubkey::new_unique(), *realm.governing_mint.key, *owner.key, deposit),
+            token_owner_record: create_empty_account(
+                find_token_owner_record_address(program_id, realm.realm.key, owner.key).0,
+            ),
+            owner,
+        };
+        deposit_governing_tokens(program_id, &deposit_accounts(realm, &member, false), deposit).unwrap();
+        member
+    }
+    
+    /// Accounts for `create_proposal` by `proposer`, with nonce 0.
+    fn create_proposal_accounts<'a>(program_id: &Pubkey, realm: &TestRealm<'a>, proposer: &TestMember<'a>) -> Vec<AccountInfo<'a>> {
+        let (proposal, _) = find_proposal_address(program_id, proposer.owner.key, 0);
+        vec![
+            create_empty_account(proposal),
+            realm.realm.clone(),
+            realm.governance_config.clone(),
+            proposer.token_owner_record.clone(),
+            proposer.owner.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ]
+    }
+    
+    /// Creates a draft proposal in `realm` and returns `[proposal, authority]`.
+    fn create_test_proposal<'a>(program_id: &Pubkey, realm: &TestRealm<'a>) -> Vec<AccountInfo<'a>> {
+        let proposer = create_member(program_id, realm, 10);
+        let accounts = create_proposal_accounts(program_id, realm, &proposer);
+        create_proposal(program_id, &accounts, 0, proposal_params()).unwrap();
+        vec![accounts[0].clone(), accounts[4].clone()]
+    }
+    
+    /// Accounts for `vote_proposal`: `[proposal, voter, vote_record, token_owner_record, system_program]`.
+    fn vote_accounts<'a>(program_id: &Pubkey, proposal: &AccountInfo<'a>, member: &TestMember<'a>) -> Vec<AccountInfo<'a>> {
+        let (vote_record, _) = find_vote_record_address(program_id, proposal.key, member.owner.key);
+        vec![
+            proposal.clone(),
+            member.owner.clone(),
+            create_empty_account(vote_record),
+            member.token_owner_record.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ]
+    }
+    
+    /// Casts a vote for a new member holding `weight` tokens.
+    fn cast_vote<'a>(program_id: &Pubkey, realm: &TestRealm<'a>, proposal: &AccountInfo<'a>, weight: u64, vote_type: VoteType) {
+        let member = create_member(program_id, realm, weight);
+        vote_proposal(program_id, &vote_accounts(program_id, proposal, &member), vote_type).unwrap();
+    }
+    
+    fn finalize_accounts<'a>(realm: &TestRealm<'a>, proposal: &AccountInfo<'a>) -> Vec<AccountInfo<'a>> {
+        vec![proposal.clone(), realm.realm.clone(), realm.governing_mint.clone()]
+    }
+    
+    /// Inserts `actions` into a new `category` proposal, which is then
+    /// activated and passes, leaving the clock where its voting ended.
+    /// Returns the proposal accounts and the proposal action accounts.
+    fn create_passed_proposal<'a>(
+        program_id: &Pubkey,
+        realm: &TestRealm<'a>,
+        category: ProposalCategory,
+        actions: Vec<(i64, Action)>,
+    ) -> (Vec<AccountInfo<'a>>, Vec<AccountInfo<'a>>) {
+        let proposer = create_member(program_id, realm, 10);
+        let create = create_proposal_accounts(program_id, realm, &proposer);
+        create_proposal(program_id, &create, 0, ProposalParams { category, ..proposal_params() }).unwrap();
+        let accounts = vec![create[0].clone(), create[4].clone()];
+        let mut proposal_actions = Vec::new();
+        for (index, (hold_up_time, action)) in actions.into_iter().enumerate() {
+            let (proposal_action, _) = find_proposal_action_address(program_id, accounts[0].key, index as u16);
+            let insert = vec![
+                accounts[0].clone(),
+                create_empty_account(proposal_action),
+                accounts[1].clone(),
+                create_program_account(solana_program::system_program::id()),
+            ];
+            insert_proposal_action(program_id, &insert, hold_up_time, action).unwrap();
+            proposal_actions.push(insert[1].clone());
+        }
+        
+        let mut activate = accounts.clone();
+        activate.extend(proposal_actions.iter().cloned());
+        activate_proposal(program_id, &activate).unwrap();
+        cast_vote(program_id, realm, &accounts[0], 300, VoteType::Yes);
+        set_clock(load_account::<Proposal>(&accounts[0], program_id).unwrap().voting_ends_at().unwrap());
+        finalize_proposal(program_id, &finalize_accounts(realm, &accounts[0])).unwrap();
+        (accounts, proposal_actions)
+    }
+    
+    /// A lending pool, token and community whose authority is `authority`.
+    fn create_governed_accounts<'a>(program_id: &Pubkey, authority: Pubkey) -> [AccountInfo<'a>; 3] {
+        let mut lending_pool_data = vec![0; LendingPool::LEN];
+        LendingPool::pack(
+            LendingPool {
+                is_initialized: true,
+                bump: 255,
+                authority,
+                token_mint: Pubkey::new_unique(),
+                token_vault: Pubkey::new_unique(),
+                share_mint: Pubkey::new_unique(),
+                collateral_mint: Pubkey::new_unique(),
+                collateral_vault: Pubkey::new_unique(),
+                collateral_price: 1_000_000,
+                max_ltv: 7_500,
+                liquidation_threshold: 8_000,
+                liquidation_bonus: 500,
+                require_verified_borrower: false,
+                credit_tier_limits: [BPS; CREDIT_TIER_COUNT],
+                interest_rate_model: InterestRateModel::Fixed { rate: 500 },
+                max_loan_amount: 1_000_000,
+                min_loan_amount: 100,
+                grace_period: DAY,
+                total_borrowed: 0,
+                total_deposited: 0,
+                total_bad_debt: 0,
+                borrow_index: Decimal::ONE,
+                last_accrual_ts: 0,
+                created_at: 0,
+                updated_at: 0,
+            },
+            &mut lending_pool_data,
+        )
+        .unwrap();
+        
+        let mut token_info_data = vec![0; TokenInfo::LEN];
+        TokenInfo {
+            is_initialized: true,
+            bump: 255,
+            creator: authority,
+            mint: Pubkey::new_unique(),
+            mint_authority_bump: 255,
+            name: "Community Token".to_string(),
+            symbol: "COMM".to_string(),
+            decimals: 6,
+            total_supply: 1_000_000,
+            status: TokenStatus::Active,
+            created_at: 0,
+            updated_at: 0,
+        }
+        .pack_into_slice(&mut token_info_data);
+        
+        let mut community_data = vec![0; Community::LEN];
+        Community {
+            is_initialized: true,
+            bump: 255,
+            authority,
+            name: "Borrowers".to_string(),
+            description: "Community of borrowers".to_string(),
+            rules: "Be kind".to_string(),
+            is_private: false,
+            status: CommunityStatus::Active,
+            member_count: 1,
+            created_at: 0,
+            updated_at: 0,
+        }
+        .pack_into_slice(&mut community_data);
+        
+        [
+            create_test_account(Pubkey::new_unique(), *program_id, false, lending_pool_data),
+            create_test_account(Pubkey::new_unique(), *program_id, false, token_info_data),
+            create_test_account(Pubkey::new_unique(), *program_id, false, community_data),
+        ]
+    }
+    
+    #[test]
+    fn test_proposal_lifecycle() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        let member = create_member(&program_id, &realm, 100);
+        
+        // Drafts cannot be voted on
+        assert_eq!(
+            vote_proposal(&program_id, &vote_accounts(&program_id, &accounts[0], &member), VoteType::Yes).unwrap_err(),
+            UnityVaultError::ProposalNotActive.into()
+        );
+        
+        set_clock(1_700_000_000 + DAY);
+        activate_proposal(&program_id, &accounts).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.status, ProposalStatus::Active);
+        assert_eq!(proposal_data.voting_starts_at, 1_700_000_000 + DAY);
+        assert_eq!(proposal_data.voting_ends_at().unwrap(), 1_700_000_000 + 4 * DAY);
+        assert_eq!(
+            activate_proposal(&program_id, &accounts).unwrap_err(),
+            UnityVaultError::ProposalNotDraft.into()
+        );
+        
+        cast_vote(&program_id, &realm, &accounts[0], 150, VoteType::Yes);
+        cast_vote(&program_id, &realm, &accounts[0], 100, VoteType::No);
+        
+        // Voting stays open, and finalization waits, until the period ends
+        let finalize = finalize_accounts(&realm, &accounts[0]);
+        set_clock(1_700_000_000 + 4 * DAY - 1);
+        assert_eq!(
+            finalize_proposal(&program_id, &finalize).unwrap_err(),
+            UnityVaultError::VotingPeriodNotEnded.into()
+        );
+        let execute = vec![accounts[0].clone(), realm.realm.clone()];
+        assert_eq!(
+            execute_proposal(&program_id, &execute).unwrap_err(),
+            UnityVaultError::ProposalNotPassed.into()
+        );
+        
+        set_clock(1_700_000_000 + 4 * DAY);
+        assert_eq!(
+            vote_proposal(&program_id, &vote_accounts(&program_id, &accounts[0], &member), VoteType::Yes).unwrap_err(),
+            UnityVaultError::VotingPeriodEnded.into()
+        );
+        
+        // Anyone can finalize without a late vote: 250 votes reach the 200
+        // quorum and 60% of them approve
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Passed);
+        assert_eq!(
+            cancel_proposal(&program_id, &accounts).unwrap_err(),
+            UnityVaultError::ProposalAlreadyFinalized.into()
+        );
+        
+        set_clock(1_700_000_000 + 5 * DAY);
+        execute_proposal(&program_id, &execute).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.status, ProposalStatus::Executed);
+        assert_eq!(proposal_data.executed_at, 1_700_000_000 + 5 * DAY);
+        assert_eq!(
+            execute_proposal(&program_id, &execute).unwrap_err(),
+            UnityVaultError::ProposalNotPassed.into()
+        );
+    }
+    
+    #[test]
+    fn test_finalize_rejects_proposal_without_quorum() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        
+        // Unanimous, but short of 20% of the supply
+        cast_vote(&program_id, &realm, &accounts[0], 199, VoteType::Yes);
+        set_clock(1_700_000_000 + 3 * DAY);
+        let finalize = finalize_accounts(&realm, &accounts[0]);
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(
+            finalize_proposal(&program_id, &finalize).unwrap_err(),
+            UnityVaultError::ProposalNotActive.into()
+        );
+    }
+    
+    #[test]
+    fn test_cancel_proposal() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        
+        // Only the author can cancel
+        let other = vec![
+            accounts[0].clone(),
+            create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]),
+        ];
+        assert_eq!(
+            cancel_proposal(&program_id, &other).unwrap_err(),
+            UnityVaultError::ProposalAuthorityMismatch.into()
+        );
+        
+        cancel_proposal(&program_id, &accounts).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Cancelled);
+        let member = create_member(&program_id, &realm, 100);
+        assert_eq!(
+            vote_proposal(&program_id, &vote_accounts(&program_id, &accounts[0], &member), VoteType::Yes).unwrap_err(),
+            UnityVaultError::ProposalNotActive.into()
+        );
+    }
+    
+    #[test]
+    fn test_one_vote_per_voter() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        let member = create_member(&program_id, &realm, 300);
+        
+        let vote = vote_accounts(&program_id, &accounts[0], &member);
+        vote_proposal(&program_id, &vote, VoteType::Yes).unwrap();
+        let vote_record_data = load_account::<VoteRecord>(&vote[2], &program_id).unwrap();
+        assert_eq!(vote_record_data.proposal, *accounts[0].key);
+        assert_eq!(vote_record_data.voter, *member.owner.key);
+        assert_eq!(vote_record_data.vote_type, VoteType::Yes);
+        assert_eq!(vote_record_data.weight, 300);
+        assert_eq!(vote_record_data.timestamp, 1_700_000_000);
+        
+        // Voting again, even the other way, is rejected
+        assert_eq!(
+            vote_proposal(&program_id, &vote, VoteType::No).unwrap_err(),
+            UnityVaultError::AlreadyVoted.into()
+        );
+        
+        // Another voter's record cannot be used
+        let other = create_member(&program_id, &realm, 100);
+        let mut other_vote = vote_accounts(&program_id, &accounts[0], &other);
+        other_vote[2] = vote[2].clone();
+        assert_eq!(
+            vote_proposal(&program_id, &other_vote, VoteType::No).unwrap_err(),
+            UnityVaultError::InvalidAccountAddress.into()
+        );
+        
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!((proposal_data.yes_votes, proposal_data.no_votes), (300, 0));
+    }
+    
+    #[test]
+    fn test_change_and_relinquish_vote() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        
+        let member = create_member(&program_id, &realm, 200);
+        let vote = vote_accounts(&program_id, &accounts[0], &member);
+        vote_proposal(&program_id, &vote, VoteType::Yes).unwrap();
+        cast_vote(&program_id, &realm, &accounts[0], 200, VoteType::Yes);
+        
+        // Changing a vote moves its full weight between tallies
+        set_clock(1_700_000_000 + DAY);
+        let ballot = &vote[..3];
+        change_vote(&program_id, ballot, VoteType::No).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!((proposal_data.yes_votes, proposal_data.no_votes), (200, 200));
+        let vote_record_data = load_account::<VoteRecord>(&vote[2], &program_id).unwrap();
+        assert_eq!(vote_record_data.vote_type, VoteType::No);
+        assert_eq!(vote_record_data.timestamp, 1_700_000_000 + DAY);
+        
+        // Records are kept until the outcome is settled
+        let relinquish = &vote[..4];
+        assert_eq!(
+            relinquish_vote(&program_id, relinquish).unwrap_err(),
+            UnityVaultError::ProposalNotFinalized.into()
+        );
+        
+        set_clock(1_700_000_000 + 3 * DAY);
+        assert_eq!(
+            change_vote(&program_id, ballot, VoteType::Yes).unwrap_err(),
+            UnityVaultError::VotingPeriodEnded.into()
+        );
+        finalize_proposal(&program_id, &finalize_accounts(&realm, &accounts[0])).unwrap();
+        
+        let voter_lamports = vote[1].lamports();
+        let rent = vote[2].lamports();
+        relinquish_vote(&program_id, relinquish).unwrap();
+        assert_eq!(vote[1].lamports(), voter_lamports + rent);
+        assert_eq!(vote[2].lamports(), 0);
+        assert!(load_account::<VoteRecord>(&vote[2], &program_id).is_err());
+        
+        // The relinquished vote still counts in the final tally
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.status, ProposalStatus::Rejected);
+        assert_eq!((proposal_data.yes_votes, proposal_data.no_votes), (200, 200));
+    }
+    
+    #[test]
+    fn test_governing_tokens_locked_while_voting() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        
+        let member = create_member(&program_id, &realm, 250);
+        assert_eq!(token_balance(&member.token_account), 0);
+        assert_eq!(token_balance(&realm.token_vault), 10 + 250); // beside the proposer's deposit
+        let token_owner_record_data = load_account::<TokenOwnerRecord>(&member.token_owner_record, &program_id).unwrap();
+        assert_eq!(token_owner_record_data.realm, *realm.realm.key);
+        assert_eq!(token_owner_record_data.owner, *member.owner.key);
+        assert_eq!(token_owner_record_data.deposited_amount, 250);
+        
+        // Wallets that withdrew their deposit have no say
+        let empty = create_member(&program_id, &realm, 10);
+        withdraw_governing_tokens(&program_id, &deposit_accounts(&realm, &empty, true), 10).unwrap();
+        assert_eq!(
+            vote_proposal(&program_id, &vote_accounts(&program_id, &accounts[0], &empty), VoteType::No).unwrap_err(),
+            UnityVaultError::NoVotingPower.into()
+        );
+        
+        // Deposited tokens cannot leave the vault, and vote again from another
+        // wallet, until the vote is relinquished
+        let vote = vote_accounts(&program_id, &accounts[0], &member);
+        vote_proposal(&program_id, &vote, VoteType::Yes).unwrap();
+        let withdraw = deposit_accounts(&realm, &member, true);
+        assert_eq!(
+            withdraw_governing_tokens(&program_id, &withdraw, 250).unwrap_err(),
+            UnityVaultError::GoverningTokensLocked.into()
+        );
+        
+        set_clock(1_700_000_000 + 3 * DAY);
+        finalize_proposal(&program_id, &finalize_accounts(&realm, &accounts[0])).unwrap();
+        relinquish_vote(&program_id, &vote[..4]).unwrap();
+        assert_eq!(
+            withdraw_governing_tokens(&program_id, &withdraw, 251).unwrap_err(),
+            UnityVaultError::InsufficientGoverningTokens.into()
+        );
+        withdraw_governing_tokens(&program_id, &withdraw, 250).unwrap();
+        assert_eq!(token_balance(&member.token_account), 250);
+        assert_eq!(token_balance(&realm.token_vault), 10);
+        assert_eq!(
+            load_account::<TokenOwnerRecord>(&member.token_owner_record, &program_id).unwrap().deposited_amount,
+            0
+        );
+    }
+    
+    /// Accounts for `delegate_votes` from `member` to `delegate`.
+    fn delegate_accounts<'a>(
+        program_id: &Pubkey,
+        realm: &TestRealm<'a>,
+        member: &TestMember<'a>,
+        delegate: &AccountInfo<'a>,
+    ) -> Vec<AccountInfo<'a>> {
+        let (delegation, _) = find_delegation_address(program_id, realm.realm.key, member.owner.key);
+        vec![
+            realm.realm.clone(),
+            create_empty_account(delegation),
+            member.owner.clone(),
+            delegate.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ]
+    }
+    
+    /// Accounts for `vote_as_delegate`, with each delegator's `[delegation,
+    /// token_owner_record, vote_record]`.
+    fn delegate_vote_accounts<'a>(
+        program_id: &Pubkey,
+        proposal: &AccountInfo<'a>,
+        delegate: &AccountInfo<'a>,
+        delegators: &[(&AccountInfo<'a>, &TestMember<'a>)],
+    ) -> Vec<AccountInfo<'a>> {
+        let mut accounts = vec![
+            proposal.clone(),
+            delegate.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        for (delegation, member) in delegators {
+            accounts.push((*delegation).clone());
+            accounts.push(member.token_owner_record.clone());
+            accounts.push(create_empty_account(
+                find_vote_record_address(program_id, proposal.key, member.owner.key).0,
+            ));
+        }
+        accounts
+    }
+    
+    #[test]
+    fn test_delegate_votes_for_delegators() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        let delegate = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        
+        let first = create_member(&program_id, &realm, 100);
+        let first_delegation = delegate_accounts(&program_id, &realm, &first, &delegate);
+        delegate_votes(&program_id, &first_delegation, 0).unwrap();
+        let second = create_member(&program_id, &realm, 200);
+        let second_delegation = delegate_accounts(&program_id, &realm, &second, &delegate);
+        delegate_votes(&program_id, &second_delegation, 0).unwrap();
+        let expiring = create_member(&program_id, &realm, 300);
+        let expiring_delegation = delegate_accounts(&program_id, &realm, &expiring, &delegate);
+        delegate_votes(&program_id, &expiring_delegation, 1_700_000_000 + DAY).unwrap();
+        
+        let delegation_data = load_account::<Delegation>(&first_delegation[1], &program_id).unwrap();
+        assert_eq!(delegation_data.delegator, *first.owner.key);
+        assert_eq!(delegation_data.delegate, *delegate.key);
+        assert_eq!(delegation_data.status, DelegationStatus::Active);
+        assert_eq!(delegation_data.expires_at, 0);
+        
+        // One instruction votes with every active delegator's deposit
+        set_clock(1_700_000_000 + DAY);
+        let votes = delegate_vote_accounts(
+            &program_id,
+            &accounts[0],
+            &delegate,
+            &[(&first_delegation[1], &first), (&second_delegation[1], &second)],
+        );
+        vote_as_delegate(&program_id, &votes, VoteType::Yes).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.yes_votes, 300);
+        let vote_record_data = load_account::<VoteRecord>(&votes[5], &program_id).unwrap();
+        assert_eq!(vote_record_data.voter, *first.owner.key);
+        assert_eq!(vote_record_data.weight, 100);
+        assert_eq!(vote_record_data.delegate, Some(*delegate.key));
+        assert_eq!(
+            load_account::<TokenOwnerRecord>(&first.token_owner_record, &program_id).unwrap().outstanding_votes,
+            1
+        );
+        
+        // Repeating the vote skips delegators who already have one
+        vote_as_delegate(&program_id, &votes, VoteType::No).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().no_votes, 0);
+        
+        // Expired delegations and other delegates are rejected
+        let expired = delegate_vote_accounts(&program_id, &accounts[0], &delegate, &[(&expiring_delegation[1], &expiring)]);
+        assert_eq!(
+            vote_as_delegate(&program_id, &expired, VoteType::Yes).unwrap_err(),
+            UnityVaultError::DelegationNotActive.into()
+        );
+        let impostor = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let stolen = delegate_vote_accounts(&program_id, &accounts[0], &impostor, &[(&first_delegation[1], &first)]);
+        assert_eq!(
+            vote_as_delegate(&program_id, &stolen, VoteType::No).unwrap_err(),
+            UnityVaultError::DelegateMismatch.into()
+        );
+        
+        // Revoked delegations are rejected
+        revoke_delegation(&program_id, &first_delegation[1..3]).unwrap();
+        assert_eq!(
+            load_account::<Delegation>(&first_delegation[1], &program_id).unwrap().status,
+            DelegationStatus::Revoked
+        );
+        assert_eq!(
+            vote_as_delegate(&program_id, &votes, VoteType::Yes).unwrap_err(),
+            UnityVaultError::DelegationNotActive.into()
+        );
+        assert_eq!(
+            revoke_delegation(&program_id, &first_delegation[1..3]).unwrap_err(),
+            UnityVaultError::DelegationNotActive.into()
+        );
+    }
+    
+    #[test]
+    fn test_direct_vote_overrides_delegate() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_test_proposal(&program_id, &realm);
+        activate_proposal(&program_id, &accounts).unwrap();
+        let delegate = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        
+        let overriding = create_member(&program_id, &realm, 200);
+        let overriding_delegation = delegate_accounts(&program_id, &realm, &overriding, &delegate);
+        delegate_votes(&program_id, &overriding_delegation, 0).unwrap();
+        let early = create_member(&program_id, &realm, 300);
+        let early_delegation = delegate_accounts(&program_id, &realm, &early, &delegate);
+        delegate_votes(&program_id, &early_delegation, 0).unwrap();
+        
+        // A delegator who voted first keeps their own vote
+        let early_vote = vote_accounts(&program_id, &accounts[0], &early);
+        vote_proposal(&program_id, &early_vote, VoteType::No).unwrap();
+        let mut votes = delegate_vote_accounts(
+            &program_id,
+            &accounts[0],
+            &delegate,
+            &[(&overriding_delegation[1], &overriding), (&early_delegation[1], &early)],
+        );
+        votes[8] = early_vote[2].clone();
+        vote_as_delegate(&program_id, &votes, VoteType::Yes).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!((proposal_data.yes_votes, proposal_data.no_votes), (200, 300));
+        assert_eq!(load_account::<VoteRecord>(&early_vote[2], &program_id).unwrap().delegate, None);
+        
+        // A delegator who votes after their delegate replaces the delegate's vote
+        let mut overriding_vote = vote_accounts(&program_id, &accounts[0], &overriding);
+        overriding_vote[2] = votes[5].clone();
+        vote_proposal(&program_id, &overriding_vote, VoteType::No).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!((proposal_data.yes_votes, proposal_data.no_votes), (0, 500));
+        let vote_record_data = load_account::<VoteRecord>(&votes[5], &program_id).unwrap();
+        assert_eq!(vote_record_data.vote_type, VoteType::No);
+        assert_eq!(vote_record_data.delegate, None);
+        assert_eq!(
+            load_account::<TokenOwnerRecord>(&overriding.token_owner_record, &program_id).unwrap().outstanding_votes,
+            1
+        );
+        
+        // After which it is theirs, like any direct vote
+        assert_eq!(
+            vote_proposal(&program_id, &overriding_vote, VoteType::Yes).unwrap_err(),
+            UnityVaultError::AlreadyVoted.into()
+        );
+    }
+    
+    #[test]
+    fn test_execute_proposal_actions() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let [lending_pool, token_info, community] = create_governed_accounts(&program_id, *realm.realm.key);
+        
+        // The treasury holds SOL and owns a token account
+        let (treasury_key, _) = find_treasury_address(&program_id, realm.realm.key);
+        let treasury = create_test_account(treasury_key, solana_program::system_program::id(), false, vec![]);
+        let mint = Pubkey::new_unique();
+        let treasury_token_account = create_token_account(Pubkey::new_unique(), mint, treasury_key, 500);
+        let recipient = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), false, vec![]);
+        let recipient_token_account = create_token_account(Pubkey::new_unique(), mint, *recipient.key, 0);
+        let target_program = create_program_account(Pubkey::new_unique());
+        add_treasury_mint(
+            &program_id,
+            &[realm.treasury_ledger.clone(), realm.realm.clone(), realm.authority.clone()],
+            mint,
+        )
+        .unwrap();
+        
+        let (accounts, proposal_actions) = create_passed_proposal(
+            &program_id,
+            &realm,
+            ProposalCategory::Treasury,
+            vec![
+                (0, Action::UpdateLendingPool {
+                    lending_pool: *lending_pool.key,
+                    param: LendingPoolParam::MaxLoanAmount(2_000_000),
+                }),
+                (0, Action::PauseToken { token_info: *token_info.key }),
+                (0, Action::SuspendCommunity { community: *community.key }),
+                (0, Action::TreasuryTransfer { mint: None, destination: *recipient.key, amount: 1_000 }),
+                (DAY, Action::TreasuryTransfer {
+                    mint: Some(mint),
+                    destination: *recipient_token_account.key,
+                    amount: 200,
+                }),
+                (0, Action::Invoke {
+                    program_id: *target_program.key,
+                    accounts: vec![ActionAccountMeta { pubkey: *realm.realm.key, is_signer: true, is_writable: false }],
+                    data: vec![1, 2, 3],
+                }),
+            ],
+        );
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.action_count, 6);
+        assert_eq!(proposal_data.status, ProposalStatus::Passed);
+        
+        // Actions can no longer be added once voting starts
+        let (late_action, _) = find_proposal_action_address(&program_id, accounts[0].key, 6);
+        let insert = vec![
+            accounts[0].clone(),
+            create_empty_account(late_action),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            insert_proposal_action(&program_id, &insert, 0, Action::PauseToken { token_info: *token_info.key })
+                .unwrap_err(),
+            UnityVaultError::ProposalNotDraft.into()
+        );
+        
+        // The token transfer waits a day after voting ends
+        let transfer_tokens = vec![
+            accounts[0].clone(),
+            realm.realm.clone(),
+            proposal_actions[4].clone(),
+            treasury.clone(),
+            realm.treasury_ledger.clone(),
+            treasury_token_account.clone(),
+            recipient_token_account.clone(),
+            create_program_account(spl_token::id()),
+        ];
+        assert_eq!(
+            execute_proposal(&program_id, &transfer_tokens).unwrap_err(),
+            UnityVaultError::HoldUpTimeNotElapsed.into()
+        );
+        
+        // Everything else executes right away
+        let recipient_lamports = recipient.lamports();
+        let execute = vec![
+            accounts[0].clone(),
+            realm.realm.clone(),
+            proposal_actions[0].clone(),
+            lending_pool.clone(),
+            proposal_actions[1].clone(),
+            token_info.clone(),
+            proposal_actions[2].clone(),
+            community.clone(),
+            proposal_actions[3].clone(),
+            treasury.clone(),
+            realm.treasury_ledger.clone(),
+            recipient.clone(),
+            create_program_account(solana_program::system_program::id()),
+            proposal_actions[5].clone(),
+            target_program.clone(),
+            realm.realm.clone(),
+        ];
+        execute_proposal(&program_id, &execute).unwrap();
+        assert_eq!(load_account::<LendingPool>(&lending_pool, &program_id).unwrap().max_loan_amount, 2_000_000);
+        assert_eq!(load_account::<TokenInfo>(&token_info, &program_id).unwrap().status, TokenStatus::Paused);
+        assert_eq!(load_account::<Community>(&community, &program_id).unwrap().status, CommunityStatus::Suspended);
+        assert_eq!(recipient.lamports(), recipient_lamports + 1_000);
+        assert_eq!(
+            load_account::<ProposalAction>(&proposal_actions[5], &program_id).unwrap().executed_at,
+            1_700_000_000 + 3 * DAY
+        );
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.actions_executed, 5);
+        assert_eq!(proposal_data.status, ProposalStatus::Passed);
+        
+        // Each action executes once
+        assert_eq!(
+            execute_proposal(&program_id, &execute[..4]).unwrap_err(),
+            UnityVaultError::ActionAlreadyExecuted.into()
+        );
+        
+        // The last action completes the proposal
+        set_clock(1_700_000_000 + 4 * DAY);
+        execute_proposal(&program_id, &transfer_tokens).unwrap();
+        assert_eq!(token_balance(&recipient_token_account), 200);
+        assert_eq!(token_balance(&treasury_token_account), 300);
+        assert_eq!(
+            load_account::<TreasuryLedger>(&realm.treasury_ledger, &program_id).unwrap().flows,
+            [
+                TreasuryFlow { mint: None, inflow: 0, outflow: 1_000 },
+                TreasuryFlow { mint: Some(mint), inflow: 0, outflow: 200 },
+            ]
+        );
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.status, ProposalStatus::Executed);
+        assert_eq!(proposal_data.executed_at, 1_700_000_000 + 4 * DAY);
+    }
+    
+    #[test]
+    fn test_execute_action_checks_accounts() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let [lending_pool, _, community] = create_governed_accounts(&program_id, *realm.realm.key);
+        let [_, _, other_community] = create_governed_accounts(&program_id, Pubkey::new_unique());
+        
+        let (accounts, proposal_actions) = create_passed_proposal(
+            &program_id,
+            &realm,
+            ProposalCategory::Community,
+            vec![
+                (0, Action::SuspendCommunity { community: *community.key }),
+                (0, Action::SuspendCommunity { community: *other_community.key }),
+                (0, Action::UpdateLendingPool {
+                    lending_pool: *lending_pool.key,
+                    param: LendingPoolParam::MaxLtv(9_000),
+                }),
+            ],
+        );
+        let execute = |action: &AccountInfo<'static>, target: &AccountInfo<'static>| {
+            vec![accounts[0].clone(), realm.realm.clone(), action.clone(), target.clone()]
+        };
+        
+        // Targets must be the accounts named by the action
+        assert_eq!(
+            execute_proposal(&program_id, &execute(&proposal_actions[0], &other_community)).unwrap_err(),
+            UnityVaultError::ActionAccountMismatch.into()
+        );
+        
+        // and governed by the realm
+        assert_eq!(
+            execute_proposal(&program_id, &execute(&proposal_actions[1], &other_community)).unwrap_err(),
+            UnityVaultError::ActionTargetNotGoverned.into()
+        );
+        assert_eq!(
+            load_account::<Community>(&other_community, &program_id).unwrap().status,
+            CommunityStatus::Active
+        );
+        
+        // Pool settings stay in range: a max LTV above the liquidation threshold is refused
+        assert_eq!(
+            execute_proposal(&program_id, &execute(&proposal_actions[2], &lending_pool)).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        
+        // A failed action can be retried, and the others execute meanwhile
+        execute_proposal(&program_id, &execute(&proposal_actions[0], &community)).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!((proposal_data.actions_executed, proposal_data.status), (1, ProposalStatus::Passed));
+        assert_eq!(load_account::<ProposalAction>(&proposal_actions[1], &program_id).unwrap().executed_at, 0);
+    }
+    
+    #[test]
+    fn test_invoke_cannot_drain_realm_vault() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let thief = create_token_account(Pubkey::new_unique(), *realm.governing_mint.key, Pubkey::new_unique(), 0);
+        let drain_vault = Action::Invoke {
+            program_id: spl_token::id(),
+            accounts: vec![
+                ActionAccountMeta { pubkey: *realm.token_vault.key, is_signer: false, is_writable: true },
+                ActionAccountMeta { pubkey: *thief.key, is_signer: false, is_writable: true },
+                ActionAccountMeta { pubkey: *realm.realm.key, is_signer: true, is_writable: false },
+            ],
+            data: spl_token::instruction::TokenInstruction::Transfer { amount: 10 }.pack(),
+        };
+        
+        // A proposal cannot carry an invoke that names the vault
+        let accounts = create_test_proposal(&program_id, &realm);
+        let insert = vec![
+            accounts[0].clone(),
+            create_empty_account(find_proposal_action_address(&program_id, accounts[0].key, 0).0),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            insert_proposal_action(&program_id, &insert, 0, drain_vault.clone()).unwrap_err(),
+            UnityVaultError::ActionTouchesRealmVault.into()
+        );
+        
+        // and one that names it anyway is refused at execution
+        let (accounts, proposal_actions) = create_passed_proposal(
+            &program_id,
+            &realm,
+            ProposalCategory::Community,
+            vec![(0, Action::Invoke { program_id: spl_token::id(), accounts: Vec::new(), data: Vec::new() })],
+        );
+        let mut proposal_action_data = load_account::<ProposalAction>(&proposal_actions[0], &program_id).unwrap();
+        proposal_action_data.action = drain_vault;
+        proposal_action_data.pack_into_slice(&mut proposal_actions[0].data.borrow_mut());
+        let execute = vec![
+            accounts[0].clone(),
+            realm.realm.clone(),
+            proposal_actions[0].clone(),
+            create_program_account(spl_token::id()),
+            realm.token_vault.clone(),
+            thief.clone(),
+            realm.realm.clone(),
+        ];
+        let vault_balance = token_balance(&realm.token_vault);
+        assert_eq!(
+            execute_proposal(&program_id, &execute).unwrap_err(),
+            UnityVaultError::ActionTouchesRealmVault.into()
+        );
+        assert_eq!(token_balance(&realm.token_vault), vault_balance);
+        assert_eq!(token_balance(&thief), 0);
+    }
+    
+    /// Gives `realm` a hold-up time of two days and a council of three, two
+    /// of whom can veto. Returns the council members.
+    fn configure_council<'a>(program_id: &Pubkey, realm: &TestRealm<'a>) -> Vec<AccountInfo<'a>> {
+        let council: Vec<AccountInfo> = (0..3)
+            .map(|_| create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]))
+            .collect();
+        let config = RealmConfig {
+            hold_up_time: 2 * DAY,
+            council: council.iter().map(|member| *member.key).collect(),
+            veto_threshold: 2,
+        };
+        set_realm_config(program_id, &[realm.realm.clone(), realm.authority.clone()], config).unwrap();
+        council
+    }
+    
+    #[test]
+    fn test_hold_up_time_delays_execution() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        
+        // Only the realm authority configures the realm, within bounds
+        let stranger = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let config = RealmConfig { hold_up_time: DAY, council: vec![*stranger.key], veto_threshold: 1 };
+        assert_eq!(
+            set_realm_config(&program_id, &[realm.realm.clone(), stranger.clone()], config.clone()).unwrap_err(),
+            UnityVaultError::RealmAuthorityMismatch.into()
+        );
+        let too_strict = RealmConfig { veto_threshold: 2, ..config.clone() };
+        assert_eq!(
+            set_realm_config(&program_id, &[realm.realm.clone(), realm.authority.clone()], too_strict).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        let duplicated = RealmConfig { council: vec![*stranger.key, *stranger.key], ..config };
+        assert_eq!(
+            set_realm_config(&program_id, &[realm.realm.clone(), realm.authority.clone()], duplicated).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        configure_council(&program_id, &realm);
+        assert_eq!(load_account::<Realm>(&realm.realm, &program_id).unwrap().hold_up_time, 2 * DAY);
+        
+        // Voting ends at 1_700_000_000 + 3 * DAY
+        let (accounts, _) = create_passed_proposal(&program_id, &realm, ProposalCategory::ParameterChange, Vec::new());
+        let execute = vec![accounts[0].clone(), realm.realm.clone()];
+        set_clock(1_700_000_000 + 5 * DAY - 1);
+        assert_eq!(
+            execute_proposal(&program_id, &execute).unwrap_err(),
+            UnityVaultError::HoldUpTimeNotElapsed.into()
+        );
+        
+        set_clock(1_700_000_000 + 5 * DAY);
+        execute_proposal(&program_id, &execute).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Executed);
+    }
+    
+    #[test]
+    fn test_council_veto() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let council = configure_council(&program_id, &realm);
+        let (accounts, _) = create_passed_proposal(&program_id, &realm, ProposalCategory::ParameterChange, Vec::new());
+        let veto = |vetoers: &[&AccountInfo<'static>]| {
+            let mut veto = vec![accounts[0].clone(), realm.realm.clone()];
+            veto.extend(vetoers.iter().map(|&vetoer| vetoer.clone()));
+            veto
+        };
+        
+        // One member, even signing twice, is short of the threshold
+        assert_eq!(
+            veto_proposal(&program_id, &veto(&[&council[0], &council[0]])).unwrap_err(),
+            UnityVaultError::NotEnoughVetoSigners.into()
+        );
+        
+        // Only signing council members count
+        let stranger = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        assert_eq!(
+            veto_proposal(&program_id, &veto(&[&council[0], &stranger])).unwrap_err(),
+            UnityVaultError::NotCouncilMember.into()
+        );
+        let mut unsigned = council[1].clone();
+        unsigned.is_signer = false;
+        assert_eq!(
+            veto_proposal(&program_id, &veto(&[&council[0], &unsigned])).unwrap_err(),
+            ProgramError::MissingRequiredSignature
+        );
+        
+        // Two members veto during the hold-up time, and the proposal can no
+        // longer execute
+        set_clock(1_700_000_000 + 5 * DAY - 1);
+        veto_proposal(&program_id, &veto(&[&council[0], &council[2]])).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Vetoed);
+        set_clock(1_700_000_000 + 5 * DAY);
+        assert_eq!(
+            execute_proposal(&program_id, &veto(&[])).unwrap_err(),
+            UnityVaultError::ProposalNotPassed.into()
+        );
+        
+        // Once the hold-up time is over, it is too late to veto
+        let (accounts, _) = create_passed_proposal(&program_id, &realm, ProposalCategory::ParameterChange, Vec::new());
+        let voting_ends_at = load_account::<Proposal>(&accounts[0], &program_id).unwrap().voting_ends_at().unwrap();
+        set_clock(voting_ends_at + 2 * DAY);
+        assert_eq!(
+            veto_proposal(&program_id, &[accounts[0].clone(), realm.realm.clone(), council[0].clone(), council[1].clone()])
+                .unwrap_err(),
+            UnityVaultError::VetoPeriodEnded.into()
+        );
+    }
+    
+    #[test]
+    fn test_passed_proposal_keeps_realm_config() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let council = configure_council(&program_id, &realm);
+        let (accounts, _) = create_passed_proposal(&program_id, &realm, ProposalCategory::ParameterChange, Vec::new());
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        assert_eq!(proposal_data.hold_up_time, 2 * DAY);
+        assert_eq!(proposal_data.council, council.iter().map(|member| *member.key).collect::<Vec<_>>());
+        assert_eq!(proposal_data.veto_threshold, 2);
+        
+        // The authority drops the hold-up time and disbands the council
+        let config = RealmConfig { hold_up_time: 0, council: Vec::new(), veto_threshold: 0 };
+        set_realm_config(&program_id, &[realm.realm.clone(), realm.authority.clone()], config).unwrap();
+        
+        // The proposal still waits out the hold-up time it passed with
+        let execute = vec![accounts[0].clone(), realm.realm.clone()];
+        assert_eq!(
+            execute_proposal(&program_id, &execute).unwrap_err(),
+            UnityVaultError::HoldUpTimeNotElapsed.into()
+        );
+        
+        // and the council it passed with can still veto it
+        veto_proposal(&program_id, &[accounts[0].clone(), realm.realm.clone(), council[0].clone(), council[1].clone()])
+            .unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Vetoed);
+        
+        // Proposals passed afterwards use the new settings
+        let (accounts, _) = create_passed_proposal(&program_id, &realm, ProposalCategory::ParameterChange, Vec::new());
+        execute_proposal(&program_id, &[accounts[0].clone(), realm.realm.clone()]).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Executed);
+    }
+    
+    /// Creates a draft `ballot` proposal in `realm` with an option for each
+    /// of `labels`, returning `[proposal, authority, proposal_options]`.
+    fn create_ballot_proposal<'a>(
+        program_id: &Pubkey,
+        realm: &TestRealm<'a>,
+        ballot: BallotType,
+        option_rule: OptionRule,
+        labels: &[&str],
+    ) -> Vec<AccountInfo<'a>> {
+        let proposer = create_member(program_id, realm, 10);
+        let accounts = create_proposal_accounts(program_id, realm, &proposer);
+        let params = ProposalParams {
+            ballot,
+            option_rule,
+            ..proposal_params()
+        };
+        create_proposal(program_id, &accounts, 0, params).unwrap();
+        
+        let proposal_options = create_empty_account(find_proposal_options_address(program_id, accounts[0].key).0);
+        let add = vec![accounts[0].clone(), proposal_options, accounts[4].clone(), accounts[5].clone()];
+        for label in labels {
+            add_proposal_option(program_id, &add, label.to_string()).unwrap();
+        }
+        vec![accounts[0].clone(), accounts[4].clone(), add[1].clone()]
+    }
+    
+    /// Casts `choices` for a new member holding `weight` tokens.
+    fn cast_choices<'a>(program_id: &Pubkey, realm: &TestRealm<'a>, accounts: &[AccountInfo<'a>], weight: u64, choices: &[u8]) {
+        let member = create_member(program_id, realm, weight);
+        vote_options(program_id, &option_vote_accounts(program_id, accounts, &member), choices.to_vec()).unwrap();
+    }
+    
+    /// Accounts for `vote_options`.
+    fn option_vote_accounts<'a>(program_id: &Pubkey, accounts: &[AccountInfo<'a>], member: &TestMember<'a>) -> Vec<AccountInfo<'a>> {
+        let mut vote = vote_accounts(program_id, &accounts[0], member);
+        vote.insert(1, accounts[2].clone());
+        vote
+    }
+    
+    fn option_votes(program_id: &Pubkey, proposal_options: &AccountInfo) -> Vec<u64> {
+        ProposalOptions::load(proposal_options, program_id)
+            .unwrap()
+            .options
+            .iter()
+            .map(|option| option.votes)
+            .collect()
+    }
+    
+    fn options_passed(program_id: &Pubkey, proposal_options: &AccountInfo) -> Vec<bool> {
+        ProposalOptions::load(proposal_options, program_id)
+            .unwrap()
+            .options
+            .iter()
+            .map(|option| option.passed)
+            .collect()
+    }
+    
+    #[test]
+    fn test_single_choice_ballot() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_ballot_proposal(
+            &program_id,
+            &realm,
+            BallotType::SingleChoice,
+            OptionRule::HighestAboveThreshold,
+            &["Option A"],
+        );
+        
+        // A single option is no choice, and the ballot type is fixed
+        assert_eq!(
+            activate_proposal(&program_id, &accounts).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert_eq!(
+            update_proposal(
+                &program_id,
+                &[accounts[0].clone(), accounts[1].clone(), realm.governance_config.clone()],
+                proposal_params()
+            )
+            .unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        
+        // The options account grows with each option
+        assert_eq!(accounts[2].data_len(), ProposalOptions::space(1));
+        let add = vec![
+            accounts[0].clone(),
+            accounts[2].clone(),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        add_proposal_option(&program_id, &add, "Option B".to_string()).unwrap();
+        add_proposal_option(&program_id, &add, "Option C".to_string()).unwrap();
+        assert_eq!(accounts[2].data_len(), ProposalOptions::space(3));
+        assert_eq!(
+            add_proposal_option(&program_id, &add, "x".repeat(MAX_OPTION_LABEL_LEN + 1)).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        
+        activate_proposal(&program_id, &accounts).unwrap();
+        assert_eq!(
+            add_proposal_option(&program_id, &add, "Option D".to_string()).unwrap_err(),
+            UnityVaultError::ProposalNotDraft.into()
+        );
+        
+        // Yes/no votes do not apply, and exactly one existing option must be chosen
+        let member = create_member(&program_id, &realm, 150);
+        assert_eq!(
+            vote_proposal(&program_id, &vote_accounts(&program_id, &accounts[0], &member), VoteType::Yes).unwrap_err(),
+            UnityVaultError::BallotTypeMismatch.into()
+        );
+        let vote = option_vote_accounts(&program_id, &accounts, &member);
+        for choices in [vec![], vec![0, 1], vec![3]] {
+            assert_eq!(vote_options(&program_id, &vote, choices).unwrap_err(), ProgramError::InvalidArgument);
+        }
+        vote_options(&program_id, &vote, vec![1]).unwrap();
+        assert_eq!(
+            vote_options(&program_id, &vote, vec![0]).unwrap_err(),
+            UnityVaultError::AlreadyVoted.into()
+        );
+        let vote_record_data = load_account::<VoteRecord>(&vote[3], &program_id).unwrap();
+        assert_eq!((vote_record_data.choices, vote_record_data.weight), (0b10, 150));
+        
+        cast_choices(&program_id, &realm, &accounts, 100, &[0]);
+        cast_choices(&program_id, &realm, &accounts, 50, &[1]);
+        
+        // B leads with 200 of the 300 votes cast, above the 60% threshold
+        set_clock(1_700_000_000 + 3 * DAY);
+        let mut finalize = finalize_accounts(&realm, &accounts[0]);
+        finalize.push(accounts[2].clone());
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Passed);
+        let proposal_options_data = ProposalOptions::load(&accounts[2], &program_id).unwrap();
+        let votes: Vec<u64> = proposal_options_data.options.iter().map(|option| option.votes).collect();
+        assert_eq!(votes, [100, 200, 0]);
+        assert_eq!(options_passed(&program_id, &accounts[2]), [false, true, false]);
+        
+        // A tie for the most votes passes no option
+        let accounts = create_ballot_proposal(
+            &program_id,
+            &realm,
+            BallotType::SingleChoice,
+            OptionRule::HighestAboveThreshold,
+            &["Option A", "Option B"],
+        );
+        activate_proposal(&program_id, &accounts).unwrap();
+        cast_choices(&program_id, &realm, &accounts, 150, &[0]);
+        cast_choices(&program_id, &realm, &accounts, 150, &[1]);
+        set_clock(1_700_000_000 + 6 * DAY);
+        let mut finalize = finalize_accounts(&realm, &accounts[0]);
+        finalize.push(accounts[2].clone());
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Rejected);
+        assert_eq!(options_passed(&program_id, &accounts[2]), [false, false]);
+    }
+    
+    #[test]
+    fn test_approval_ballot() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        
+        // Yes/no proposals take no options
+        let yes_no = create_test_proposal(&program_id, &realm);
+        let add = vec![
+            yes_no[0].clone(),
+            create_empty_account(find_proposal_options_address(&program_id, yes_no[0].key).0),
+            yes_no[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            add_proposal_option(&program_id, &add, "Option A".to_string()).unwrap_err(),
+            UnityVaultError::BallotTypeMismatch.into()
+        );
+        
+        let labels: Vec<String> = (0..MAX_PROPOSAL_OPTIONS).map(|index| format!("Option {}", index)).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let accounts = create_ballot_proposal(
+            &program_id,
+            &realm,
+            BallotType::Approval,
+            OptionRule::AllAboveThreshold,
+            &labels,
+        );
+        let add = vec![
+            accounts[0].clone(),
+            accounts[2].clone(),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            add_proposal_option(&program_id, &add, "One too many".to_string()).unwrap_err(),
+            UnityVaultError::ProposalOptionsFull.into()
+        );
+        
+        // Multiple-choice proposals take no actions
+        let insert = vec![
+            accounts[0].clone(),
+            create_empty_account(find_proposal_action_address(&program_id, accounts[0].key, 0).0),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            insert_proposal_action(&program_id, &insert, 0, Action::PauseToken { token_info: Pubkey::new_unique() })
+                .unwrap_err(),
+            UnityVaultError::BallotTypeMismatch.into()
+        );
+        activate_proposal(&program_id, &accounts).unwrap();
+        
+        // Choices must be distinct
+        let member = create_member(&program_id, &realm, 200);
+        let vote = option_vote_accounts(&program_id, &accounts, &member);
+        assert_eq!(
+            vote_options(&program_id, &vote, vec![0, 0]).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        vote_options(&program_id, &vote, vec![0, 1]).unwrap();
+        cast_choices(&program_id, &realm, &accounts, 100, &[0, 2]);
+        
+        // Every option approved by 60% of the 300 votes cast passes
+        set_clock(1_700_000_000 + 3 * DAY);
+        let mut finalize = finalize_accounts(&realm, &accounts[0]);
+        finalize.push(accounts[2].clone());
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().status, ProposalStatus::Passed);
+        let passed = options_passed(&program_id, &accounts[2]);
+        assert_eq!(passed[..3], [true, true, false]);
+        assert!(passed[3..].iter().all(|passed| !passed));
+    }
+    
+    #[test]
+    fn test_change_and_delegate_option_votes() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let accounts = create_ballot_proposal(
+            &program_id,
+            &realm,
+            BallotType::SingleChoice,
+            OptionRule::HighestAboveThreshold,
+            &["Option A", "Option B", "Option C"],
+        );
+        activate_proposal(&program_id, &accounts).unwrap();
+        let delegate = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        
+        let changing = create_member(&program_id, &realm, 100);
+        let changing_delegation = delegate_accounts(&program_id, &realm, &changing, &delegate);
+        delegate_votes(&program_id, &changing_delegation, 0).unwrap();
+        let overriding = create_member(&program_id, &realm, 200);
+        let overriding_delegation = delegate_accounts(&program_id, &realm, &overriding, &delegate);
+        delegate_votes(&program_id, &overriding_delegation, 0).unwrap();
+        
+        // Delegates vote on options with the choices rather than a vote type
+        let mut votes = delegate_vote_accounts(
+            &program_id,
+            &accounts[0],
+            &delegate,
+            &[(&changing_delegation[1], &changing), (&overriding_delegation[1], &overriding)],
+        );
+        assert_eq!(
+            vote_as_delegate(&program_id, &votes, VoteType::Yes).unwrap_err(),
+            UnityVaultError::BallotTypeMismatch.into()
+        );
+        votes.insert(1, accounts[2].clone());
+        assert_eq!(
+            vote_options_as_delegate(&program_id, &votes, vec![0, 1]).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        vote_options_as_delegate(&program_id, &votes, vec![0]).unwrap();
+        assert_eq!(option_votes(&program_id, &accounts[2]), [300, 0, 0]);
+        let vote_record_data = load_account::<VoteRecord>(&votes[6], &program_id).unwrap();
+        assert_eq!((vote_record_data.choices, vote_record_data.delegate), (0b1, Some(*delegate.key)));
+        
+        // A delegator who votes after their delegate replaces the delegate's choices
+        let mut overriding_vote = option_vote_accounts(&program_id, &accounts, &overriding);
+        overriding_vote[3] = votes[9].clone();
+        vote_options(&program_id, &overriding_vote, vec![1]).unwrap();
+        assert_eq!(option_votes(&program_id, &accounts[2]), [100, 200, 0]);
+        assert_eq!(load_account::<VoteRecord>(&votes[9], &program_id).unwrap().delegate, None);
+        assert_eq!(
+            vote_options(&program_id, &overriding_vote, vec![2]).unwrap_err(),
+            UnityVaultError::AlreadyVoted.into()
+        );
+        
+        // Or changes their vote, taking over the delegate's choices
+        let changing_vote = vec![accounts[0].clone(), accounts[2].clone(), changing.owner.clone(), votes[6].clone()];
+        assert_eq!(
+            change_vote(&program_id, &[accounts[0].clone(), changing.owner.clone(), votes[6].clone()], VoteType::No)
+                .unwrap_err(),
+            UnityVaultError::BallotTypeMismatch.into()
+        );
+        assert_eq!(
+            change_vote_options(&program_id, &changing_vote, vec![3]).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        change_vote_options(&program_id, &changing_vote, vec![2]).unwrap();
+        assert_eq!(option_votes(&program_id, &accounts[2]), [0, 200, 100]);
+        let vote_record_data = load_account::<VoteRecord>(&votes[6], &program_id).unwrap();
+        assert_eq!((vote_record_data.choices, vote_record_data.delegate), (0b100, None));
+        
+        // Direct votes can be changed too, until voting ends
+        let member = create_member(&program_id, &realm, 150);
+        let vote = option_vote_accounts(&program_id, &accounts, &member);
+        vote_options(&program_id, &vote, vec![0]).unwrap();
+        change_vote_options(&program_id, &vote[..4], vec![1]).unwrap();
+        assert_eq!(option_votes(&program_id, &accounts[2]), [0, 350, 100]);
+        assert_eq!(ProposalOptions::load(&accounts[2], &program_id).unwrap().total_weight, 450);
+        
+        set_clock(1_700_000_000 + 3 * DAY);
+        assert_eq!(
+            change_vote_options(&program_id, &vote[..4], vec![0]).unwrap_err(),
+            UnityVaultError::VotingPeriodEnded.into()
+        );
+        let mut finalize = finalize_accounts(&realm, &accounts[0]);
+        finalize.push(accounts[2].clone());
+        finalize_proposal(&program_id, &finalize).unwrap();
+        assert_eq!(options_passed(&program_id, &accounts[2]), [false, true, false]);
+    }
+    
+    #[test]
+    fn test_proposal_category_requirements() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let config = vec![realm.governance_config.clone(), realm.realm.clone(), realm.authority.clone()];
+        let treasury_config = CategoryConfig {
+            min_quorum_bps: 3_000,
+            min_approval_percentage: 66,
+            min_voting_duration: 2 * DAY,
+            max_voting_duration: 5 * DAY,
+            min_proposer_deposit: 100,
+        };
+        
+        // Only the realm authority sets valid requirements, once per realm
+        let mut init = config.clone();
+        init.push(create_program_account(solana_program::system_program::id()));
+        assert_eq!(
+            init_governance_config(&program_id, &init, [category_config(); PROPOSAL_CATEGORY_COUNT]).unwrap_err(),
+            ProgramError::AccountAlreadyInitialized
+        );
+        let stranger = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        assert_eq!(
+            set_category_config(
+                &program_id,
+                &[config[0].clone(), config[1].clone(), stranger],
+                ProposalCategory::Treasury,
+                treasury_config
+            )
+            .unwrap_err(),
+            UnityVaultError::RealmAuthorityMismatch.into()
+        );
+        let inverted = CategoryConfig {
+            max_voting_duration: DAY,
+            ..treasury_config
+        };
+        assert_eq!(
+            set_category_config(&program_id, &config, ProposalCategory::Treasury, inverted).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        set_category_config(&program_id, &config, ProposalCategory::Treasury, treasury_config).unwrap();
+        let governance_config_data = load_account::<GovernanceConfig>(&realm.governance_config, &program_id).unwrap();
+        assert_eq!(*governance_config_data.category(ProposalCategory::Treasury), treasury_config);
+        assert_eq!(*governance_config_data.category(ProposalCategory::Community), category_config());
+        
+        // Treasury proposals need a higher quorum and approval threshold, a
+        // voting period within bounds and a larger deposit
+        let small_holder = create_member(&program_id, &realm, 50);
+        let accounts = create_proposal_accounts(&program_id, &realm, &small_holder);
+        let treasury_params = ProposalParams {
+            category: ProposalCategory::Treasury,
+            quorum_bps: 3_000,
+            min_approval_percentage: 70,
+            ..proposal_params()
+        };
+        for params in [
+            ProposalParams { category: ProposalCategory::Treasury, ..proposal_params() },
+            ProposalParams { voting_duration: 6 * DAY, ..treasury_params.clone() },
+            ProposalParams { voting_duration: DAY, ..treasury_params.clone() },
+        ] {
+            assert_eq!(
+                create_proposal(&program_id, &accounts, 0, params).unwrap_err(),
+                UnityVaultError::CategoryRequirementsNotMet.into()
+            );
+        }
+        assert_eq!(
+            create_proposal(&program_id, &accounts, 0, treasury_params.clone()).unwrap_err(),
+            UnityVaultError::ProposerNotEligible.into()
+        );
+        
+        // A minimum quorum of zero is no longer the proposer's to choose
+        let unguarded = ProposalParams { quorum_bps: 0, ..proposal_params() };
+        assert_eq!(
+            create_proposal(&program_id, &accounts, 0, unguarded).unwrap_err(),
+            UnityVaultError::CategoryRequirementsNotMet.into()
+        );
+        create_proposal(&program_id, &accounts, 0, proposal_params()).unwrap();
+        
+        let large_holder = create_member(&program_id, &realm, 100);
+        let accounts = create_proposal_accounts(&program_id, &realm, &large_holder);
+        create_proposal(&program_id, &accounts, 0, treasury_params.clone()).unwrap();
+        assert_eq!(
+            load_account::<Proposal>(&accounts[0], &program_id).unwrap().category,
+            ProposalCategory::Treasury
+        );
+        
+        // Updates stay within the category, which is fixed
+        let update = vec![accounts[0].clone(), accounts[4].clone(), realm.governance_config.clone()];
+        let lowered = ProposalParams { quorum_bps: 2_000, ..treasury_params.clone() };
+        assert_eq!(
+            update_proposal(&program_id, &update, lowered).unwrap_err(),
+            UnityVaultError::CategoryRequirementsNotMet.into()
+        );
+        assert_eq!(
+            update_proposal(&program_id, &update, proposal_params()).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        let extended = ProposalParams { voting_duration: 5 * DAY, ..treasury_params };
+        update_proposal(&program_id, &update, extended).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().voting_duration, 5 * DAY);
+    }
+    
+    #[test]
+    fn test_proposal_text_must_fit_layout() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let proposer = create_member(&program_id, &realm, 10);
+        let long_title = || ProposalParams {
+            title: "a".repeat(101),
+            ..proposal_params()
+        };
+        
+        let accounts = create_proposal_accounts(&program_id, &realm, &proposer);
+        assert_eq!(
+            create_proposal(&program_id, &accounts, 0, long_title()).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert!(accounts[0].data_is_empty());
+        
+        create_proposal(&program_id, &accounts, 0, proposal_params()).unwrap();
+        let update = vec![accounts[0].clone(), accounts[4].clone(), realm.governance_config.clone()];
+        assert_eq!(update_proposal(&program_id, &update, long_title()).unwrap_err(), ProgramError::InvalidArgument);
+        let long_description = ProposalParams {
+            description: "a".repeat(1001),
+            ..proposal_params()
+        };
+        assert_eq!(
+            update_proposal(&program_id, &update, long_description).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        
+        let mut params = long_title();
+        params.title = "a".repeat(100);
+        update_proposal(&program_id, &update, params).unwrap();
+        assert_eq!(load_account::<Proposal>(&accounts[0], &program_id).unwrap().title.len(), 100);
+    }
+    
+    #[test]
+    fn test_proposal_edits_and_versions() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let proposer = create_member(&program_id, &realm, 10);
+        let accounts = create_proposal_accounts(&program_id, &realm, &proposer);
+        create_proposal(&program_id, &accounts, 0, proposal_params()).unwrap();
+        let update = vec![accounts[0].clone(), accounts[4].clone(), realm.governance_config.clone()];
+        let insert = vec![
+            accounts[0].clone(),
+            create_empty_account(find_proposal_action_address(&program_id, accounts[0].key, 0).0),
+            accounts[4].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        insert_proposal_action(&program_id, &insert, 0, Action::PauseToken { token_info: Pubkey::new_unique() }).unwrap();
+        
+        // Drafts can be edited
+        let revised = ProposalParams {
+            description: "Raise the maximum loan amount to 1,500,000".to_string(),
+            ..proposal_params()
+        };
+        update_proposal(&program_id, &update, revised.clone()).unwrap();
+        
+        // Activation takes every action, and hashes them with the text
+        let mut activate = vec![accounts[0].clone(), accounts[4].clone()];
+        assert_eq!(
+            activate_proposal(&program_id, &activate).unwrap_err(),
+            ProgramError::NotEnoughAccountKeys
+        );
+        activate.push(insert[1].clone());
+        activate_proposal(&program_id, &activate).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        let proposal_action_data = load_account::<ProposalAction>(&insert[1], &program_id).unwrap();
+        assert_eq!(proposal_data.description, revised.description);
+        assert_eq!(proposal_data.content_hash, proposal_data.hash_content(&[], &[proposal_action_data]).unwrap());
+        assert_ne!(proposal_data.content_hash, proposal_data.hash_content(&[], &[]).unwrap());
+        
+        // Once voting starts the content is fixed
+        assert_eq!(
+            update_proposal(&program_id, &update, proposal_params()).unwrap_err(),
+            UnityVaultError::ProposalNotDraft.into()
+        );
+        
+        // A change is a new proposal, linked to the one it supersedes
+        let mut revision = create_proposal_accounts(&program_id, &realm, &proposer);
+        revision[0] = create_empty_account(find_proposal_address(&program_id, proposer.owner.key, 1).0);
+        create_proposal(&program_id, &revision, 1, proposal_params()).unwrap();
+        let supersede = vec![accounts[0].clone(), revision[0].clone(), proposer.owner.clone()];
+        
+        let other = create_test_proposal(&program_id, &realm);
+        assert_eq!(
+            supersede_proposal(&program_id, &[other[0].clone(), revision[0].clone(), proposer.owner.clone()]).unwrap_err(),
+            UnityVaultError::ProposalAuthorityMismatch.into()
+        );
+        assert_eq!(
+            supersede_proposal(&program_id, &[accounts[0].clone(), accounts[0].clone(), proposer.owner.clone()])
+                .unwrap_err(),
+            UnityVaultError::ProposalNotDraft.into()
+        );
+        
+        supersede_proposal(&program_id, &supersede).unwrap();
+        let proposal_data = load_account::<Proposal>(&accounts[0], &program_id).unwrap();
+        let revision_data = load_account::<Proposal>(&revision[0], &program_id).unwrap();
+        assert_eq!(proposal_data.status, ProposalStatus::Cancelled);
+        assert_eq!(proposal_data.next_version, Some(*revision[0].key));
+        assert_eq!(revision_data.previous_version, Some(*accounts[0].key));
+        assert_eq!((proposal_data.version, revision_data.version), (1, 2));
+        
+        // Each version is superseded at most once
+        assert_eq!(
+            supersede_proposal(&program_id, &supersede).unwrap_err(),
+            UnityVaultError::ProposalVersionLinked.into()
+        );
+    }
+    
+    #[test]
+    fn test_treasury_deposits_and_spending() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let realm = create_realm(&program_id);
+        let (treasury_key, _) = find_treasury_address(&program_id, realm.realm.key);
+        let treasury = create_test_account(treasury_key, solana_program::system_program::id(), false, vec![]);
+        let mint = Pubkey::new_unique();
+        let treasury_token_account = create_token_account(Pubkey::new_unique(), mint, treasury_key, 0);
+        let depositor = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let depositor_token_account = create_token_account(Pubkey::new_unique(), mint, *depositor.key, 1_000);
+        
+        // The ledger is created once per realm
+        let ledger = vec![
+            realm.treasury_ledger.clone(),
+            realm.realm.clone(),
+            realm.authority.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            init_treasury_ledger(&program_id, &ledger).unwrap_err(),
+            ProgramError::AccountAlreadyInitialized
+        );
+        
+        // Anyone deposits SOL and tokens
+        let treasury_lamports = treasury.lamports();
+        let deposit_sol = vec![
+            realm.realm.clone(),
+            realm.treasury_ledger.clone(),
+            depositor.clone(),
+            treasury.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        assert_eq!(
+            deposit_to_treasury(&program_id, &deposit_sol, None, 0).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        deposit_to_treasury(&program_id, &deposit_sol, None, 5_000).unwrap();
+        deposit_to_treasury(&program_id, &deposit_sol, None, 2_000).unwrap();
+        assert_eq!(treasury.lamports(), treasury_lamports + 7_000);
+        
+        let deposit_tokens = |destination: &AccountInfo<'static>| {
+            vec![
+                realm.realm.clone(),
+                realm.treasury_ledger.clone(),
+                depositor.clone(),
+                depositor_token_account.clone(),
+                destination.clone(),
+                create_program_account(spl_token::id()),
+            ]
+        };
+        
+        // Tokens are only taken once the realm authority adds their mint
+        assert_eq!(
+            deposit_to_treasury(&program_id, &deposit_tokens(&treasury_token_account), Some(mint), 400).unwrap_err(),
+            UnityVaultError::TreasuryMintNotAllowed.into()
+        );
+        let mut add_mint = vec![realm.treasury_ledger.clone(), realm.realm.clone(), depositor.clone()];
+        assert_eq!(
+            add_treasury_mint(&program_id, &add_mint, mint).unwrap_err(),
+            UnityVaultError::RealmAuthorityMismatch.into()
+        );
+        add_mint[2] = realm.authority.clone();
+        add_treasury_mint(&program_id, &add_mint, mint).unwrap();
+        assert_eq!(add_treasury_mint(&program_id, &add_mint, mint).unwrap_err(), ProgramError::InvalidArgument);
+        let stranger_token_account = create_token_account(Pubkey::new_unique(), mint, Pubkey::new_unique(), 0);
+        assert_eq!(
+            deposit_to_treasury(&program_id, &deposit_tokens(&stranger_token_account), Some(mint), 400).unwrap_err(),
+            UnityVaultError::InvalidAccountAddress.into()
+        );
+        deposit_to_treasury(&program_id, &deposit_tokens(&treasury_token_account), Some(mint), 400).unwrap();
+        assert_eq!(token_balance(&treasury_token_account), 400);
+        assert_eq!(
+            load_account::<TreasuryLedger>(&realm.treasury_ledger, &program_id).unwrap().flows,
+            [
+                TreasuryFlow { mint: None, inflow: 7_000, outflow: 0 },
+                TreasuryFlow { mint: Some(mint), inflow: 400, outflow: 0 },
+            ]
+        );
+        
+        // SOL keeps its slot however many mints are added
+        for _ in 2..MAX_TREASURY_MINTS {
+            add_treasury_mint(&program_id, &add_mint, Pubkey::new_unique()).unwrap();
+        }
+        assert_eq!(
+            add_treasury_mint(&program_id, &add_mint, Pubkey::new_unique()).unwrap_err(),
+            UnityVaultError::TreasuryLedgerFull.into()
+        );
+        
+        // Other categories cannot spend from the treasury
+        let accounts = create_test_proposal(&program_id, &realm);
+        let insert = vec![
+            accounts[0].clone(),
+            create_empty_account(find_proposal_action_address(&program_id, accounts[0].key, 0).0),
+            accounts[1].clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        let recipient = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), false, vec![]);
+        let transfer = Action::TreasuryTransfer { mint: None, destination: *recipient.key, amount: 3_000 };
+        assert_eq!(
+            insert_proposal_action(&program_id, &insert, 0, transfer.clone()).unwrap_err(),
+            UnityVaultError::NotTreasuryProposal.into()
+        );
+        
+        // And no proposal has the treasury sign an invoked instruction, which
+        // would spend past the ledger
+        let invoke = Action::Invoke {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![ActionAccountMeta { pubkey: treasury_key, is_signer: true, is_writable: true }],
+            data: Vec::new(),
+        };
+        let proposer = create_member(&program_id, &realm, 10);
+        let create = create_proposal_accounts(&program_id, &realm, &proposer);
+        create_proposal(
+            &program_id,
+            &create,
+            0,
+            ProposalParams { category: ProposalCategory::Treasury, ..proposal_params() },
+        )
+        .unwrap();
+        for (proposal, authority) in [(&accounts[0], &accounts[1]), (&create[0], &create[4])] {
+            let insert = vec![
+                proposal.clone(),
+                create_empty_account(find_proposal_action_address(&program_id, proposal.key, 0).0),
+                authority.clone(),
+                create_program_account(solana_program::system_program::id()),
+            ];
+            assert_eq!(
+                insert_proposal_action(&program_id, &insert, 0, invoke.clone()).unwrap_err(),
+                UnityVaultError::ActionSignsForTreasury.into()
+            );
+        }
+        
+        // A Treasury proposal withdraws, and the ledger records it
+        let (accounts, proposal_actions) =
+            create_passed_proposal(&program_id, &realm, ProposalCategory::Treasury, vec![(0, transfer)]);
+        let recipient_lamports = recipient.lamports();
+        let execute = vec![
+            accounts[0].clone(),
+            realm.realm.clone(),
+            proposal_actions[0].clone(),
+            treasury.clone(),
+            realm.treasury_ledger.clone(),
+            recipient.clone(),
+            create_program_account(solana_program::system_program::id()),
+        ];
+        execute_proposal(&program_id, &execute).unwrap();
+        assert_eq!(recipient.lamports(), recipient_lamports + 3_000);
+        assert_eq!(treasury.lamports(), treasury_lamports + 4_000);
+        assert_eq!(
+            load_account::<TreasuryLedger>(&realm.treasury_ledger, &program_id).unwrap().flows[0],
+            TreasuryFlow { mint: None, inflow: 7_000, outflow: 3_000 }
+        );
+    }
+}
@@ -1,10 +1,122 @@
 use solana_program::{
     account_info::AccountInfo,
+    hash::hash,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::lending::state::LendingPoolParam;
+use crate::math::{bps_of_ceil, TryMath, BPS};
+use crate::error::UnityVaultError;
+use crate::utils::{AccountType, ProgramAccount};
+
+/// Seed for a realm PDA: `["realm", authority, governing_mint]`.
+pub const REALM_SEED: &[u8] = b"realm";
+
+/// Seed for the token account holding a realm's deposited governing tokens:
+/// `["realm_vault", realm]`.
+pub const REALM_VAULT_SEED: &[u8] = b"realm_vault";
+
+/// Seed for a realm's governance config PDA: `["governance_config", realm]`.
+pub const GOVERNANCE_CONFIG_SEED: &[u8] = b"governance_config";
+
+/// Seed for a token owner record PDA: `["token_owner_record", realm, owner]`.
+pub const TOKEN_OWNER_RECORD_SEED: &[u8] = b"token_owner_record";
+
+/// Seed for a delegation PDA: `["delegation", realm, delegator]`.
+pub const DELEGATION_SEED: &[u8] = b"delegation";
+
+/// Seed for a proposal PDA: `["proposal", authority, nonce]`.
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Seed for a vote record PDA: `["vote_record", proposal, voter]`.
+pub const VOTE_RECORD_SEED: &[u8] = b"vote_record";
+
+/// Seed for a proposal's options PDA: `["proposal_options", proposal]`.
+pub const PROPOSAL_OPTIONS_SEED: &[u8] = b"proposal_options";
+
+/// Maximum number of options on a multiple-choice ballot, one per bit of a
+/// vote record's `choices`.
+pub const MAX_PROPOSAL_OPTIONS: usize = 16;
+
+/// Maximum length of a proposal option's label.
+pub const MAX_OPTION_LABEL_LEN: usize = 50;
+
+/// Seed for a proposal action PDA: `["proposal_action", proposal, index]`.
+pub const PROPOSAL_ACTION_SEED: &[u8] = b"proposal_action";
+
+/// Seed for a realm's treasury: `["treasury", realm]`. The treasury is a
+/// system account holding the realm's SOL, and owns its SPL token accounts.
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Seed for a realm's treasury ledger PDA: `["treasury_ledger", realm]`.
+pub const TREASURY_LEDGER_SEED: &[u8] = b"treasury_ledger";
+
+/// Maximum number of assets, SOL included, a treasury ledger tracks.
+pub const MAX_TREASURY_MINTS: usize = 16;
+
+/// Number of `ProposalCategory` variants, each with its own `CategoryConfig`.
+pub const PROPOSAL_CATEGORY_COUNT: usize = 5;
+
+/// Maximum number of council members a realm can have.
+pub const MAX_COUNCIL_MEMBERS: usize = 7;
+
+/// Maximum number of accounts an `Action::Invoke` can pass to its program.
+pub const MAX_ACTION_ACCOUNTS: usize = 16;
+
+/// Maximum length of an `Action::Invoke`'s instruction data.
+pub const MAX_ACTION_DATA_LEN: usize = 512;
+
+pub fn find_proposal_address(program_id: &Pubkey, authority: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROPOSAL_SEED, authority.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_realm_address(program_id: &Pubkey, authority: &Pubkey, governing_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REALM_SEED, authority.as_ref(), governing_mint.as_ref()], program_id)
+}
+
+pub fn find_realm_vault_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REALM_VAULT_SEED, realm.as_ref()], program_id)
+}
+
+pub fn find_governance_config_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_CONFIG_SEED, realm.as_ref()], program_id)
+}
+
+pub fn find_token_owner_record_address(program_id: &Pubkey, realm: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_OWNER_RECORD_SEED, realm.as_ref(), owner.as_ref()], program_id)
+}
+
+pub fn find_delegation_address(program_id: &Pubkey, realm: &Pubkey, delegator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DELEGATION_SEED, realm.as_ref(), delegator.as_ref()], program_id)
+}
+
+pub fn find_vote_record_address(program_id: &Pubkey, proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_RECORD_SEED, proposal.as_ref(), voter.as_ref()], program_id)
+}
+
+pub fn find_proposal_options_address(program_id: &Pubkey, proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_OPTIONS_SEED, proposal.as_ref()], program_id)
+}
+
+pub fn find_proposal_action_address(program_id: &Pubkey, proposal: &Pubkey, index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROPOSAL_ACTION_SEED, proposal.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_treasury_ledger_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_LEDGER_SEED, realm.as_ref()], program_id)
+}
+
+pub fn find_treasury_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, realm.as_ref()], program_id)
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProposalStatus {
@@ -13,6 +125,19 @@ pub enum ProposalStatus {
     Passed,
     Rejected,
     Executed,
+    Cancelled,
+    Vetoed,
+}
+
+/// What a proposal is about, matching the frontend's categories. Each
+/// category has its own requirements in the realm's `GovernanceConfig`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalCategory {
+    ParameterChange,
+    AssetListing,
+    Treasury,
+    ProtocolUpgrade,
+    Community,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,52 +147,1235 @@ pub enum VoteType {
     Abstain,
 }
 
+/// How voters answer a proposal.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BallotType {
+    /// Yes, no or abstain, tallied on the proposal.
+    YesNo,
+    /// One of the proposal's options.
+    SingleChoice,
+    /// Any number of the proposal's options, each getting the voter's full
+    /// weight.
+    Approval,
+}
+
+/// Which options of a multiple-choice proposal pass. An option clears the
+/// threshold when its share of the voting weight cast reaches the proposal's
+/// `min_approval_percentage`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionRule {
+    /// The option with the most votes, if it clears the threshold. A tie for
+    /// the most votes passes no option.
+    HighestAboveThreshold,
+    /// Every option that clears the threshold.
+    AllAboveThreshold,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegationStatus {
+    Active,
+    Revoked,
+}
+
+/// An account passed to the program called by an `Action::Invoke`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActionAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A change a passed proposal makes on chain. Accounts the realm governs
+/// have the realm as their authority; the realm PDA signs the invoked
+/// instructions, and the treasury only spends through `TreasuryTransfer`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    UpdateLendingPool {
+        lending_pool: Pubkey,
+        param: LendingPoolParam,
+    },
+    PauseToken {
+        token_info: Pubkey,
+    },
+    /// Sends `amount` lamports to the `destination` wallet, or with a `mint`,
+    /// `amount` tokens from a treasury token account to the `destination`
+    /// token account. Only `Treasury` proposals can spend from the treasury.
+    TreasuryTransfer {
+        mint: Option<Pubkey>,
+        destination: Pubkey,
+        amount: u64,
+    },
+    SuspendCommunity {
+        community: Pubkey,
+    },
+    Invoke {
+        program_id: Pubkey,
+        accounts: Vec<ActionAccountMeta>,
+        data: Vec<u8>,
+    },
+}
+
+/// Settings the realm authority controls.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RealmConfig {
+    /// Seconds after voting ends before a passed proposal can execute.
+    pub hold_up_time: i64,
+    pub council: Vec<Pubkey>,
+    /// Council signatures needed to veto a passed proposal during its
+    /// hold-up time; 0 disables the veto.
+    pub veto_threshold: u8,
+}
+
+/// Requirements a proposal in one category must meet.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CategoryConfig {
+    /// Lowest `quorum_bps` a proposal may set.
+    pub min_quorum_bps: u64,
+    /// Lowest `min_approval_percentage` a proposal may set.
+    pub min_approval_percentage: u8,
+    /// Bounds on a proposal's `voting_duration`, in seconds.
+    pub min_voting_duration: i64,
+    pub max_voting_duration: i64,
+    /// Governing tokens the proposer must have deposited in the realm.
+    pub min_proposer_deposit: u64,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ProposalParams {
     pub title: String,
     pub description: String,
     pub voting_duration: i64,
-    pub min_votes: u32,
+    /// Votes required, in basis points of the governing mint's supply.
+    pub quorum_bps: u64,
     pub min_approval_percentage: u8,
+    /// Fixed at creation.
+    pub category: ProposalCategory,
+    /// Fixed at creation.
+    pub ballot: BallotType,
+    /// Ignored on yes/no ballots.
+    pub option_rule: OptionRule,
+}
+
+/// Binds proposals to a governing token mint. Voting power is the amount of
+/// that token a voter has deposited into the realm's vault.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Realm {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub authority: Pubkey,
+    pub governing_mint: Pubkey,
+    pub token_vault: Pubkey,
+    /// Seconds after voting ends before a passed proposal can execute.
+    pub hold_up_time: i64,
+    /// Signers who can veto passed proposals during their hold-up time.
+    pub council: Vec<Pubkey>,
+    /// Council signatures needed to veto; 0 disables the veto.
+    pub veto_threshold: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Per-category proposal requirements of a realm, set by the realm
+/// authority.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct GovernanceConfig {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub realm: Pubkey,
+    /// Indexed by `ProposalCategory`.
+    pub categories: [CategoryConfig; PROPOSAL_CATEGORY_COUNT],
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Running totals of one asset moved through a realm's treasury.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct TreasuryFlow {
+    /// The SPL mint, or `None` for SOL.
+    pub mint: Option<Pubkey>,
+    pub inflow: u64,
+    pub outflow: u64,
+}
+
+/// What has been deposited into and spent from a realm's treasury, per
+/// asset. Only transfers made through the program are recorded, and only of
+/// SOL and the mints the realm authority has added.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct TreasuryLedger {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub realm: Pubkey,
+    /// SOL first, then each mint in the order it was added.
+    pub flows: Vec<TreasuryFlow>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Governing tokens a wallet has deposited into a realm. Deposits stay locked
+/// while any of the owner's votes are unrelinquished, so the same tokens
+/// cannot be moved to another wallet and vote again.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct TokenOwnerRecord {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub deposited_amount: u64,
+    pub outstanding_votes: u32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A wallet's grant of its voting power in a realm to a delegate. Each
+/// delegator has at most one delegation per realm.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Delegation {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub realm: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub status: DelegationStatus,
+    /// When the delegation lapses; 0 if it never does.
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// One choice on a multiple-choice ballot.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalOption {
+    pub label: String,
+    pub votes: u64,
+    /// Set when the proposal is finalized.
+    pub passed: bool,
+}
+
+/// The options of a multiple-choice proposal and their tallies. The account
+/// grows by one option at a time while the proposal is a draft, so unlike
+/// fixed-size accounts it is not `Pack`.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ProposalOptions {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub proposal: Pubkey,
+    /// Voting weight of everyone who voted, counted once per voter however
+    /// many options they chose.
+    pub total_weight: u64,
+    pub options: Vec<ProposalOption>,
+}
+
+/// One action of a proposal, executed once the proposal passes and its
+/// hold-up time has elapsed. Actions execute independently, so one that
+/// fails can be retried without repeating the others.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ProposalAction {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub proposal: Pubkey,
+    pub index: u16,
+    /// Seconds after voting ends before the action can execute.
+    pub hold_up_time: i64,
+    pub action: Action,
+    /// Set when the action executes; 0 until then.
+    pub executed_at: i64,
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Proposal {
     pub is_initialized: bool,
+    pub bump: u8,
     pub authority: Pubkey,
+    pub realm: Pubkey,
+    pub category: ProposalCategory,
     pub title: String,
     pub description: String,
     pub status: ProposalStatus,
     pub voting_duration: i64,
-    pub min_votes: u32,
+    /// Set when the proposal is activated; 0 while it is a draft.
+    pub voting_starts_at: i64,
+    pub quorum_bps: u64,
     pub min_approval_percentage: u8,
-    pub yes_votes: u32,
-    pub no_votes: u32,
-    pub abstain_votes: u32,
+    pub ballot: BallotType,
+    pub option_rule: OptionRule,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub abstain_votes: u64,
+    /// Number of `ProposalAction`s inserted; also the index of the next one.
+    pub action_count: u16,
+    pub actions_executed: u16,
+    /// SHA-256 of the proposal's content, recorded when it is activated; all
+    /// zeros while it is a draft.
+    pub content_hash: [u8; 32],
+    /// 1 for a new proposal, one more than the previous version's for a
+    /// revision.
+    pub version: u16,
+    /// The proposal this one revises.
+    pub previous_version: Option<Pubkey>,
+    /// The proposal that revises this one.
+    pub next_version: Option<Pubkey>,
+    /// The realm's hold-up time, council and veto threshold when the
+    /// proposal passed; execution and vetoes use these, not the realm's
+    /// current settings.
+    pub hold_up_time: i64,
+    pub council: Vec<Pubkey>,
+    pub veto_threshold: u8,
     pub created_at: i64,
     pub updated_at: i64,
     pub executed_at: i64,
 }
 
+/// A voter's ballot on one proposal. Its address makes it unique per
+/// (proposal, voter), so each voter is counted once.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct VoteRecord {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub vote_type: VoteType,
+    /// Options voted for on a multiple-choice ballot, one bit per option
+    /// index; 0 on yes/no ballots. Multiple-choice votes are recorded as
+    /// `VoteType::Yes` for the chosen options.
+    pub choices: u16,
+    pub weight: u64,
+    /// The delegate who cast this vote for the voter, if the voter did not
+    /// vote directly.
+    pub delegate: Option<Pubkey>,
+    /// When the vote was cast or last changed.
+    pub timestamp: i64,
+}
+
+impl ProposalParams {
+    /// Checks that the title and description fit the proposal layout.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.title.len() > 100 || self.description.len() > 1000 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
 impl Proposal {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // authority
+        32 + // realm
+        1 + // category
         4 + 100 + // title (max 100 chars)
         4 + 1000 + // description (max 1000 chars)
         1 + // status
         8 + // voting_duration
-        4 + // min_votes
+        8 + // voting_starts_at
+        8 + // quorum_bps
         1 + // min_approval_percentage
-        4 + // yes_votes
-        4 + // no_votes
-        4 + // abstain_votes
+        1 + // ballot
+        1 + // option_rule
+        8 + // yes_votes
+        8 + // no_votes
+        8 + // abstain_votes
+        2 + // action_count
+        2 + // actions_executed
+        32 + // content_hash
+        2 + // version
+        1 + 32 + // previous_version
+        1 + 32 + // next_version
+        8 + // hold_up_time
+        4 + MAX_COUNCIL_MEMBERS * 32 + // council
+        1 + // veto_threshold
         8 + // created_at
         8 + // updated_at
         8; // executed_at
+    
+    /// Hashes the title, description, option labels and actions (with their
+    /// hold-up times) as Borsh, whose length prefixes keep distinct contents
+    /// from serializing alike.
+    pub fn hash_content(&self, option_labels: &[String], actions: &[ProposalAction]) -> Result<[u8; 32], ProgramError> {
+        let actions: Vec<(i64, &Action)> = actions.iter().map(|action| (action.hold_up_time, &action.action)).collect();
+        let content = borsh::to_vec(&(&self.title, &self.description, option_labels, actions))
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(hash(&content).to_bytes())
+    }
+    
+    pub fn voting_ends_at(&self) -> Result<i64, ProgramError> {
+        self.voting_starts_at.try_add(self.voting_duration)
+    }
+    
+    /// Records the realm's hold-up time and veto council on the proposal.
+    pub fn snapshot_realm_config(&mut self, realm: &Realm) {
+        self.hold_up_time = realm.hold_up_time;
+        self.council = realm.council.clone();
+        self.veto_threshold = realm.veto_threshold;
+    }
+    
+    pub fn is_council_member(&self, key: &Pubkey) -> bool {
+        self.council.contains(key)
+    }
+    
+    /// Adds `weight` votes to the tally for `vote_type`.
+    pub fn add_vote(&mut self, vote_type: VoteType, weight: u64) -> Result<(), ProgramError> {
+        let tally = self.tally_mut(vote_type);
+        *tally = tally.try_add(weight)?;
+        Ok(())
+    }
+    
+    /// Takes `weight` votes back out of the tally for `vote_type`.
+    pub fn remove_vote(&mut self, vote_type: VoteType, weight: u64) -> Result<(), ProgramError> {
+        let tally = self.tally_mut(vote_type);
+        *tally = tally.try_sub(weight)?;
+        Ok(())
+    }
+    
+    fn tally_mut(&mut self, vote_type: VoteType) -> &mut u64 {
+        match vote_type {
+            VoteType::Yes => &mut self.yes_votes,
+            VoteType::No => &mut self.no_votes,
+            VoteType::Abstain => &mut self.abstain_votes,
+        }
+    }
+    
+    /// Whether votes reached quorum out of `supply` governing tokens and
+    /// enough of them approve.
+    pub fn is_approved(&self, supply: u64) -> Result<bool, ProgramError> {
+        let total_votes = self.yes_votes
+            .try_add(self.no_votes)?
+            .try_add(self.abstain_votes)?;
+        let quorum = bps_of_ceil(supply, self.quorum_bps)?;
+        
+        // yes / total >= min% without dividing: yes * 100 >= min% * total
+        let approved = (self.yes_votes as u128).try_mul(100)?
+            >= (self.min_approval_percentage as u128).try_mul(total_votes as u128)?;
+        
+        Ok(total_votes >= quorum && approved)
+    }
+}
+
+impl Action {
+    /// Largest Borsh encoding of an action, which is an `Invoke` with the
+    /// maximum accounts and data.
+    pub const MAX_LEN: usize = 1 + // variant
+        32 + // program_id
+        4 + MAX_ACTION_ACCOUNTS * (32 + 1 + 1) + // accounts
+        4 + MAX_ACTION_DATA_LEN; // data
+    
+    /// Checks that the action is well formed and fits its account.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        match self {
+            Action::TreasuryTransfer { amount, .. } if *amount == 0 => Err(ProgramError::InvalidArgument),
+            Action::Invoke { accounts, data, .. }
+                if accounts.len() > MAX_ACTION_ACCOUNTS || data.len() > MAX_ACTION_DATA_LEN =>
+            {
+                Err(ProgramError::InvalidArgument)
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Number of accounts executing the action takes after the proposal
+    /// action account itself.
+    pub fn account_count(&self) -> usize {
+        match self {
+            Action::UpdateLendingPool { .. } | Action::PauseToken { .. } | Action::SuspendCommunity { .. } => 1,
+            // treasury, treasury ledger, destination, system program
+            Action::TreasuryTransfer { mint: None, .. } => 4,
+            // treasury, treasury ledger, source token account, destination,
+            // token program
+            Action::TreasuryTransfer { mint: Some(_), .. } => 5,
+            // program, then the instruction's accounts
+            Action::Invoke { accounts, .. } => 1 + accounts.len(),
+        }
+    }
+}
+
+impl ProposalOptions {
+    pub const HEADER_LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // proposal
+        8 + // total_weight
+        4; // options count
+    
+    pub const OPTION_LEN: usize = 4 + MAX_OPTION_LABEL_LEN + // label (max 50 chars)
+        8 + // votes
+        1; // passed
+    
+    /// Account size holding `option_count` options.
+    pub fn space(option_count: usize) -> usize {
+        Self::HEADER_LEN + option_count * Self::OPTION_LEN
+    }
+    
+    /// Loads the options from `account`, checking that it is owned by this
+    /// program, carries the `ProposalOptions` discriminator and is sized for
+    /// the options it holds.
+    pub fn load(account: &AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError> {
+        if account.owner != program_id {
+            return Err(UnityVaultError::InvalidAccountOwner.into());
+        }
+        
+        let data = account.data.borrow();
+        if data.len() < Self::HEADER_LEN {
+            return Err(UnityVaultError::InvalidAccountSize.into());
+        }
+        if data[0] != AccountType::ProposalOptions as u8 {
+            return Err(UnityVaultError::InvalidAccountType.into());
+        }
+        let option_count = u32::from_le_bytes(data[Self::HEADER_LEN - 4..Self::HEADER_LEN].try_into().unwrap()) as usize;
+        if option_count > MAX_PROPOSAL_OPTIONS || data.len() != Self::space(option_count) {
+            return Err(UnityVaultError::InvalidAccountSize.into());
+        }
+        
+        let options = Self::unpack_from_slice(&data)?;
+        if !options.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(options)
+    }
+    
+    /// Adds `weight` votes to each option whose bit is set in `choices`.
+    pub fn add_vote(&mut self, choices: u16, weight: u64) -> Result<(), ProgramError> {
+        for (index, option) in self.options.iter_mut().enumerate() {
+            if choices & (1 << index) != 0 {
+                option.votes = option.votes.try_add(weight)?;
+            }
+        }
+        self.total_weight = self.total_weight.try_add(weight)?;
+        Ok(())
+    }
+    
+    /// Takes `weight` votes back out of each option whose bit is set in
+    /// `choices`.
+    pub fn remove_vote(&mut self, choices: u16, weight: u64) -> Result<(), ProgramError> {
+        for (index, option) in self.options.iter_mut().enumerate() {
+            if choices & (1 << index) != 0 {
+                option.votes = option.votes.try_sub(weight)?;
+            }
+        }
+        self.total_weight = self.total_weight.try_sub(weight)?;
+        Ok(())
+    }
+
+    /// Marks the options that pass under `rule` and returns whether any did.
+    /// None pass unless the weight cast reached quorum out of `supply`.
+    pub fn finalize(
+        &mut self,
+        supply: u64,
+        quorum_bps: u64,
+        min_approval_percentage: u8,
+        rule: OptionRule,
+    ) -> Result<bool, ProgramError> {
+        if self.total_weight < bps_of_ceil(supply, quorum_bps)? {
+            return Ok(false);
+        }
+        
+        // votes / total >= min% without dividing: votes * 100 >= min% * total
+        let threshold = (min_approval_percentage as u128).try_mul(self.total_weight as u128)?;
+        let mut above_threshold = Vec::with_capacity(self.options.len());
+        for option in &self.options {
+            above_threshold.push((option.votes as u128).try_mul(100)? >= threshold);
+        }
+        
+        match rule {
+            OptionRule::HighestAboveThreshold => {
+                let most_votes = self.options.iter().map(|option| option.votes).max().unwrap_or(0);
+                let mut leaders = self.options.iter().enumerate().filter(|(_, option)| option.votes == most_votes);
+                if let (Some((index, _)), None) = (leaders.next(), leaders.next()) {
+                    self.options[index].passed = above_threshold[index];
+                }
+            }
+            OptionRule::AllAboveThreshold => {
+                for (option, passed) in self.options.iter_mut().zip(above_threshold) {
+                    option.passed = passed;
+                }
+            }
+        }
+        
+        Ok(self.options.iter().any(|option| option.passed))
+    }
+    
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = AccountType::ProposalOptions as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack proposal
+        dst[offset..offset + 32].copy_from_slice(&self.proposal.to_bytes());
+        offset += 32;
+        
+        // Pack total_weight
+        dst[offset..offset + 8].copy_from_slice(&self.total_weight.to_le_bytes());
+        offset += 8;
+        
+        // Pack options, each label zero-padded to its maximum
+        dst[offset..offset + 4].copy_from_slice(&(self.options.len() as u32).to_le_bytes());
+        offset += 4;
+        for option in &self.options {
+            let label_bytes = option.label.as_bytes();
+            dst[offset..offset + 4].copy_from_slice(&(label_bytes.len() as u32).to_le_bytes());
+            offset += 4;
+            dst[offset..offset + label_bytes.len()].copy_from_slice(label_bytes);
+            dst[offset + label_bytes.len()..offset + MAX_OPTION_LABEL_LEN].fill(0);
+            offset += MAX_OPTION_LABEL_LEN;
+            dst[offset..offset + 8].copy_from_slice(&option.votes.to_le_bytes());
+            offset += 8;
+            dst[offset] = option.passed as u8;
+            offset += 1;
+        }
+    }
+    
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte and size are checked by load
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack proposal
+        let proposal_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let proposal = Pubkey::from(proposal_bytes);
+        offset += 32;
+        
+        // Unpack total_weight
+        let total_weight = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack options
+        let option_count = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        let mut options = Vec::with_capacity(option_count);
+        for _ in 0..option_count {
+            let label_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+            if label_len > MAX_OPTION_LABEL_LEN {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let label = String::from_utf8(src[offset..offset + label_len].to_vec())
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+            offset += MAX_OPTION_LABEL_LEN;
+            let votes = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            let passed = src[offset] != 0;
+            offset += 1;
+            options.push(ProposalOption { label, votes, passed });
+        }
+        
+        Ok(ProposalOptions {
+            is_initialized,
+            bump,
+            proposal,
+            total_weight,
+            options,
+        })
+    }
+}
+
+impl ProposalAction {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // proposal
+        2 + // index
+        8 + // hold_up_time
+        4 + Action::MAX_LEN + // action
+        8; // executed_at
+}
+
+impl Realm {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // authority
+        32 + // governing_mint
+        32 + // token_vault
+        8 + // hold_up_time
+        4 + MAX_COUNCIL_MEMBERS * 32 + // council
+        1 + // veto_threshold
+        8 + // created_at
+        8; // updated_at
+    
+    pub fn is_council_member(&self, key: &Pubkey) -> bool {
+        self.council.contains(key)
+    }
+    
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            REALM_SEED,
+            self.authority.as_ref(),
+            self.governing_mint.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl CategoryConfig {
+    pub const LEN: usize = 8 + // min_quorum_bps
+        1 + // min_approval_percentage
+        8 + // min_voting_duration
+        8 + // max_voting_duration
+        8; // min_proposer_deposit
+    
+    pub fn is_valid(&self) -> bool {
+        self.min_quorum_bps <= BPS
+            && self.min_approval_percentage <= 100
+            && self.min_voting_duration > 0
+            && self.min_voting_duration <= self.max_voting_duration
+    }
+    
+    /// Whether a proposal with `params` meets these requirements.
+    pub fn permits(&self, params: &ProposalParams) -> bool {
+        params.quorum_bps >= self.min_quorum_bps
+            && params.min_approval_percentage >= self.min_approval_percentage
+            && params.voting_duration >= self.min_voting_duration
+            && params.voting_duration <= self.max_voting_duration
+    }
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // realm
+        PROPOSAL_CATEGORY_COUNT * CategoryConfig::LEN + // categories
+        8 + // created_at
+        8; // updated_at
+    
+    pub fn category(&self, category: ProposalCategory) -> &CategoryConfig {
+        &self.categories[category as usize]
+    }
+}
+
+impl TreasuryLedger {
+    const FLOW_LEN: usize = 1 + 32 + // mint
+        8 + // inflow
+        8; // outflow
+    
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // realm
+        4 + MAX_TREASURY_MINTS * Self::FLOW_LEN + // flows
+        8 + // created_at
+        8; // updated_at
+    
+    /// Starts tracking `mint`, so the treasury can take deposits of it and
+    /// spend it.
+    pub fn add_mint(&mut self, mint: Pubkey) -> Result<(), ProgramError> {
+        if self.flows.iter().any(|flow| flow.mint == Some(mint)) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if self.flows.len() >= MAX_TREASURY_MINTS {
+            return Err(UnityVaultError::TreasuryLedgerFull.into());
+        }
+        self.flows.push(TreasuryFlow { mint: Some(mint), inflow: 0, outflow: 0 });
+        Ok(())
+    }
+    
+    /// The totals for `mint`, which must have been added.
+    fn flow_mut(&mut self, mint: Option<Pubkey>) -> Result<&mut TreasuryFlow, ProgramError> {
+        self.flows
+            .iter_mut()
+            .find(|flow| flow.mint == mint)
+            .ok_or_else(|| UnityVaultError::TreasuryMintNotAllowed.into())
+    }
+    
+    pub fn record_inflow(&mut self, mint: Option<Pubkey>, amount: u64) -> Result<(), ProgramError> {
+        let flow = self.flow_mut(mint)?;
+        flow.inflow = flow.inflow.try_add(amount)?;
+        Ok(())
+    }
+    
+    pub fn record_outflow(&mut self, mint: Option<Pubkey>, amount: u64) -> Result<(), ProgramError> {
+        let flow = self.flow_mut(mint)?;
+        flow.outflow = flow.outflow.try_add(amount)?;
+        Ok(())
+    }
+}
+
+impl TokenOwnerRecord {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // realm
+        32 + // owner
+        8 + // deposited_amount
+        4 + // outstanding_votes
+        8 + // created_at
+        8; // updated_at
+}
+
+impl Sealed for Realm {}
+
+impl ProgramAccount for Realm {
+    const ACCOUNT_TYPE: AccountType = AccountType::Realm;
+}
+
+impl IsInitialized for Realm {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Realm {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack authority, governing_mint and token_vault
+        dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.governing_mint.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.token_vault.to_bytes());
+        offset += 32;
+        
+        // Pack hold_up_time
+        dst[offset..offset + 8].copy_from_slice(&self.hold_up_time.to_le_bytes());
+        offset += 8;
+        
+        // Pack council
+        dst[offset..offset + 4].copy_from_slice(&(self.council.len() as u32).to_le_bytes());
+        offset += 4;
+        for member in &self.council {
+            dst[offset..offset + 32].copy_from_slice(&member.to_bytes());
+            offset += 32;
+        }
+        dst[offset..offset + (MAX_COUNCIL_MEMBERS - self.council.len()) * 32].fill(0);
+        offset += (MAX_COUNCIL_MEMBERS - self.council.len()) * 32;
+        
+        // Pack veto_threshold
+        dst[offset] = self.veto_threshold;
+        offset += 1;
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack authority, governing_mint and token_vault
+        let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let authority = Pubkey::from(authority_bytes);
+        offset += 32;
+        let governing_mint_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let governing_mint = Pubkey::from(governing_mint_bytes);
+        offset += 32;
+        let token_vault_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let token_vault = Pubkey::from(token_vault_bytes);
+        offset += 32;
+        
+        // Unpack hold_up_time
+        let hold_up_time = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack council
+        let council_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        if council_len > MAX_COUNCIL_MEMBERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut council = Vec::with_capacity(council_len);
+        for _ in 0..council_len {
+            let member_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+            council.push(Pubkey::from(member_bytes));
+            offset += 32;
+        }
+        offset += (MAX_COUNCIL_MEMBERS - council_len) * 32;
+        
+        // Unpack veto_threshold
+        let veto_threshold = src[offset];
+        offset += 1;
+        
+        // Unpack timestamps
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(Realm {
+            is_initialized,
+            bump,
+            authority,
+            governing_mint,
+            token_vault,
+            hold_up_time,
+            council,
+            veto_threshold,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl Sealed for GovernanceConfig {}
+
+impl ProgramAccount for GovernanceConfig {
+    const ACCOUNT_TYPE: AccountType = AccountType::GovernanceConfig;
+}
+
+impl IsInitialized for GovernanceConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for GovernanceConfig {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack realm
+        dst[offset..offset + 32].copy_from_slice(&self.realm.to_bytes());
+        offset += 32;
+        
+        // Pack categories
+        for category in &self.categories {
+            dst[offset..offset + 8].copy_from_slice(&category.min_quorum_bps.to_le_bytes());
+            offset += 8;
+            dst[offset] = category.min_approval_percentage;
+            offset += 1;
+            dst[offset..offset + 8].copy_from_slice(&category.min_voting_duration.to_le_bytes());
+            offset += 8;
+            dst[offset..offset + 8].copy_from_slice(&category.max_voting_duration.to_le_bytes());
+            offset += 8;
+            dst[offset..offset + 8].copy_from_slice(&category.min_proposer_deposit.to_le_bytes());
+            offset += 8;
+        }
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack realm
+        let realm_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let realm = Pubkey::from(realm_bytes);
+        offset += 32;
+        
+        // Unpack categories
+        let mut categories = [CategoryConfig::default(); PROPOSAL_CATEGORY_COUNT];
+        for category in &mut categories {
+            category.min_quorum_bps = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            category.min_approval_percentage = src[offset];
+            offset += 1;
+            category.min_voting_duration = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            category.max_voting_duration = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            category.min_proposer_deposit = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+        }
+        
+        // Unpack timestamps
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(GovernanceConfig {
+            is_initialized,
+            bump,
+            realm,
+            categories,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl Sealed for TreasuryLedger {}
+
+impl ProgramAccount for TreasuryLedger {
+    const ACCOUNT_TYPE: AccountType = AccountType::TreasuryLedger;
+}
+
+impl IsInitialized for TreasuryLedger {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for TreasuryLedger {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack realm
+        dst[offset..offset + 32].copy_from_slice(&self.realm.to_bytes());
+        offset += 32;
+        
+        // Pack flows
+        dst[offset..offset + 4].copy_from_slice(&(self.flows.len() as u32).to_le_bytes());
+        offset += 4;
+        for flow in &self.flows {
+            match flow.mint {
+                Some(mint) => {
+                    dst[offset] = 1;
+                    dst[offset + 1..offset + 33].copy_from_slice(&mint.to_bytes());
+                }
+                None => {
+                    dst[offset] = 0;
+                    dst[offset + 1..offset + 33].fill(0);
+                }
+            }
+            offset += 33;
+            dst[offset..offset + 8].copy_from_slice(&flow.inflow.to_le_bytes());
+            offset += 8;
+            dst[offset..offset + 8].copy_from_slice(&flow.outflow.to_le_bytes());
+            offset += 8;
+        }
+        dst[offset..offset + (MAX_TREASURY_MINTS - self.flows.len()) * Self::FLOW_LEN].fill(0);
+        offset += (MAX_TREASURY_MINTS - self.flows.len()) * Self::FLOW_LEN;
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack realm
+        let realm_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let realm = Pubkey::from(realm_bytes);
+        offset += 32;
+        
+        // Unpack flows
+        let flows_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        if flows_len > MAX_TREASURY_MINTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut flows = Vec::with_capacity(flows_len);
+        for _ in 0..flows_len {
+            let mint = match src[offset] {
+                0 => None,
+                1 => Some(Pubkey::from(<[u8; 32]>::try_from(&src[offset + 1..offset + 33]).unwrap())),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            offset += 33;
+            let inflow = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            let outflow = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+            flows.push(TreasuryFlow { mint, inflow, outflow });
+        }
+        offset += (MAX_TREASURY_MINTS - flows_len) * Self::FLOW_LEN;
+        
+        // Unpack timestamps
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(TreasuryLedger {
+            is_initialized,
+            bump,
+            realm,
+            flows,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl Sealed for TokenOwnerRecord {}
+
+impl ProgramAccount for TokenOwnerRecord {
+    const ACCOUNT_TYPE: AccountType = AccountType::TokenOwnerRecord;
+}
+
+impl IsInitialized for TokenOwnerRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for TokenOwnerRecord {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack realm and owner
+        dst[offset..offset + 32].copy_from_slice(&self.realm.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.owner.to_bytes());
+        offset += 32;
+        
+        // Pack deposited_amount
+        dst[offset..offset + 8].copy_from_slice(&self.deposited_amount.to_le_bytes());
+        offset += 8;
+        
+        // Pack outstanding_votes
+        dst[offset..offset + 4].copy_from_slice(&self.outstanding_votes.to_le_bytes());
+        offset += 4;
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack realm and owner
+        let realm_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let realm = Pubkey::from(realm_bytes);
+        offset += 32;
+        let owner_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let owner = Pubkey::from(owner_bytes);
+        offset += 32;
+        
+        // Unpack deposited_amount
+        let deposited_amount = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack outstanding_votes
+        let outstanding_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        
+        // Unpack timestamps
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(TokenOwnerRecord {
+            is_initialized,
+            bump,
+            realm,
+            owner,
+            deposited_amount,
+            outstanding_votes,
+            created_at,
+            updated_at,
+        })
+    }
 }
 
 impl Sealed for Proposal {}
 
+impl ProgramAccount for Proposal {
+    const ACCOUNT_TYPE: AccountType = AccountType::Proposal;
+}
+
 impl IsInitialized for Proposal {
     fn is_initialized(&self) -> bool {
         self.is_initialized
@@ -80,14 +1388,30 @@ impl Pack for Proposal {
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         // Pack is_initialized
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
         // Pack authority
         dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
         offset += 32;
         
+        // Pack realm
+        dst[offset..offset + 32].copy_from_slice(&self.realm.to_bytes());
+        offset += 32;
+        
+        // Pack category
+        dst[offset] = self.category as u8;
+        offset += 1;
+        
         // Pack title
         let title_bytes = self.title.as_bytes();
         dst[offset..offset + 4].copy_from_slice(&(title_bytes.len() as u32).to_le_bytes());
@@ -110,21 +1434,76 @@ impl Pack for Proposal {
         dst[offset..offset + 8].copy_from_slice(&self.voting_duration.to_le_bytes());
         offset += 8;
         
-        // Pack min_votes
-        dst[offset..offset + 4].copy_from_slice(&self.min_votes.to_le_bytes());
-        offset += 4;
+        // Pack voting_starts_at
+        dst[offset..offset + 8].copy_from_slice(&self.voting_starts_at.to_le_bytes());
+        offset += 8;
+        
+        // Pack quorum_bps
+        dst[offset..offset + 8].copy_from_slice(&self.quorum_bps.to_le_bytes());
+        offset += 8;
         
         // Pack min_approval_percentage
         dst[offset] = self.min_approval_percentage;
         offset += 1;
         
+        // Pack ballot and option_rule
+        dst[offset] = self.ballot as u8;
+        offset += 1;
+        dst[offset] = self.option_rule as u8;
+        offset += 1;
+        
         // Pack votes
-        dst[offset..offset + 4].copy_from_slice(&self.yes_votes.to_le_bytes());
-        offset += 4;
-        dst[offset..offset + 4].copy_from_slice(&self.no_votes.to_le_bytes());
-        offset += 4;
-        dst[offset..offset + 4].copy_from_slice(&self.abstain_votes.to_le_bytes());
+        dst[offset..offset + 8].copy_from_slice(&self.yes_votes.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.no_votes.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.abstain_votes.to_le_bytes());
+        offset += 8;
+        
+        // Pack action counts
+        dst[offset..offset + 2].copy_from_slice(&self.action_count.to_le_bytes());
+        offset += 2;
+        dst[offset..offset + 2].copy_from_slice(&self.actions_executed.to_le_bytes());
+        offset += 2;
+        
+        // Pack content_hash and version
+        dst[offset..offset + 32].copy_from_slice(&self.content_hash);
+        offset += 32;
+        dst[offset..offset + 2].copy_from_slice(&self.version.to_le_bytes());
+        offset += 2;
+        
+        // Pack version links
+        for link in [self.previous_version, self.next_version] {
+            match link {
+                Some(key) => {
+                    dst[offset] = 1;
+                    dst[offset + 1..offset + 33].copy_from_slice(&key.to_bytes());
+                }
+                None => {
+                    dst[offset] = 0;
+                    dst[offset + 1..offset + 33].fill(0);
+                }
+            }
+            offset += 33;
+        }
+        
+        // Pack hold_up_time
+        dst[offset..offset + 8].copy_from_slice(&self.hold_up_time.to_le_bytes());
+        offset += 8;
+        
+        // Pack council
+        dst[offset..offset + 4].copy_from_slice(&(self.council.len() as u32).to_le_bytes());
         offset += 4;
+        for member in &self.council {
+            dst[offset..offset + 32].copy_from_slice(&member.to_bytes());
+            offset += 32;
+        }
+        dst[offset..offset + (MAX_COUNCIL_MEMBERS - self.council.len()) * 32].fill(0);
+        offset += (MAX_COUNCIL_MEMBERS - self.council.len()) * 32;
+        
+        // Pack veto_threshold
+        dst[offset] = self.veto_threshold;
+        offset += 1;
         
         // Pack timestamps
         dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
@@ -135,18 +1514,40 @@ impl Pack for Proposal {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         // Unpack is_initialized
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
         // Unpack authority
         let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| ProgramError::InvalidAccountData)?;
         let authority = Pubkey::from(authority_bytes);
         offset += 32;
         
+        // Unpack realm
+        let realm_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let realm = Pubkey::from(realm_bytes);
+        offset += 32;
+        
+        // Unpack category
+        let category = match src[offset] {
+            0 => ProposalCategory::ParameterChange,
+            1 => ProposalCategory::AssetListing,
+            2 => ProposalCategory::Treasury,
+            3 => ProposalCategory::ProtocolUpgrade,
+            4 => ProposalCategory::Community,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        
         // Unpack title
         let title_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
         offset += 4;
@@ -168,6 +1569,8 @@ impl Pack for Proposal {
             2 => ProposalStatus::Passed,
             3 => ProposalStatus::Rejected,
             4 => ProposalStatus::Executed,
+            5 => ProposalStatus::Cancelled,
+            6 => ProposalStatus::Vetoed,
             _ => return Err(ProgramError::InvalidAccountData),
         };
         offset += 1;
@@ -176,21 +1579,87 @@ impl Pack for Proposal {
         let voting_duration = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
-        // Unpack min_votes
-        let min_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
+        // Unpack voting_starts_at
+        let voting_starts_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack quorum_bps
+        let quorum_bps = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
         
         // Unpack min_approval_percentage
         let min_approval_percentage = src[offset];
         offset += 1;
         
+        // Unpack ballot and option_rule
+        let ballot = match src[offset] {
+            0 => BallotType::YesNo,
+            1 => BallotType::SingleChoice,
+            2 => BallotType::Approval,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        let option_rule = match src[offset] {
+            0 => OptionRule::HighestAboveThreshold,
+            1 => OptionRule::AllAboveThreshold,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        
         // Unpack votes
-        let yes_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        let no_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        let abstain_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
+        let yes_votes = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let no_votes = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let abstain_votes = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack action counts
+        let action_count = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        let actions_executed = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
+        // Unpack content_hash and version
+        let content_hash: [u8; 32] = src[offset..offset + 32].try_into().unwrap();
+        offset += 32;
+        let version = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
+        // Unpack version links
+        let mut links = [None; 2];
+        for link in &mut links {
+            *link = match src[offset] {
+                0 => None,
+                1 => Some(Pubkey::from(<[u8; 32]>::try_from(&src[offset + 1..offset + 33]).unwrap())),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            offset += 33;
+        }
+        let [previous_version, next_version] = links;
+        
+        // Unpack hold_up_time
+        let hold_up_time = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack council
+        let council_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
         offset += 4;
+        if council_len > MAX_COUNCIL_MEMBERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut council = Vec::with_capacity(council_len);
+        for _ in 0..council_len {
+            let member_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+            council.push(Pubkey::from(member_bytes));
+            offset += 32;
+        }
+        offset += (MAX_COUNCIL_MEMBERS - council_len) * 32;
+        
+        // Unpack veto_threshold
+        let veto_threshold = src[offset];
+        offset += 1;
         
         // Unpack timestamps
         let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
@@ -201,25 +1670,404 @@ impl Pack for Proposal {
         
         Ok(Proposal {
             is_initialized,
+            bump,
             authority,
+            realm,
+            category,
             title,
             description,
             status,
             voting_duration,
-            min_votes,
+            voting_starts_at,
+            quorum_bps,
             min_approval_percentage,
+            ballot,
+            option_rule,
             yes_votes,
             no_votes,
             abstain_votes,
+            action_count,
+            actions_executed,
+            content_hash,
+            version,
+            previous_version,
+            next_version,
+            hold_up_time,
+            council,
+            veto_threshold,
             created_at,
             updated_at,
             executed_at,
         })
     }
+} 
+
+impl VoteRecord {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // proposal
+        32 + // voter
+        1 + // vote_type
+        2 + // choices
+        8 + // weight
+        1 + 32 + // delegate
+        8; // timestamp
+}
+
+impl Sealed for VoteRecord {}
+
+impl ProgramAccount for VoteRecord {
+    const ACCOUNT_TYPE: AccountType = AccountType::VoteRecord;
+}
+
+impl IsInitialized for VoteRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for VoteRecord {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack proposal and voter
+        dst[offset..offset + 32].copy_from_slice(&self.proposal.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.voter.to_bytes());
+        offset += 32;
+        
+        // Pack vote_type
+        dst[offset] = self.vote_type as u8;
+        offset += 1;
+        
+        // Pack choices
+        dst[offset..offset + 2].copy_from_slice(&self.choices.to_le_bytes());
+        offset += 2;
+        
+        // Pack weight
+        dst[offset..offset + 8].copy_from_slice(&self.weight.to_le_bytes());
+        offset += 8;
+        
+        // Pack delegate
+        match self.delegate {
+            Some(key) => {
+                dst[offset] = 1;
+                dst[offset + 1..offset + 33].copy_from_slice(&key.to_bytes());
+            }
+            None => {
+                dst[offset] = 0;
+                dst[offset + 1..offset + 33].fill(0);
+            }
+        }
+        offset += 33;
+        
+        // Pack timestamp
+        dst[offset..offset + 8].copy_from_slice(&self.timestamp.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack proposal and voter
+        let proposal_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let proposal = Pubkey::from(proposal_bytes);
+        offset += 32;
+        let voter_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let voter = Pubkey::from(voter_bytes);
+        offset += 32;
+        
+        // Unpack vote_type
+        let vote_type = match src[offset] {
+            0 => VoteType::Yes,
+            1 => VoteType::No,
+            2 => VoteType::Abstain,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        
+        // Unpack choices
+        let choices = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
+        // Unpack weight
+        let weight = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack delegate
+        let delegate = match src[offset] {
+            0 => None,
+            1 => Some(Pubkey::from(<[u8; 32]>::try_from(&src[offset + 1..offset + 33]).unwrap())),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 33;
+        
+        // Unpack timestamp
+        let timestamp = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(VoteRecord {
+            is_initialized,
+            bump,
+            proposal,
+            voter,
+            vote_type,
+            choices,
+            weight,
+            delegate,
+            timestamp,
+        })
+    }
 }
 
-impl Proposal {
-    pub fn unpack_unchecked(data: &[u8]) -> Result<Self, ProgramError> {
-        Self::unpack_from_slice(data)
+impl Delegation {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // realm
+        32 + // delegator
+        32 + // delegate
+        1 + // status
+        8 + // expires_at
+        8 + // created_at
+        8; // updated_at
+    
+    /// Whether the delegate may vote with the delegator's power at `now`.
+    pub fn is_active(&self, now: i64) -> bool {
+        self.status == DelegationStatus::Active && (self.expires_at == 0 || now < self.expires_at)
     }
-} 
\ No newline at end of file
+}
+
+impl Sealed for Delegation {}
+
+impl ProgramAccount for Delegation {
+    const ACCOUNT_TYPE: AccountType = AccountType::Delegation;
+}
+
+impl IsInitialized for Delegation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Delegation {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack realm, delegator and delegate
+        dst[offset..offset + 32].copy_from_slice(&self.realm.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.delegator.to_bytes());
+        offset += 32;
+        dst[offset..offset + 32].copy_from_slice(&self.delegate.to_bytes());
+        offset += 32;
+        
+        // Pack status
+        dst[offset] = self.status as u8;
+        offset += 1;
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.expires_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack realm, delegator and delegate
+        let realm_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let realm = Pubkey::from(realm_bytes);
+        offset += 32;
+        let delegator_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let delegator = Pubkey::from(delegator_bytes);
+        offset += 32;
+        let delegate_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let delegate = Pubkey::from(delegate_bytes);
+        offset += 32;
+        
+        // Unpack status
+        let status = match src[offset] {
+            0 => DelegationStatus::Active,
+            1 => DelegationStatus::Revoked,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        
+        // Unpack timestamps
+        let expires_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(Delegation {
+            is_initialized,
+            bump,
+            realm,
+            delegator,
+            delegate,
+            status,
+            expires_at,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl Sealed for ProposalAction {}
+
+impl ProgramAccount for ProposalAction {
+    const ACCOUNT_TYPE: AccountType = AccountType::ProposalAction;
+}
+
+impl IsInitialized for ProposalAction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProposalAction {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack proposal
+        dst[offset..offset + 32].copy_from_slice(&self.proposal.to_bytes());
+        offset += 32;
+        
+        // Pack index
+        dst[offset..offset + 2].copy_from_slice(&self.index.to_le_bytes());
+        offset += 2;
+        
+        // Pack hold_up_time
+        dst[offset..offset + 8].copy_from_slice(&self.hold_up_time.to_le_bytes());
+        offset += 8;
+        
+        // Pack action as length-prefixed Borsh, zero-padded to its maximum
+        let action_bytes = borsh::to_vec(&self.action).unwrap();
+        dst[offset..offset + 4].copy_from_slice(&(action_bytes.len() as u32).to_le_bytes());
+        offset += 4;
+        dst[offset..offset + action_bytes.len()].copy_from_slice(&action_bytes);
+        dst[offset + action_bytes.len()..offset + Action::MAX_LEN].fill(0);
+        offset += Action::MAX_LEN;
+        
+        // Pack executed_at
+        dst[offset..offset + 8].copy_from_slice(&self.executed_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack proposal
+        let proposal_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let proposal = Pubkey::from(proposal_bytes);
+        offset += 32;
+        
+        // Unpack index
+        let index = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
+        // Unpack hold_up_time
+        let hold_up_time = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack action
+        let action_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        if action_len > Action::MAX_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let action = Action::try_from_slice(&src[offset..offset + action_len])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        offset += Action::MAX_LEN;
+        
+        // Unpack executed_at
+        let executed_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(ProposalAction {
+            is_initialized,
+            bump,
+            proposal,
+            index,
+            hold_up_time,
+            action,
+            executed_at,
+        })
+    }
+}
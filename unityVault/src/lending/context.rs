@@ -4,16 +4,23 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::Sysvar,
 };
-use std::str::FromStr;
-use crate::lending::state::{LendingPool, Loan};
+use crate::error::UnityVaultError;
+use crate::utils::load_account;
+use crate::lending::state::{
+    find_credit_record_address, find_interest_rate_history_address, CreditRecord, LendingPool, Loan,
+};
+use crate::user::state::find_user_profile_address;
 
 pub struct InitLendingPoolContext<'a> {
     pub lending_pool: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
     pub token_mint: &'a AccountInfo<'a>,
     pub token_vault: &'a AccountInfo<'a>,
+    pub share_mint: &'a AccountInfo<'a>,
+    pub collateral_mint: &'a AccountInfo<'a>,
+    pub collateral_vault: &'a AccountInfo<'a>,
+    pub interest_rate_history: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
     pub token_program: &'a AccountInfo<'a>,
     pub rent: &'a AccountInfo<'a>,
@@ -23,21 +30,87 @@ pub struct CreateLoanContext<'a> {
     pub loan: &'a AccountInfo<'a>,
     pub lending_pool: &'a AccountInfo<'a>,
     pub borrower: &'a AccountInfo<'a>,
+    pub borrower_profile: &'a AccountInfo<'a>,
+    pub credit_record: &'a AccountInfo<'a>,
+    pub token_vault: &'a AccountInfo<'a>,
+    pub borrower_token_account: &'a AccountInfo<'a>,
+    pub collateral_vault: &'a AccountInfo<'a>,
+    pub borrower_collateral_account: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
 }
 
 pub struct RepayLoanContext<'a> {
     pub loan: &'a AccountInfo<'a>,
     pub lending_pool: &'a AccountInfo<'a>,
     pub borrower: &'a AccountInfo<'a>,
+    pub credit_record: &'a AccountInfo<'a>,
+    pub borrower_token_account: &'a AccountInfo<'a>,
+    pub token_vault: &'a AccountInfo<'a>,
+    pub borrower_collateral_account: &'a AccountInfo<'a>,
+    pub collateral_vault: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct DepositContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub depositor: &'a AccountInfo<'a>,
+    pub depositor_token_account: &'a AccountInfo<'a>,
+    pub token_vault: &'a AccountInfo<'a>,
+    pub share_mint: &'a AccountInfo<'a>,
+    pub depositor_share_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct WithdrawContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub withdrawer: &'a AccountInfo<'a>,
+    pub withdrawer_share_account: &'a AccountInfo<'a>,
+    pub share_mint: &'a AccountInfo<'a>,
+    pub token_vault: &'a AccountInfo<'a>,
+    pub withdrawer_token_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct MarkDefaultedContext<'a> {
+    pub loan: &'a AccountInfo<'a>,
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub credit_record: &'a AccountInfo<'a>,
+}
+
+pub struct UpdateInterestRateModelContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+    pub interest_rate_history: &'a AccountInfo<'a>,
+}
+
+pub struct RecordInterestRateContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub interest_rate_history: &'a AccountInfo<'a>,
+}
+
+pub struct AccrueInterestContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+}
+
+pub struct LiquidateLoanContext<'a> {
+    pub loan: &'a AccountInfo<'a>,
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub liquidation_event: &'a AccountInfo<'a>,
+    pub liquidator: &'a AccountInfo<'a>,
+    pub liquidator_token_account: &'a AccountInfo<'a>,
+    pub liquidator_collateral_account: &'a AccountInfo<'a>,
+    pub token_vault: &'a AccountInfo<'a>,
+    pub collateral_vault: &'a AccountInfo<'a>,
+    pub borrower_collateral_account: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
     pub token_program: &'a AccountInfo<'a>,
 }
 
 impl<'a> InitLendingPoolContext<'a> {
     pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify lending pool is not initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if lending_pool_data.is_initialized {
+        // Verify lending pool has not been created yet
+        if !self.lending_pool.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
         
@@ -52,26 +125,37 @@ impl<'a> InitLendingPoolContext<'a> {
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
+        if self.token_program.key != &spl_token::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
+        // Verify token and collateral mints are SPL mints
+        if self.token_mint.owner != &spl_token::id() || self.collateral_mint.owner != &spl_token::id() {
+            return Err(ProgramError::IllegalOwner);
+        }
+        
         Ok(())
     }
 }
 
 impl<'a> CreateLoanContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify loan account is not initialized
-        let loan_data = Loan::unpack_unchecked(&self.loan.data.borrow())?;
-        if loan_data.is_initialized {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify loan account has not been created yet
+        if !self.loan.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
         
-        // Verify lending pool is initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if !lending_pool_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify token vault is the pool's vault
+        if lending_pool_data.token_vault != *self.token_vault.key {
+            return Err(UnityVaultError::TokenVaultMismatch.into());
+        }
+        
+        // Verify collateral vault is the pool's collateral vault
+        if lending_pool_data.collateral_vault != *self.collateral_vault.key {
+            return Err(UnityVaultError::CollateralVaultMismatch.into());
         }
         
         // Verify borrower is signer
@@ -79,27 +163,51 @@ impl<'a> CreateLoanContext<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
         
+        // Verify borrower profile is the borrower's profile PDA
+        if find_user_profile_address(program_id, self.borrower.key).0 != *self.borrower_profile.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
+        // Verify credit record is the borrower's credit record PDA
+        if find_credit_record_address(program_id, self.borrower.key).0 != *self.credit_record.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
         // Verify system program
         if self.system_program.key != &solana_program::system_program::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
+        // Verify token program
+        if self.token_program.key != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
         Ok(())
     }
 }
 
 impl<'a> RepayLoanContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify loan is initialized
-        let loan_data = Loan::unpack_unchecked(&self.loan.data.borrow())?;
-        if !loan_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify loan is an initialized Loan owned by this program
+        let loan_data = load_account::<Loan>(self.loan, program_id)?;
+        
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify loan belongs to the lending pool
+        if loan_data.lending_pool != *self.lending_pool.key {
+            return Err(UnityVaultError::LoanPoolMismatch.into());
+        }
+        
+        // Verify token vault is the pool's vault
+        if lending_pool_data.token_vault != *self.token_vault.key {
+            return Err(UnityVaultError::TokenVaultMismatch.into());
         }
         
-        // Verify lending pool is initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if !lending_pool_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
+        // Verify collateral vault is the pool's collateral vault
+        if lending_pool_data.collateral_vault != *self.collateral_vault.key {
+            return Err(UnityVaultError::CollateralVaultMismatch.into());
         }
         
         // Verify borrower is signer
@@ -109,14 +217,206 @@ impl<'a> RepayLoanContext<'a> {
         
         // Verify borrower matches loan
         if loan_data.borrower != *self.borrower.key {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(UnityVaultError::LoanBorrowerMismatch.into());
+        }
+        
+        // Verify credit record is the borrower's
+        let credit_record_data = load_account::<CreditRecord>(self.credit_record, program_id)?;
+        if credit_record_data.borrower != loan_data.borrower {
+            return Err(UnityVaultError::LoanBorrowerMismatch.into());
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
+        if self.token_program.key != &spl_token::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+impl<'a> DepositContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify token vault is the pool's vault
+        if lending_pool_data.token_vault != *self.token_vault.key {
+            return Err(UnityVaultError::TokenVaultMismatch.into());
+        }
+        
+        // Verify share mint is the pool's share mint
+        if lending_pool_data.share_mint != *self.share_mint.key {
+            return Err(UnityVaultError::ShareMintMismatch.into());
+        }
+        
+        // Verify depositor is signer
+        if !self.depositor.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Verify token program
+        if self.token_program.key != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'a> WithdrawContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify token vault is the pool's vault
+        if lending_pool_data.token_vault != *self.token_vault.key {
+            return Err(UnityVaultError::TokenVaultMismatch.into());
+        }
+        
+        // Verify share mint is the pool's share mint
+        if lending_pool_data.share_mint != *self.share_mint.key {
+            return Err(UnityVaultError::ShareMintMismatch.into());
+        }
+        
+        // Verify withdrawer is signer
+        if !self.withdrawer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Verify token program
+        if self.token_program.key != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'a> MarkDefaultedContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify loan is an initialized Loan owned by this program
+        let loan_data = load_account::<Loan>(self.loan, program_id)?;
+        
+        // Verify lending pool is an initialized LendingPool owned by this program
+        load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify loan belongs to the lending pool
+        if loan_data.lending_pool != *self.lending_pool.key {
+            return Err(UnityVaultError::LoanPoolMismatch.into());
+        }
+        
+        // Verify credit record is the borrower's
+        let credit_record_data = load_account::<CreditRecord>(self.credit_record, program_id)?;
+        if credit_record_data.borrower != loan_data.borrower {
+            return Err(UnityVaultError::LoanBorrowerMismatch.into());
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'a> UpdateInterestRateModelContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify authority is signer
+        if !self.authority.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Verify authority matches lending pool
+        if lending_pool_data.authority != *self.authority.key {
+            return Err(UnityVaultError::LendingPoolAuthorityMismatch.into());
+        }
+        
+        // Verify interest rate history is the pool's history PDA
+        if find_interest_rate_history_address(program_id, self.lending_pool.key).0 != *self.interest_rate_history.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'a> RecordInterestRateContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify lending pool is an initialized LendingPool owned by this program
+        load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify interest rate history is the pool's history PDA
+        if find_interest_rate_history_address(program_id, self.lending_pool.key).0 != *self.interest_rate_history.key {
+            return Err(UnityVaultError::InvalidAccountAddress.into());
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'a> AccrueInterestContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify lending pool is an initialized LendingPool owned by this program
+        load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        Ok(())
+    }
+}
+
+impl<'a> LiquidateLoanContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify loan is an initialized Loan owned by this program
+        let loan_data = load_account::<Loan>(self.loan, program_id)?;
+        
+        // Verify lending pool is an initialized LendingPool owned by this program
+        let lending_pool_data = load_account::<LendingPool>(self.lending_pool, program_id)?;
+        
+        // Verify loan belongs to the lending pool
+        if loan_data.lending_pool != *self.lending_pool.key {
+            return Err(UnityVaultError::LoanPoolMismatch.into());
+        }
+        
+        // Verify token vault is the pool's vault
+        if lending_pool_data.token_vault != *self.token_vault.key {
+            return Err(UnityVaultError::TokenVaultMismatch.into());
+        }
+        
+        // Verify collateral vault is the pool's collateral vault
+        if lending_pool_data.collateral_vault != *self.collateral_vault.key {
+            return Err(UnityVaultError::CollateralVaultMismatch.into());
+        }
+        
+        // Verify excess collateral goes back to the borrower
+        if self.borrower_collateral_account.owner != &spl_token::id() {
+            return Err(UnityVaultError::InvalidAccountOwner.into());
+        }
+        let borrower_collateral_data = spl_token::state::Account::unpack(&self.borrower_collateral_account.data.borrow())?;
+        if borrower_collateral_data.owner != loan_data.borrower
+            || borrower_collateral_data.mint != lending_pool_data.collateral_mint
+        {
+            return Err(UnityVaultError::BorrowerCollateralAccountMismatch.into());
+        }
+        
+        // Verify liquidation event has not been created yet
+        if !self.liquidation_event.data_is_empty() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        
+        // Verify liquidator is signer
+        if !self.liquidator.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Verify system program
+        if self.system_program.key != &solana_program::system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        // Verify token program
+        if self.token_program.key != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(())
+    }
+}
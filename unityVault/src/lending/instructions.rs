@@ -2,16 +2,35 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
     entrypoint::ProgramResult,
+    program::invoke,
+    program::invoke_signed,
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::Sysvar,
 };
+use spl_token::state::Mint;
+use crate::error::UnityVaultError;
 use crate::lending::{
-    state::{LendingPool, LendingPoolParams, Loan, LoanParams, LoanStatus},
-    context::{InitLendingPoolContext, CreateLoanContext, RepayLoanContext},
+    state::{
+        find_collateral_vault_address, find_credit_record_address, find_interest_rate_history_address,
+        find_lending_pool_address, find_liquidation_event_address, find_loan_address,
+        find_share_mint_address, CreditRecord, InterestRateHistory, InterestRateModel, LendingPool,
+        LendingPoolParams, LiquidationEvent, LiquidationReason, Loan, LoanParams, LoanStatus,
+        COLLATERAL_VAULT_SEED, CREDIT_RECORD_SEED, INTEREST_RATE_HISTORY_SEED, LENDING_POOL_SEED,
+        LIQUIDATION_EVENT_SEED, LOAN_SEED, SHARE_MINT_SEED,
+    },
+    context::{
+        InitLendingPoolContext, CreateLoanContext, RepayLoanContext, DepositContext, WithdrawContext,
+        MarkDefaultedContext, UpdateInterestRateModelContext, RecordInterestRateContext,
+        AccrueInterestContext, LiquidateLoanContext,
+    },
 };
-use std::str::FromStr;
+use crate::math::{bps_of_floor, mul_div_floor, to_u64, Decimal, TryMath, BPS};
+use crate::user::state::{KycStatus, UserProfile, UserStatus};
+use crate::utils::{create_pda_account, load_account};
 
 pub fn init_lending_pool<'a>(
     program_id: &Pubkey,
@@ -25,6 +44,10 @@ pub fn init_lending_pool<'a>(
         authority: next_account_info(account_info_iter)?,
         token_mint: next_account_info(account_info_iter)?,
         token_vault: next_account_info(account_info_iter)?,
+        share_mint: next_account_info(account_info_iter)?,
+        collateral_mint: next_account_info(account_info_iter)?,
+        collateral_vault: next_account_info(account_info_iter)?,
+        interest_rate_history: next_account_info(account_info_iter)?,
         system_program: next_account_info(account_info_iter)?,
         token_program: next_account_info(account_info_iter)?,
         rent: next_account_info(account_info_iter)?,
@@ -32,22 +55,170 @@ pub fn init_lending_pool<'a>(
     
     context.validate(program_id)?;
     
+    // Liquidation must kick in at or above the origination LTV, and both are
+    // at most 100%
+    if params.grace_period < 0
+        || params.collateral_price == 0
+        || params.max_ltv > params.liquidation_threshold
+        || params.liquidation_threshold > BPS
+        || params.liquidation_bonus > BPS
+        || params.credit_tier_limits.iter().any(|&limit| limit > BPS)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    params.interest_rate_model.validate()?;
+    
+    // Verify lending pool is the PDA for this authority and mint
+    let (lending_pool_key, bump) = find_lending_pool_address(
+        program_id,
+        context.authority.key,
+        context.token_mint.key,
+    );
+    if lending_pool_key != *context.lending_pool.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Verify token vault holds the pool's mint and is owned by the pool PDA,
+    // so only this program can move funds out of it
+    if context.token_vault.owner != &spl_token::id() {
+        return Err(UnityVaultError::TokenVaultMismatch.into());
+    }
+    let token_vault_data = spl_token::state::Account::unpack(&context.token_vault.data.borrow())?;
+    if token_vault_data.mint != *context.token_mint.key || token_vault_data.owner != lending_pool_key {
+        return Err(UnityVaultError::TokenVaultMismatch.into());
+    }
+    
+    // Verify share mint is the pool's share mint PDA
+    let (share_mint_key, share_mint_bump) = find_share_mint_address(program_id, &lending_pool_key);
+    if share_mint_key != *context.share_mint.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the share mint, owned by the token program
+    invoke_signed(
+        &system_instruction::create_account(
+            context.authority.key,
+            context.share_mint.key,
+            Rent::get()?.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[context.authority.clone(), context.share_mint.clone(), context.system_program.clone()],
+        &[&[SHARE_MINT_SEED, lending_pool_key.as_ref(), &[share_mint_bump]]],
+    )?;
+    
+    // Initialize the share mint with the pool as mint authority and the
+    // decimals of the underlying token
+    let token_mint_data = Mint::unpack(&context.token_mint.data.borrow())?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            context.share_mint.key,
+            &lending_pool_key,
+            None,
+            token_mint_data.decimals,
+        )?,
+        std::slice::from_ref(context.share_mint),
+    )?;
+    
+    // Verify collateral vault is the pool's collateral vault PDA
+    let (collateral_vault_key, collateral_vault_bump) = find_collateral_vault_address(program_id, &lending_pool_key);
+    if collateral_vault_key != *context.collateral_vault.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the collateral vault as a token account owned by the pool PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            context.authority.key,
+            context.collateral_vault.key,
+            Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[context.authority.clone(), context.collateral_vault.clone(), context.system_program.clone()],
+        &[&[COLLATERAL_VAULT_SEED, lending_pool_key.as_ref(), &[collateral_vault_bump]]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            context.collateral_vault.key,
+            context.collateral_mint.key,
+            &lending_pool_key,
+        )?,
+        &[context.collateral_vault.clone(), context.collateral_mint.clone()],
+    )?;
+    
+    // Create the lending pool account
+    create_pda_account(
+        context.authority,
+        context.lending_pool,
+        context.system_program,
+        program_id,
+        LendingPool::LEN,
+        &[
+            LENDING_POOL_SEED,
+            context.authority.key.as_ref(),
+            context.token_mint.key.as_ref(),
+            &[bump],
+        ],
+    )?;
+    
     let clock = Clock::get()?;
     let lending_pool_data = LendingPool {
         is_initialized: true,
+        bump,
         authority: *context.authority.key,
         token_mint: *context.token_mint.key,
         token_vault: *context.token_vault.key,
-        interest_rate: params.interest_rate,
+        share_mint: share_mint_key,
+        collateral_mint: *context.collateral_mint.key,
+        collateral_vault: collateral_vault_key,
+        collateral_price: params.collateral_price,
+        max_ltv: params.max_ltv,
+        liquidation_threshold: params.liquidation_threshold,
+        liquidation_bonus: params.liquidation_bonus,
+        require_verified_borrower: params.require_verified_borrower,
+        credit_tier_limits: params.credit_tier_limits,
+        interest_rate_model: params.interest_rate_model,
         max_loan_amount: params.max_loan_amount,
         min_loan_amount: params.min_loan_amount,
+        grace_period: params.grace_period,
         total_borrowed: 0,
         total_deposited: 0,
+        total_bad_debt: 0,
+        borrow_index: Decimal::ONE,
+        last_accrual_ts: clock.unix_timestamp,
         created_at: clock.unix_timestamp,
         updated_at: clock.unix_timestamp,
     };
     
+    // Create the interest rate history, starting from the pool's initial rate
+    let (interest_rate_history_key, interest_rate_history_bump) =
+        find_interest_rate_history_address(program_id, &lending_pool_key);
+    if interest_rate_history_key != *context.interest_rate_history.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    create_pda_account(
+        context.authority,
+        context.interest_rate_history,
+        context.system_program,
+        program_id,
+        InterestRateHistory::LEN,
+        &[INTEREST_RATE_HISTORY_SEED, lending_pool_key.as_ref(), &[interest_rate_history_bump]],
+    )?;
+    
+    let mut interest_rate_history_data = InterestRateHistory {
+        is_initialized: true,
+        bump: interest_rate_history_bump,
+        lending_pool: lending_pool_key,
+        next_index: 0,
+        snapshots: Vec::new(),
+    };
+    interest_rate_history_data.record(lending_pool_data.rate_snapshot(clock.unix_timestamp)?);
+    
     LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    InterestRateHistory::pack(interest_rate_history_data, &mut context.interest_rate_history.data.borrow_mut())?;
     
     Ok(())
 }
@@ -63,48 +234,194 @@ pub fn create_loan<'a>(
         loan: next_account_info(account_info_iter)?,
         lending_pool: next_account_info(account_info_iter)?,
         borrower: next_account_info(account_info_iter)?,
+        borrower_profile: next_account_info(account_info_iter)?,
+        credit_record: next_account_info(account_info_iter)?,
+        token_vault: next_account_info(account_info_iter)?,
+        borrower_token_account: next_account_info(account_info_iter)?,
+        collateral_vault: next_account_info(account_info_iter)?,
+        borrower_collateral_account: next_account_info(account_info_iter)?,
         system_program: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
     };
     
     context.validate(program_id)?;
     
-    let lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    
+    // Borrowers without a profile may only use pools that do not require one
+    if context.borrower_profile.data_is_empty() {
+        if lending_pool_data.require_verified_borrower {
+            return Err(UnityVaultError::BorrowerProfileRequired.into());
+        }
+    } else {
+        let borrower_profile_data = load_account::<UserProfile>(context.borrower_profile, program_id)?;
+        
+        // Verify the borrower's second factor co-signed, if they have one
+        borrower_profile_data.check_second_factor(account_info_iter.next())?;
+        
+        if borrower_profile_data.status != UserStatus::Active {
+            return Err(UnityVaultError::BorrowerNotActive.into());
+        }
+        if lending_pool_data.require_verified_borrower
+            && borrower_profile_data.effective_kyc_status(clock.unix_timestamp) != KycStatus::Verified
+        {
+            return Err(UnityVaultError::BorrowerNotVerified.into());
+        }
+    }
+    
+    // Create the borrower's credit record on their first loan
+    if context.credit_record.data_is_empty() {
+        let (_, credit_record_bump) = find_credit_record_address(program_id, context.borrower.key);
+        create_pda_account(
+            context.borrower,
+            context.credit_record,
+            context.system_program,
+            program_id,
+            CreditRecord::LEN,
+            &[CREDIT_RECORD_SEED, context.borrower.key.as_ref(), &[credit_record_bump]],
+        )?;
+        let credit_record_data = CreditRecord {
+            is_initialized: true,
+            bump: credit_record_bump,
+            borrower: *context.borrower.key,
+            loans_repaid: 0,
+            loans_defaulted: 0,
+            created_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+        };
+        CreditRecord::pack(credit_record_data, &mut context.credit_record.data.borrow_mut())?;
+    }
+    let credit_record_data = load_account::<CreditRecord>(context.credit_record, program_id)?;
     
     // Validate loan amount
-    if params.amount < lending_pool_data.min_loan_amount || params.amount > lending_pool_data.max_loan_amount {
-        return Err(ProgramError::InvalidArgument);
+    if params.amount < lending_pool_data.min_loan_amount {
+        return Err(UnityVaultError::LoanAmountBelowMinimum.into());
+    }
+    if params.amount > lending_pool_data.max_loan_amount {
+        return Err(UnityVaultError::LoanAmountAboveMaximum.into());
     }
     
-    let clock = Clock::get()?;
+    // Validate loan amount against the borrower's credit tier
+    let credit_limit = lending_pool_data.credit_limit(credit_record_data.tier())?;
+    if credit_limit == 0 {
+        return Err(UnityVaultError::CreditTierNotAccepted.into());
+    }
+    if params.amount > credit_limit {
+        return Err(UnityVaultError::LoanAboveCreditLimit.into());
+    }
+    if params.amount > lending_pool_data.available_liquidity() {
+        return Err(UnityVaultError::InsufficientLiquidity.into());
+    }
+    
+    // Validate loan-to-value against the posted collateral
+    let max_borrow = bps_of_floor(
+        lending_pool_data.collateral_value(params.collateral_amount)?,
+        lending_pool_data.max_ltv,
+    )?;
+    if params.amount > max_borrow {
+        return Err(UnityVaultError::LoanToValueTooHigh.into());
+    }
+    
+    // Verify loan is the PDA for this pool, borrower and nonce
+    let (loan_key, bump) = find_loan_address(
+        program_id,
+        context.lending_pool.key,
+        context.borrower.key,
+        params.nonce,
+    );
+    if loan_key != *context.loan.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the loan account
+    create_pda_account(
+        context.borrower,
+        context.loan,
+        context.system_program,
+        program_id,
+        Loan::LEN,
+        &[
+            LOAN_SEED,
+            context.lending_pool.key.as_ref(),
+            context.borrower.key.as_ref(),
+            &params.nonce.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+    
+    // Update lending pool total borrowed
+    lending_pool_data.total_borrowed = lending_pool_data.total_borrowed.try_add(params.amount)?;
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    
     let loan_data = Loan {
         is_initialized: true,
+        bump,
         borrower: *context.borrower.key,
         lending_pool: *context.lending_pool.key,
         amount: params.amount,
-        interest_rate: lending_pool_data.interest_rate,
+        amount_repaid: 0,
+        collateral_amount: params.collateral_amount,
+        principal: params.amount,
+        borrow_index_snapshot: lending_pool_data.borrow_index,
         start_time: clock.unix_timestamp,
-        due_time: clock.unix_timestamp + params.duration,
+        due_time: clock.unix_timestamp.try_add(params.duration)?,
         status: LoanStatus::Active,
         created_at: clock.unix_timestamp,
         updated_at: clock.unix_timestamp,
     };
     
-    let loan_data_clone = loan_data.clone();
     Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
     
-    // Update lending pool total borrowed
-    let mut lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
-    let loan_amount = loan_data_clone.amount;
-    lending_pool_data.total_borrowed += loan_amount;
-    lending_pool_data.updated_at = clock.unix_timestamp;
+    // Lock the collateral in the pool's collateral vault
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            context.borrower_collateral_account.key,
+            context.collateral_vault.key,
+            context.borrower.key,
+            &[],
+            params.collateral_amount,
+        )?,
+        &[
+            context.borrower_collateral_account.clone(),
+            context.collateral_vault.clone(),
+            context.borrower.clone(),
+            context.token_program.clone(),
+        ],
+    )?;
+    
+    // Disburse the loan from the vault, signed by the pool PDA
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            context.token_vault.key,
+            context.borrower_token_account.key,
+            context.lending_pool.key,
+            &[],
+            params.amount,
+        )?,
+        &[
+            context.token_vault.clone(),
+            context.borrower_token_account.clone(),
+            context.lending_pool.clone(),
+            context.token_program.clone(),
+        ],
+        &[&lending_pool_data.signer_seeds()],
+    )?;
+    
     LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
     
     Ok(())
 }
 
+/// Repays up to `amount` of the loan's debt at the current borrow index. The
+/// loan is marked `Repaid`, and its collateral released, once nothing is owed.
 pub fn repay_loan<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
+    amount: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
@@ -112,103 +429,1432 @@ pub fn repay_loan<'a>(
         loan: next_account_info(account_info_iter)?,
         lending_pool: next_account_info(account_info_iter)?,
         borrower: next_account_info(account_info_iter)?,
+        credit_record: next_account_info(account_info_iter)?,
+        borrower_token_account: next_account_info(account_info_iter)?,
+        token_vault: next_account_info(account_info_iter)?,
+        borrower_collateral_account: next_account_info(account_info_iter)?,
+        collateral_vault: next_account_info(account_info_iter)?,
         token_program: next_account_info(account_info_iter)?,
     };
     
     context.validate(program_id)?;
     
     let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    
+    let mut loan_data = load_account::<Loan>(context.loan, program_id)?;
+    if loan_data.status != LoanStatus::Active {
+        return Err(UnityVaultError::LoanNotActive.into());
+    }
+    
+    // Never collect more than is owed
+    let debt = loan_data.debt(lending_pool_data.borrow_index)?;
+    let payment = amount.min(debt);
+    if payment == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
     
-    // Update loan status
-    let mut loan_data = Loan::unpack(&context.loan.data.borrow())?;
-    let loan_amount = loan_data.amount;
-    loan_data.status = LoanStatus::Repaid;
+    // Collect the payment into the vault
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            context.borrower_token_account.key,
+            context.token_vault.key,
+            context.borrower.key,
+            &[],
+            payment,
+        )?,
+        &[
+            context.borrower_token_account.clone(),
+            context.token_vault.clone(),
+            context.borrower.clone(),
+            context.token_program.clone(),
+        ],
+    )?;
+    
+    // Update loan, carrying the remaining debt forward from the current index
+    loan_data.principal = debt.try_sub(payment)?;
+    loan_data.borrow_index_snapshot = lending_pool_data.borrow_index;
+    loan_data.amount_repaid = loan_data.amount_repaid.try_add(payment)?;
+    let fully_repaid = loan_data.principal == 0;
+    if fully_repaid {
+        loan_data.status = LoanStatus::Repaid;
+    }
     loan_data.updated_at = clock.unix_timestamp;
+    let collateral_amount = loan_data.collateral_amount;
     Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
     
-    // Update lending pool total borrowed
-    let mut lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
-    lending_pool_data.total_borrowed -= loan_amount;
+    // Loan debts round up while the pool's aggregate debt rounds down, so the
+    // last repayments may slightly exceed it
+    lending_pool_data.total_borrowed = lending_pool_data.total_borrowed.saturating_sub(payment);
     lending_pool_data.updated_at = clock.unix_timestamp;
+    
+    if fully_repaid {
+        let mut credit_record_data = load_account::<CreditRecord>(context.credit_record, program_id)?;
+        credit_record_data.loans_repaid = credit_record_data.loans_repaid.try_add(1)?;
+        credit_record_data.updated_at = clock.unix_timestamp;
+        CreditRecord::pack(credit_record_data, &mut context.credit_record.data.borrow_mut())?;
+        
+        // Release the collateral back to the borrower
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                context.collateral_vault.key,
+                context.borrower_collateral_account.key,
+                context.lending_pool.key,
+                &[],
+                collateral_amount,
+            )?,
+            &[
+                context.collateral_vault.clone(),
+                context.borrower_collateral_account.clone(),
+                context.lending_pool.clone(),
+                context.token_program.clone(),
+            ],
+            &[&lending_pool_data.signer_seeds()],
+        )?;
+    }
+    
     LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
     
     Ok(())
 }
 
+/// Deposits `amount` of the pool's token and mints LP shares at the current
+/// exchange rate.
+pub fn deposit<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = DepositContext {
+        lending_pool: next_account_info(account_info_iter)?,
+        depositor: next_account_info(account_info_iter)?,
+        depositor_token_account: next_account_info(account_info_iter)?,
+        token_vault: next_account_info(account_info_iter)?,
+        share_mint: next_account_info(account_info_iter)?,
+        depositor_share_account: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    let share_supply = Mint::unpack(&context.share_mint.data.borrow())?.supply;
+    let shares = lending_pool_data.shares_for_deposit(amount, share_supply)?;
+    if shares == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Move the deposit into the vault
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            context.depositor_token_account.key,
+            context.token_vault.key,
+            context.depositor.key,
+            &[],
+            amount,
+        )?,
+        &[
+            context.depositor_token_account.clone(),
+            context.token_vault.clone(),
+            context.depositor.clone(),
+            context.token_program.clone(),
+        ],
+    )?;
+    
+    // Mint the depositor's shares, signed by the pool PDA
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            &spl_token::id(),
+            context.share_mint.key,
+            context.depositor_share_account.key,
+            context.lending_pool.key,
+            &[],
+            shares,
+        )?,
+        &[
+            context.share_mint.clone(),
+            context.depositor_share_account.clone(),
+            context.lending_pool.clone(),
+            context.token_program.clone(),
+        ],
+        &[&lending_pool_data.signer_seeds()],
+    )?;
+    
+    lending_pool_data.total_deposited = lending_pool_data.total_deposited.try_add(amount)?;
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Burns `shares` LP shares and pays out their value from unlent liquidity.
+pub fn withdraw<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    shares: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = WithdrawContext {
+        lending_pool: next_account_info(account_info_iter)?,
+        withdrawer: next_account_info(account_info_iter)?,
+        withdrawer_share_account: next_account_info(account_info_iter)?,
+        share_mint: next_account_info(account_info_iter)?,
+        token_vault: next_account_info(account_info_iter)?,
+        withdrawer_token_account: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    let share_supply = Mint::unpack(&context.share_mint.data.borrow())?.supply;
+    let amount = lending_pool_data.amount_for_shares(shares, share_supply)?;
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if amount > lending_pool_data.available_liquidity() {
+        return Err(UnityVaultError::InsufficientLiquidity.into());
+    }
+    
+    // Burn the withdrawer's shares
+    invoke(
+        &spl_token::instruction::burn(
+            &spl_token::id(),
+            context.withdrawer_share_account.key,
+            context.share_mint.key,
+            context.withdrawer.key,
+            &[],
+            shares,
+        )?,
+        &[
+            context.withdrawer_share_account.clone(),
+            context.share_mint.clone(),
+            context.withdrawer.clone(),
+            context.token_program.clone(),
+        ],
+    )?;
+    
+    // Pay out from the vault, signed by the pool PDA
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            context.token_vault.key,
+            context.withdrawer_token_account.key,
+            context.lending_pool.key,
+            &[],
+            amount,
+        )?,
+        &[
+            context.token_vault.clone(),
+            context.withdrawer_token_account.clone(),
+            context.lending_pool.clone(),
+            context.token_program.clone(),
+        ],
+        &[&lending_pool_data.signer_seeds()],
+    )?;
+    
+    lending_pool_data.total_deposited = lending_pool_data.total_deposited.try_sub(amount)?;
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Marks an active loan defaulted once it is past `due_time` plus the pool's
+/// grace period. Anyone may crank this.
+pub fn mark_defaulted<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = MarkDefaultedContext {
+        loan: next_account_info(account_info_iter)?,
+        lending_pool: next_account_info(account_info_iter)?,
+        credit_record: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    let mut loan_data = load_account::<Loan>(context.loan, program_id)?;
+    if loan_data.status != LoanStatus::Active {
+        return Err(UnityVaultError::LoanNotActive.into());
+    }
+    
+    let default_time = loan_data.due_time.try_add(lending_pool_data.grace_period)?;
+    if clock.unix_timestamp <= default_time {
+        return Err(UnityVaultError::LoanNotOverdue.into());
+    }
+    
+    // Freeze the debt and write it off, so the loan stops compounding into
+    // the pool's borrows and lenders' liquidity. A later liquidation books
+    // whatever it recovers back to lenders.
+    let debt = loan_data.debt(lending_pool_data.borrow_index)?;
+    // As in repay_loan, the pool's aggregate debt may round below the loan's
+    lending_pool_data.total_borrowed = lending_pool_data.total_borrowed.saturating_sub(debt);
+    lending_pool_data.total_deposited = lending_pool_data.total_deposited.saturating_sub(debt);
+    lending_pool_data.total_bad_debt = lending_pool_data.total_bad_debt.try_add(debt)?;
+    
+    loan_data.principal = debt;
+    loan_data.borrow_index_snapshot = lending_pool_data.borrow_index;
+    loan_data.status = LoanStatus::Defaulted;
+    loan_data.updated_at = clock.unix_timestamp;
+    Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
+    
+    let mut credit_record_data = load_account::<CreditRecord>(context.credit_record, program_id)?;
+    credit_record_data.loans_defaulted = credit_record_data.loans_defaulted.try_add(1)?;
+    credit_record_data.updated_at = clock.unix_timestamp;
+    CreditRecord::pack(credit_record_data, &mut context.credit_record.data.borrow_mut())?;
+    
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Replaces the pool's interest rate model. Only the pool authority may
+/// call this; the new rate is recorded in the pool's rate history.
+pub fn update_interest_rate_model<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    model: InterestRateModel,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = UpdateInterestRateModelContext {
+        lending_pool: next_account_info(account_info_iter)?,
+        authority: next_account_info(account_info_iter)?,
+        interest_rate_history: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    model.validate()?;
+    
+    // Interest up to now accrues at the old model's rate
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    lending_pool_data.interest_rate_model = model;
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    
+    let mut interest_rate_history_data = load_account::<InterestRateHistory>(context.interest_rate_history, program_id)?;
+    interest_rate_history_data.record(lending_pool_data.rate_snapshot(clock.unix_timestamp)?);
+    
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    InterestRateHistory::pack(interest_rate_history_data, &mut context.interest_rate_history.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Records the pool's current utilization and borrow rate in its rate
+/// history. Permissionless, so keepers can sample the rate for charting.
+pub fn record_interest_rate<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = RecordInterestRateContext {
+        lending_pool: next_account_info(account_info_iter)?,
+        interest_rate_history: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    let mut interest_rate_history_data = load_account::<InterestRateHistory>(context.interest_rate_history, program_id)?;
+    interest_rate_history_data.record(lending_pool_data.rate_snapshot(clock.unix_timestamp)?);
+    
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    InterestRateHistory::pack(interest_rate_history_data, &mut context.interest_rate_history.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Accrues interest on the pool's borrows up to now. Permissionless; every
+/// other lending instruction also accrues before doing its own work.
+pub fn accrue_interest<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = AccrueInterestContext {
+        lending_pool: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Liquidates a loan that is defaulted or whose health factor has dropped
+/// below 1. The liquidator repays the debt, up to what the collateral covers
+/// after the liquidation bonus, and receives that much collateral plus the
+/// bonus. Any remaining collateral goes back to the borrower and any
+/// unrecovered debt is written off as bad debt. A `LiquidationEvent`
+/// records the outcome.
+pub fn liquidate_loan<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let context = LiquidateLoanContext {
+        loan: next_account_info(account_info_iter)?,
+        lending_pool: next_account_info(account_info_iter)?,
+        liquidation_event: next_account_info(account_info_iter)?,
+        liquidator: next_account_info(account_info_iter)?,
+        liquidator_token_account: next_account_info(account_info_iter)?,
+        liquidator_collateral_account: next_account_info(account_info_iter)?,
+        token_vault: next_account_info(account_info_iter)?,
+        collateral_vault: next_account_info(account_info_iter)?,
+        borrower_collateral_account: next_account_info(account_info_iter)?,
+        system_program: next_account_info(account_info_iter)?,
+        token_program: next_account_info(account_info_iter)?,
+    };
+    
+    context.validate(program_id)?;
+    
+    let clock = Clock::get()?;
+    let mut loan_data = load_account::<Loan>(context.loan, program_id)?;
+    let mut lending_pool_data = load_account::<LendingPool>(context.lending_pool, program_id)?;
+    lending_pool_data.accrue_interest(clock.unix_timestamp)?;
+    
+    let debt = loan_data.debt(lending_pool_data.borrow_index)?;
+    let health_factor_before = lending_pool_data.health_factor(loan_data.collateral_amount, debt)?;
+    let reason = match loan_data.status {
+        LoanStatus::Defaulted => LiquidationReason::Default,
+        LoanStatus::Active if health_factor_before < BPS => LiquidationReason::Undercollateralized,
+        _ => return Err(UnityVaultError::LoanNotLiquidatable.into()),
+    };
+    
+    // Verify liquidation event is the PDA for this loan
+    let (liquidation_event_key, bump) = find_liquidation_event_address(program_id, context.loan.key);
+    if liquidation_event_key != *context.liquidation_event.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Repay as much debt as the collateral covers once the bonus is added
+    let bonus_factor = BPS.try_add(lending_pool_data.liquidation_bonus)? as u128;
+    let collateral_value = lending_pool_data.collateral_value(loan_data.collateral_amount)?;
+    let coverable = to_u64(mul_div_floor(collateral_value as u128, BPS as u128, bonus_factor)?)?;
+    let repay_amount = debt.min(coverable);
+    let seized_value = to_u64(mul_div_floor(repay_amount as u128, bonus_factor, BPS as u128)?)?;
+    let seized = lending_pool_data
+        .collateral_for_value(seized_value)?
+        .min(loan_data.collateral_amount);
+    let bounty = seized.saturating_sub(lending_pool_data.collateral_for_value(repay_amount)?);
+    let returned = loan_data.collateral_amount.try_sub(seized)?;
+    
+    let pool_seeds = lending_pool_data.signer_seeds();
+    
+    // Collect the repayment from the liquidator
+    if repay_amount > 0 {
+        invoke(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                context.liquidator_token_account.key,
+                context.token_vault.key,
+                context.liquidator.key,
+                &[],
+                repay_amount,
+            )?,
+            &[
+                context.liquidator_token_account.clone(),
+                context.token_vault.clone(),
+                context.liquidator.clone(),
+                context.token_program.clone(),
+            ],
+        )?;
+    }
+    
+    // Pay the seized collateral, bonus included, to the liquidator and
+    // return the rest to the borrower
+    for (destination, amount) in [
+        (context.liquidator_collateral_account, seized),
+        (context.borrower_collateral_account, returned),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                context.collateral_vault.key,
+                destination.key,
+                context.lending_pool.key,
+                &[],
+                amount,
+            )?,
+            &[
+                context.collateral_vault.clone(),
+                destination.clone(),
+                context.lending_pool.clone(),
+                context.token_program.clone(),
+            ],
+            &[&pool_seeds],
+        )?;
+    }
+    
+    // Settle the loan against the pool. Whatever debt the repayment does not
+    // cover is bad debt, written off against lenders' liquidity.
+    let bad_debt = debt.try_sub(repay_amount)?;
+    if reason == LiquidationReason::Default {
+        // Written off when it defaulted, so the repayment is a recovery
+        lending_pool_data.total_deposited = lending_pool_data.total_deposited.try_add(repay_amount)?;
+        lending_pool_data.total_bad_debt = lending_pool_data.total_bad_debt.try_sub(repay_amount)?;
+    } else {
+        // As in repay_loan, the pool's aggregate debt may round below the loan's
+        lending_pool_data.total_borrowed = lending_pool_data.total_borrowed.saturating_sub(debt);
+        lending_pool_data.total_deposited = lending_pool_data.total_deposited.try_sub(bad_debt)?;
+        lending_pool_data.total_bad_debt = lending_pool_data.total_bad_debt.try_add(bad_debt)?;
+    }
+    lending_pool_data.updated_at = clock.unix_timestamp;
+    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    
+    loan_data.amount_repaid = loan_data.amount_repaid.try_add(repay_amount)?;
+    loan_data.collateral_amount = 0;
+    loan_data.principal = 0;
+    loan_data.status = LoanStatus::Liquidated;
+    loan_data.updated_at = clock.unix_timestamp;
+    let borrower = loan_data.borrower;
+    Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
+    
+    // Record the liquidation
+    create_pda_account(
+        context.liquidator,
+        context.liquidation_event,
+        context.system_program,
+        program_id,
+        LiquidationEvent::LEN,
+        &[LIQUIDATION_EVENT_SEED, context.loan.key.as_ref(), &[bump]],
+    )?;
+    
+    let liquidation_event_data = LiquidationEvent {
+        is_initialized: true,
+        bump,
+        loan: *context.loan.key,
+        borrower,
+        liquidator: *context.liquidator.key,
+        lending_pool: *context.lending_pool.key,
+        amount: repay_amount,
+        bad_debt,
+        collateral_amount: seized,
+        bounty,
+        reason,
+        health_factor_before,
+        // The loan is closed, so no debt remains
+        health_factor_after: u64::MAX,
+        timestamp: clock.unix_timestamp,
+    };
+    LiquidationEvent::pack(liquidation_event_data, &mut context.liquidation_event.data.borrow_mut())?;
+    
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_program::clock::Epoch;
-    
-    fn create_test_account<'a>(lamports: u64, owner: &'a Pubkey, data: &'a mut [u8]) -> AccountInfo<'a> {
-        static KEY: Pubkey = Pubkey::new_from_array([0; 32]);
-        static mut LAMPORTS: u64 = 0;
-        unsafe {
-            LAMPORTS = lamports;
-            AccountInfo::new(
-                &KEY,
-                false,
-                true,
-                &mut LAMPORTS,
-                data,
-                owner,
-                false,
-                Epoch::default(),
-            )
+    use crate::lending::state::{CreditTier, RateSnapshot, MAX_RATE_SNAPSHOTS, PRICE_SCALE};
+    use crate::math::{Decimal, WAD};
+    use proptest::prelude::*;
+    use crate::test_utils::{
+        create_empty_account, create_mint_account, create_program_account, create_test_account, create_token_account,
+        create_user_profile_account, set_clock, setup, token_balance,
+    };
+    
+    const YEAR: i64 = 365 * 24 * 60 * 60;
+    
+    struct TestPool<'a> {
+        lending_pool: AccountInfo<'a>,
+        token_mint: AccountInfo<'a>,
+        token_vault: AccountInfo<'a>,
+        share_mint: AccountInfo<'a>,
+        collateral_mint: AccountInfo<'a>,
+        collateral_vault: AccountInfo<'a>,
+        interest_rate_history: AccountInfo<'a>,
+    }
+    
+    struct TestLender<'a> {
+        lender: AccountInfo<'a>,
+        token_account: AccountInfo<'a>,
+        share_account: AccountInfo<'a>,
+    }
+    
+    struct TestLoan<'a> {
+        loan: AccountInfo<'a>,
+        borrower: AccountInfo<'a>,
+        profile: AccountInfo<'a>,
+        credit_record: AccountInfo<'a>,
+        token_account: AccountInfo<'a>,
+        collateral_account: AccountInfo<'a>,
+    }
+    
+    fn pool_params(interest_rate: u64) -> LendingPoolParams {
+        LendingPoolParams {
+            interest_rate_model: InterestRateModel::Fixed { rate: interest_rate },
+            max_loan_amount: 1_000_000,
+            min_loan_amount: 1_000,
+            grace_period: 7 * 24 * 60 * 60,
+            collateral_price: PRICE_SCALE as u64, // parity with the pool token
+            max_ltv: 8_000,
+            liquidation_threshold: 9_000,
+            liquidation_bonus: 500,
+            require_verified_borrower: false,
+            // Poor, Standard, Good and Excellent borrowers
+            credit_tier_limits: [0, 2_500, 5_000, BPS],
+        }
+    }
+    
+    /// Accounts for `init_lending_pool`, with the vault owned by `vault_owner`
+    /// (the pool PDA when `None`).
+    fn init_pool_accounts<'a>(
+        program_id: &Pubkey,
+        authority: Pubkey,
+        token_mint: Pubkey,
+        vault_owner: Option<Pubkey>,
+    ) -> Vec<AccountInfo<'a>> {
+        let (lending_pool, _) = find_lending_pool_address(program_id, &authority, &token_mint);
+        let collateral_mint = Pubkey::new_unique();
+        vec![
+            create_empty_account(lending_pool),
+            create_test_account(authority, solana_program::system_program::id(), true, vec![]),
+            create_mint_account(token_mint, authority, 0, 6),
+            create_token_account(Pubkey::new_unique(), token_mint, vault_owner.unwrap_or(lending_pool), 0),
+            create_empty_account(find_share_mint_address(program_id, &lending_pool).0),
+            create_mint_account(collateral_mint, authority, 0, 9),
+            create_empty_account(find_collateral_vault_address(program_id, &lending_pool).0),
+            create_empty_account(find_interest_rate_history_address(program_id, &lending_pool).0),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(spl_token::id()),
+            create_program_account(solana_program::sysvar::rent::id()),
+        ]
+    }
+    
+    fn create_pool<'a>(program_id: &Pubkey, interest_rate: u64) -> TestPool<'a> {
+        // Leaked so the pool's accounts can outlive this helper
+        let accounts = Vec::leak(init_pool_accounts(program_id, Pubkey::new_unique(), Pubkey::new_unique(), None));
+        init_lending_pool(program_id, accounts, pool_params(interest_rate)).unwrap();
+        
+        TestPool {
+            lending_pool: accounts[0].clone(),
+            token_mint: accounts[2].clone(),
+            token_vault: accounts[3].clone(),
+            share_mint: accounts[4].clone(),
+            collateral_mint: accounts[5].clone(),
+            collateral_vault: accounts[6].clone(),
+            interest_rate_history: accounts[7].clone(),
         }
     }
     
+    fn create_lender<'a>(pool: &TestPool<'a>, balance: u64) -> TestLender<'a> {
+        let lender = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        TestLender {
+            token_account: create_token_account(Pubkey::new_unique(), *pool.token_mint.key, *lender.key, balance),
+            share_account: create_token_account(Pubkey::new_unique(), *pool.share_mint.key, *lender.key, 0),
+            lender,
+        }
+    }
+    
+    fn deposit_accounts<'a>(pool: &TestPool<'a>, lender: &TestLender<'a>) -> &'a [AccountInfo<'a>] {
+        Vec::leak(vec![
+            pool.lending_pool.clone(),
+            lender.lender.clone(),
+            lender.token_account.clone(),
+            pool.token_vault.clone(),
+            pool.share_mint.clone(),
+            lender.share_account.clone(),
+            create_program_account(spl_token::id()),
+        ])
+    }
+    
+    fn withdraw_accounts<'a>(pool: &TestPool<'a>, lender: &TestLender<'a>) -> &'a [AccountInfo<'a>] {
+        Vec::leak(vec![
+            pool.lending_pool.clone(),
+            lender.lender.clone(),
+            lender.share_account.clone(),
+            pool.share_mint.clone(),
+            pool.token_vault.clone(),
+            lender.token_account.clone(),
+            create_program_account(spl_token::id()),
+        ])
+    }
+    
+    fn create_borrower<'a>(program_id: &Pubkey, pool: &TestPool<'a>, balance: u64, collateral: u64) -> TestLoan<'a> {
+        let borrower = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        TestLoan {
+            loan: create_empty_account(Pubkey::new_unique()),
+            profile: create_user_profile_account(*program_id, *borrower.key),
+            credit_record: create_empty_account(find_credit_record_address(program_id, borrower.key).0),
+            token_account: create_token_account(Pubkey::new_unique(), *pool.token_mint.key, *borrower.key, balance),
+            collateral_account: create_token_account(
+                Pubkey::new_unique(),
+                *pool.collateral_mint.key,
+                *borrower.key,
+                collateral,
+            ),
+            borrower,
+        }
+    }
+    
+    fn create_loan_accounts<'a>(program_id: &Pubkey, pool: &TestPool<'a>, loan: &mut TestLoan<'a>) -> &'a [AccountInfo<'a>] {
+        let (loan_key, _) = find_loan_address(program_id, pool.lending_pool.key, loan.borrower.key, 0);
+        loan.loan = create_empty_account(loan_key);
+        Vec::leak(vec![
+            loan.loan.clone(),
+            pool.lending_pool.clone(),
+            loan.borrower.clone(),
+            loan.profile.clone(),
+            loan.credit_record.clone(),
+            pool.token_vault.clone(),
+            loan.token_account.clone(),
+            pool.collateral_vault.clone(),
+            loan.collateral_account.clone(),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(spl_token::id()),
+        ])
+    }
+    
+    fn loan_params(amount: u64, collateral_amount: u64) -> LoanParams {
+        LoanParams {
+            amount,
+            duration: YEAR,
+            nonce: 0,
+            collateral_amount,
+        }
+    }
+    
+    /// Opens a loan of `amount` backed by `collateral`; the borrower starts
+    /// with `balance` pool tokens and exactly `collateral` collateral tokens.
+    fn open_loan<'a>(program_id: &Pubkey, pool: &TestPool<'a>, amount: u64, collateral: u64, balance: u64) -> TestLoan<'a> {
+        let mut loan = create_borrower(program_id, pool, balance, collateral);
+        let accounts = create_loan_accounts(program_id, pool, &mut loan);
+        create_loan(program_id, accounts, loan_params(amount, collateral)).unwrap();
+        loan
+    }
+    
+    fn repay_accounts<'a>(pool: &TestPool<'a>, loan: &TestLoan<'a>) -> &'a [AccountInfo<'a>] {
+        Vec::leak(vec![
+            loan.loan.clone(),
+            pool.lending_pool.clone(),
+            loan.borrower.clone(),
+            loan.credit_record.clone(),
+            loan.token_account.clone(),
+            pool.token_vault.clone(),
+            loan.collateral_account.clone(),
+            pool.collateral_vault.clone(),
+            create_program_account(spl_token::id()),
+        ])
+    }
+    
+    /// Liquidator accounts for `liquidate_loan`, funded with `balance` pool tokens.
+    fn liquidate_accounts<'a>(
+        program_id: &Pubkey,
+        pool: &TestPool<'a>,
+        loan: &TestLoan<'a>,
+        balance: u64,
+    ) -> &'a [AccountInfo<'a>] {
+        let liquidator = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        Vec::leak(vec![
+            loan.loan.clone(),
+            pool.lending_pool.clone(),
+            create_empty_account(find_liquidation_event_address(program_id, loan.loan.key).0),
+            liquidator.clone(),
+            create_token_account(Pubkey::new_unique(), *pool.token_mint.key, *liquidator.key, balance),
+            create_token_account(Pubkey::new_unique(), *pool.collateral_mint.key, *liquidator.key, 0),
+            pool.token_vault.clone(),
+            pool.collateral_vault.clone(),
+            loan.collateral_account.clone(),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(spl_token::id()),
+        ])
+    }
+    
     #[test]
     fn test_init_lending_pool() {
+        setup(1_700_000_000);
         let program_id = Pubkey::new_unique();
         let authority = Pubkey::new_unique();
         let token_mint = Pubkey::new_unique();
-        let token_vault = Pubkey::new_unique();
-        let system_program_id = solana_program::system_program::id();
-        let spl_token_id = Pubkey::from_str(&spl_token::ID.to_string()).unwrap();
-        let rent_id = solana_program::sysvar::rent::id();
-        
-        let mut lending_pool_data = vec![0; LendingPool::LEN];
-        let lending_pool_account = create_test_account(
-            1000000,
-            &program_id,
-            &mut lending_pool_data,
-        );
-        
-        let params = LendingPoolParams {
-            interest_rate: 500, // 5%
-            max_loan_amount: 1000000,
-            min_loan_amount: 1000,
+        
+        let (lending_pool, bump) = find_lending_pool_address(&program_id, &authority, &token_mint);
+        let accounts = init_pool_accounts(&program_id, authority, token_mint, None);
+        
+        assert!(init_lending_pool(&program_id, &accounts, pool_params(500)).is_ok());
+        
+        assert_eq!(*accounts[0].owner, program_id);
+        let lending_pool_data = LendingPool::unpack(&accounts[0].data.borrow()).unwrap();
+        assert!(lending_pool_data.is_initialized);
+        assert_eq!(lending_pool_data.bump, bump);
+        assert_eq!(lending_pool_data.authority, authority);
+        assert_eq!(lending_pool_data.token_mint, token_mint);
+        assert_eq!(lending_pool_data.token_vault, *accounts[3].key);
+        assert_eq!(lending_pool_data.share_mint, *accounts[4].key);
+        assert_eq!(lending_pool_data.collateral_mint, *accounts[5].key);
+        assert_eq!(lending_pool_data.collateral_vault, *accounts[6].key);
+        assert_eq!(lending_pool_data.interest_rate_model, InterestRateModel::Fixed { rate: 500 });
+        assert_eq!(lending_pool_data.max_loan_amount, 1000000);
+        assert_eq!(lending_pool_data.min_loan_amount, 1000);
+        assert_eq!(lending_pool_data.max_ltv, 8_000);
+        assert_eq!(lending_pool_data.total_borrowed, 0);
+        assert_eq!(lending_pool_data.total_deposited, 0);
+        
+        let share_mint_data = Mint::unpack(&accounts[4].data.borrow()).unwrap();
+        assert_eq!(share_mint_data.mint_authority, Some(lending_pool).into());
+        assert_eq!(share_mint_data.decimals, 6);
+        
+        let collateral_vault_data = spl_token::state::Account::unpack(&accounts[6].data.borrow()).unwrap();
+        assert_eq!(collateral_vault_data.owner, lending_pool);
+        assert_eq!(collateral_vault_data.mint, *accounts[5].key);
+        
+        let interest_rate_history_data = load_account::<InterestRateHistory>(&accounts[7], &program_id).unwrap();
+        assert_eq!(interest_rate_history_data.lending_pool, lending_pool);
+        assert_eq!(
+            interest_rate_history_data.snapshots,
+            vec![RateSnapshot { timestamp: 1_700_000_000, utilization: 0, borrow_rate: 500 }]
+        );
+    }
+    
+    #[test]
+    fn test_init_lending_pool_rejects_vault_not_owned_by_pool() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let accounts = init_pool_accounts(&program_id, authority, Pubkey::new_unique(), Some(authority));
+        
+        assert_eq!(
+            init_lending_pool(&program_id, &accounts, pool_params(500)).unwrap_err(),
+            UnityVaultError::TokenVaultMismatch.into()
+        );
+    }
+    
+    #[test]
+    fn test_loan_disbursement_and_repayment_with_interest() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000); // 10%
+        let lender = create_lender(&pool, 1_000_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 1_000_000).unwrap();
+        
+        let loan = open_loan(&program_id, &pool, 100_000, 125_000, 10_000);
+        
+        assert_eq!(token_balance(&loan.token_account), 110_000);
+        assert_eq!(token_balance(&pool.token_vault), 900_000);
+        assert_eq!(token_balance(&loan.collateral_account), 0);
+        assert_eq!(token_balance(&pool.collateral_vault), 125_000);
+        assert_eq!(load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().total_borrowed, 100_000);
+        
+        // Half a year at 10% accrues 5% interest
+        set_clock(1_700_000_000 + YEAR / 2);
+        let accounts = repay_accounts(&pool, &loan);
+        
+        repay_loan(&program_id, accounts, 50_000).unwrap();
+        let loan_data = load_account::<Loan>(&loan.loan, &program_id).unwrap();
+        assert_eq!(loan_data.amount_repaid, 50_000);
+        assert_eq!(loan_data.status, LoanStatus::Active);
+        assert_eq!(token_balance(&pool.collateral_vault), 125_000);
+        
+        // Overpayment is capped at the outstanding amount
+        repay_loan(&program_id, accounts, 1_000_000).unwrap();
+        let loan_data = load_account::<Loan>(&loan.loan, &program_id).unwrap();
+        assert_eq!(loan_data.amount_repaid, 105_000);
+        assert_eq!(loan_data.status, LoanStatus::Repaid);
+        assert_eq!(token_balance(&loan.token_account), 5_000);
+        assert_eq!(token_balance(&pool.token_vault), 1_005_000);
+        
+        // Collateral is released once the loan is settled
+        assert_eq!(token_balance(&loan.collateral_account), 125_000);
+        
+        // Only the full repayment counts towards the borrower's credit
+        let credit_record_data = load_account::<CreditRecord>(&loan.credit_record, &program_id).unwrap();
+        assert_eq!(credit_record_data.loans_repaid, 1);
+        assert_eq!(credit_record_data.loans_defaulted, 0);
+        assert_eq!(token_balance(&pool.collateral_vault), 0);
+        
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(lending_pool_data.total_borrowed, 0);
+        assert_eq!(lending_pool_data.total_deposited, 1_005_000);
+        
+        assert_eq!(
+            repay_loan(&program_id, accounts, 1).unwrap_err(),
+            UnityVaultError::LoanNotActive.into()
+        );
+    }
+    
+    #[test]
+    fn test_credit_tier_limits_loan_size() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 1_000_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 1_000_000).unwrap();
+        
+        // New borrowers are Standard, limited to 25% of the pool's maximum
+        let mut loan = create_borrower(&program_id, &pool, 0, 1_000_000);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(250_001, 400_000)).unwrap_err(),
+            UnityVaultError::LoanAboveCreditLimit.into()
+        );
+        let credit_record_data = load_account::<CreditRecord>(&loan.credit_record, &program_id).unwrap();
+        assert_eq!(credit_record_data.borrower, *loan.borrower.key);
+        assert_eq!(credit_record_data.score(), 500);
+        assert_eq!(credit_record_data.tier(), CreditTier::Standard);
+        
+        // Three repaid loans make a Good borrower, limited to 50%
+        let mut credit_record_data = credit_record_data;
+        credit_record_data.loans_repaid = 3;
+        CreditRecord::pack(credit_record_data.clone(), &mut loan.credit_record.data.borrow_mut()).unwrap();
+        assert_eq!(credit_record_data.tier(), CreditTier::Good);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(500_001, 700_000)).unwrap_err(),
+            UnityVaultError::LoanAboveCreditLimit.into()
+        );
+        create_loan(&program_id, accounts, loan_params(500_000, 700_000)).unwrap();
+        
+        // A default outweighs the repayments, and the pool does not lend to Poor borrowers
+        let mut other = create_borrower(&program_id, &pool, 0, 125_000);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut other);
+        create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap();
+        let mut credit_record_data = load_account::<CreditRecord>(&other.credit_record, &program_id).unwrap();
+        credit_record_data.loans_repaid = 1;
+        credit_record_data.loans_defaulted = 2;
+        assert_eq!(credit_record_data.score(), 100);
+        CreditRecord::pack(credit_record_data, &mut other.credit_record.data.borrow_mut()).unwrap();
+        let accounts = create_loan_accounts(&program_id, &pool, &mut other);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(1_000, 125_000)).unwrap_err(),
+            UnityVaultError::CreditTierNotAccepted.into()
+        );
+    }
+    
+    #[test]
+    fn test_create_loan_checks_borrower_profile() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 1_000_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 1_000_000).unwrap();
+        
+        // Pools that do not require verification lend without a profile
+        let mut loan = create_borrower(&program_id, &pool, 0, 125_000);
+        loan.profile = create_empty_account(*loan.profile.key);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap();
+        
+        // Suspended borrowers cannot borrow
+        let mut loan = create_borrower(&program_id, &pool, 0, 125_000);
+        let mut profile_data = UserProfile::unpack(&loan.profile.data.borrow()).unwrap();
+        profile_data.status = UserStatus::Suspended;
+        UserProfile::pack(profile_data, &mut loan.profile.data.borrow_mut()).unwrap();
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap_err(),
+            UnityVaultError::BorrowerNotActive.into()
+        );
+        
+        let mut lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        lending_pool_data.require_verified_borrower = true;
+        LendingPool::pack(lending_pool_data, &mut pool.lending_pool.data.borrow_mut()).unwrap();
+        
+        let mut loan = create_borrower(&program_id, &pool, 0, 125_000);
+        let profile = loan.profile.clone();
+        loan.profile = create_empty_account(*profile.key);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap_err(),
+            UnityVaultError::BorrowerProfileRequired.into()
+        );
+        
+        loan.profile = profile;
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap_err(),
+            UnityVaultError::BorrowerNotVerified.into()
+        );
+        
+        // Expired verification does not count
+        let mut profile_data = UserProfile::unpack(&loan.profile.data.borrow()).unwrap();
+        profile_data.kyc_status = KycStatus::Verified;
+        profile_data.kyc_data.expires_at = 1_700_000_000;
+        UserProfile::pack(profile_data.clone(), &mut loan.profile.data.borrow_mut()).unwrap();
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap_err(),
+            UnityVaultError::BorrowerNotVerified.into()
+        );
+        
+        profile_data.kyc_data.expires_at = 1_700_000_000 + YEAR;
+        UserProfile::pack(profile_data, &mut loan.profile.data.borrow_mut()).unwrap();
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap();
+    }
+    
+    #[test]
+    fn test_create_loan_enforces_max_ltv() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 1_000_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 1_000_000).unwrap();
+        
+        // 80% max LTV at parity: 100_000 needs at least 125_000 collateral
+        let mut loan = create_borrower(&program_id, &pool, 0, 124_999);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut loan);
+        assert_eq!(
+            create_loan(&program_id, accounts, loan_params(100_000, 124_999)).unwrap_err(),
+            UnityVaultError::LoanToValueTooHigh.into()
+        );
+    }
+    
+    #[test]
+    fn test_kinked_interest_rate_model() {
+        let model = InterestRateModel::Kinked {
+            base_rate: 200,
+            slope1: 400,
+            slope2: 6_000,
+            optimal_utilization: 8_000,
+        };
+        assert!(model.validate().is_ok());
+        assert_eq!(model.borrow_rate(0).unwrap(), 200);
+        assert_eq!(model.borrow_rate(4_000).unwrap(), 400);
+        assert_eq!(model.borrow_rate(8_000).unwrap(), 600);
+        assert_eq!(model.borrow_rate(9_000).unwrap(), 3_600);
+        assert_eq!(model.borrow_rate(10_000).unwrap(), 6_600);
+        // Utilization is capped at 100%
+        assert_eq!(model.borrow_rate(20_000).unwrap(), 6_600);
+        
+        for optimal_utilization in [0, 10_001] {
+            let model = InterestRateModel::Kinked { base_rate: 0, slope1: 0, slope2: 0, optimal_utilization };
+            assert_eq!(model.validate().unwrap_err(), UnityVaultError::InvalidInterestRateModel.into());
+        }
+    }
+    
+    #[test]
+    fn test_interest_rate_follows_utilization() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 100_000).unwrap();
+        
+        // Only the pool authority can change the model
+        let model = InterestRateModel::Kinked {
+            base_rate: 200,
+            slope1: 400,
+            slope2: 6_000,
+            optimal_utilization: 8_000,
         };
+        let authority = create_test_account(
+            load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().authority,
+            solana_program::system_program::id(),
+            true,
+            vec![],
+        );
+        let impostor = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        assert_eq!(
+            update_interest_rate_model(
+                &program_id,
+                Vec::leak(vec![pool.lending_pool.clone(), impostor, pool.interest_rate_history.clone()]),
+                model,
+            )
+            .unwrap_err(),
+            UnityVaultError::LendingPoolAuthorityMismatch.into()
+        );
+        
+        set_clock(1_700_000_100);
+        let update = Vec::leak(vec![pool.lending_pool.clone(), authority, pool.interest_rate_history.clone()]);
+        update_interest_rate_model(&program_id, update, model).unwrap();
         
-        let mut authority_data = vec![];
-        let mut token_mint_data = vec![];
-        let mut token_vault_data = vec![];
-        let mut system_program_data = vec![];
-        let mut spl_token_data = vec![];
-        let mut rent_data = vec![];
-        
-        let accounts = vec![
-            lending_pool_account.clone(),
-            create_test_account(1000000, &authority, &mut authority_data),
-            create_test_account(1000000, &token_mint, &mut token_mint_data),
-            create_test_account(1000000, &token_vault, &mut token_vault_data),
-            create_test_account(1000000, &system_program_id, &mut system_program_data),
-            create_test_account(1000000, &spl_token_id, &mut spl_token_data),
-            create_test_account(1000000, &rent_id, &mut rent_data),
-        ];
-        
-        assert!(init_lending_pool(&program_id, &accounts, params).is_ok());
-        
-        let lending_pool = LendingPool::unpack(&lending_pool_account.data.borrow()).unwrap();
-        assert!(lending_pool.is_initialized);
-        assert_eq!(lending_pool.authority, authority);
-        assert_eq!(lending_pool.token_mint, token_mint);
-        assert_eq!(lending_pool.token_vault, token_vault);
-        assert_eq!(lending_pool.interest_rate, 500);
-        assert_eq!(lending_pool.max_loan_amount, 1000000);
-        assert_eq!(lending_pool.min_loan_amount, 1000);
-        assert_eq!(lending_pool.total_borrowed, 0);
-        assert_eq!(lending_pool.total_deposited, 0);
-    }
-} 
\ No newline at end of file
+        // Borrowing 40% of the pool moves the rate up the first slope
+        open_loan(&program_id, &pool, 40_000, 50_000, 0);
+        assert_eq!(load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().borrow_rate().unwrap(), 400);
+        
+        set_clock(1_700_000_200);
+        let record = Vec::leak(vec![pool.lending_pool.clone(), pool.interest_rate_history.clone()]);
+        record_interest_rate(&program_id, record).unwrap();
+        
+        let interest_rate_history_data =
+            load_account::<InterestRateHistory>(&pool.interest_rate_history, &program_id).unwrap();
+        assert_eq!(
+            interest_rate_history_data.snapshots,
+            vec![
+                RateSnapshot { timestamp: 1_700_000_000, utilization: 0, borrow_rate: 1_000 },
+                RateSnapshot { timestamp: 1_700_000_100, utilization: 0, borrow_rate: 200 },
+                RateSnapshot { timestamp: 1_700_000_200, utilization: 4_000, borrow_rate: 400 },
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_interest_rate_history_wraps_around() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let record = Vec::leak(vec![pool.lending_pool.clone(), pool.interest_rate_history.clone()]);
+        
+        // The snapshot taken at creation is overwritten once the buffer is full
+        for i in 1..=MAX_RATE_SNAPSHOTS as i64 {
+            set_clock(1_700_000_000 + i);
+            record_interest_rate(&program_id, record).unwrap();
+        }
+        
+        let interest_rate_history_data =
+            load_account::<InterestRateHistory>(&pool.interest_rate_history, &program_id).unwrap();
+        assert_eq!(interest_rate_history_data.snapshots.len(), MAX_RATE_SNAPSHOTS);
+        assert_eq!(interest_rate_history_data.next_index, 1);
+        let timestamps: Vec<i64> = interest_rate_history_data.chronological().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, (1..=MAX_RATE_SNAPSHOTS as i64).map(|i| 1_700_000_000 + i).collect::<Vec<_>>());
+    }
+    
+    #[test]
+    fn test_borrow_index_accrues_interest() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000); // 10%
+        let lender = create_lender(&pool, 1_000_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 1_000_000).unwrap();
+        let first = open_loan(&program_id, &pool, 100_000, 125_000, 0);
+        
+        // Cranking accrual twice over a year compounds at the half-year mark
+        let accrue = Vec::leak(vec![pool.lending_pool.clone()]);
+        set_clock(1_700_000_000 + YEAR / 2);
+        accrue_interest(&program_id, accrue).unwrap();
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(lending_pool_data.borrow_index, Decimal(WAD * 105 / 100));
+        assert_eq!(lending_pool_data.last_accrual_ts, 1_700_000_000 + YEAR / 2);
+        assert_eq!(lending_pool_data.total_borrowed, 105_000);
+        assert_eq!(lending_pool_data.total_deposited, 1_005_000);
+        
+        // A loan opened later only pays interest from its own snapshot
+        let mut second = create_borrower(&program_id, &pool, 0, 125_000);
+        let accounts = create_loan_accounts(&program_id, &pool, &mut second);
+        create_loan(&program_id, accounts, loan_params(100_000, 125_000)).unwrap();
+        let second_data = load_account::<Loan>(&second.loan, &program_id).unwrap();
+        assert_eq!(second_data.borrow_index_snapshot, Decimal(WAD * 105 / 100));
+        
+        set_clock(1_700_000_000 + YEAR);
+        accrue_interest(&program_id, accrue).unwrap();
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(lending_pool_data.borrow_index, Decimal(WAD * 105 * 105 / 10_000));
+        let first_data = load_account::<Loan>(&first.loan, &program_id).unwrap();
+        assert_eq!(first_data.debt(lending_pool_data.borrow_index).unwrap(), 110_250);
+        assert_eq!(second_data.debt(lending_pool_data.borrow_index).unwrap(), 105_000);
+        assert_eq!(lending_pool_data.total_borrowed, 215_250);
+        assert_eq!(lending_pool_data.total_deposited, 1_015_250);
+        
+        // Accruing again at the same time is a no-op
+        accrue_interest(&program_id, accrue).unwrap();
+        assert_eq!(
+            load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().borrow_index,
+            lending_pool_data.borrow_index
+        );
+        
+        // A partial repayment re-snapshots the remaining debt
+        repay_loan(&program_id, repay_accounts(&pool, &first), 10_250).unwrap();
+        let first_data = load_account::<Loan>(&first.loan, &program_id).unwrap();
+        assert_eq!(first_data.principal, 100_000);
+        assert_eq!(first_data.borrow_index_snapshot, lending_pool_data.borrow_index);
+        assert_eq!(load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().total_borrowed, 205_000);
+    }
+    
+    #[test]
+    fn test_shares_accrue_interest() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000); // 10%
+        let first = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &first), 100_000).unwrap();
+        assert_eq!(token_balance(&first.share_account), 100_000);
+        
+        // A full year at 10% on the whole pool earns 10_000
+        let loan = open_loan(&program_id, &pool, 100_000, 125_000, 10_000);
+        set_clock(1_700_000_000 + YEAR);
+        repay_loan(&program_id, repay_accounts(&pool, &loan), u64::MAX).unwrap();
+        
+        // A later depositor buys in at the higher exchange rate
+        let second = create_lender(&pool, 110_000);
+        deposit(&program_id, deposit_accounts(&pool, &second), 110_000).unwrap();
+        assert_eq!(token_balance(&second.share_account), 100_000);
+        
+        withdraw(&program_id, withdraw_accounts(&pool, &first), 100_000).unwrap();
+        assert_eq!(token_balance(&first.token_account), 110_000);
+        assert_eq!(token_balance(&first.share_account), 0);
+        assert_eq!(load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap().total_deposited, 110_000);
+    }
+    
+    #[test]
+    fn test_deposit_rejected_when_shares_are_unbacked() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let first = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &first), 100_000).unwrap();
+        
+        // Bad debt wipes out all of the pool's liquidity
+        let mut lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        lending_pool_data.total_deposited = 0;
+        lending_pool_data.total_bad_debt = 100_000;
+        LendingPool::pack(lending_pool_data, &mut pool.lending_pool.data.borrow_mut()).unwrap();
+        
+        let second = create_lender(&pool, 50_000);
+        assert_eq!(
+            deposit(&program_id, deposit_accounts(&pool, &second), 50_000).unwrap_err(),
+            UnityVaultError::PoolInsolvent.into()
+        );
+        assert_eq!(token_balance(&second.token_account), 50_000);
+        assert_eq!(token_balance(&second.share_account), 0);
+    }
+    
+    #[test]
+    fn test_withdraw_limited_to_available_liquidity() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 100_000).unwrap();
+        open_loan(&program_id, &pool, 60_000, 75_000, 0);
+        
+        assert_eq!(
+            withdraw(&program_id, withdraw_accounts(&pool, &lender), 50_000).unwrap_err(),
+            UnityVaultError::InsufficientLiquidity.into()
+        );
+        withdraw(&program_id, withdraw_accounts(&pool, &lender), 40_000).unwrap();
+        assert_eq!(token_balance(&lender.token_account), 40_000);
+        assert_eq!(token_balance(&lender.share_account), 60_000);
+    }
+    
+    #[test]
+    fn test_default_and_liquidate_overdue_loan() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000); // 10%, 7 day grace period
+        let lender = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 100_000).unwrap();
+        
+        let loan = open_loan(&program_id, &pool, 50_000, 80_000, 0);
+        let repay = repay_accounts(&pool, &loan);
+        repay_loan(&program_id, repay, 20_000).unwrap();
+        
+        let mark = Vec::leak(vec![loan.loan.clone(), pool.lending_pool.clone(), loan.credit_record.clone()]);
+        let due_time = 1_700_000_000 + YEAR;
+        
+        // Not defaultable until the grace period has passed
+        set_clock(due_time + 7 * 24 * 60 * 60);
+        assert_eq!(
+            mark_defaulted(&program_id, mark).unwrap_err(),
+            UnityVaultError::LoanNotOverdue.into()
+        );
+        
+        // Healthy and not defaulted, so not liquidatable yet
+        let liquidate = liquidate_accounts(&program_id, &pool, &loan, 100_000);
+        assert_eq!(
+            liquidate_loan(&program_id, liquidate).unwrap_err(),
+            UnityVaultError::LoanNotLiquidatable.into()
+        );
+        
+        set_clock(due_time + 7 * 24 * 60 * 60 + 1);
+        mark_defaulted(&program_id, mark).unwrap();
+        assert_eq!(load_account::<Loan>(&loan.loan, &program_id).unwrap().status, LoanStatus::Defaulted);
+        assert_eq!(load_account::<CreditRecord>(&loan.credit_record, &program_id).unwrap().loans_defaulted, 1);
+        assert_eq!(
+            repay_loan(&program_id, repay, 1).unwrap_err(),
+            UnityVaultError::LoanNotActive.into()
+        );
+        
+        // Marking the default accrued the pool up to now, froze the loan's debt
+        // and wrote it off
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        let debt = load_account::<Loan>(&loan.loan, &program_id).unwrap().debt(lending_pool_data.borrow_index).unwrap();
+        assert!(debt > 30_000);
+        assert_eq!(lending_pool_data.total_borrowed, 0);
+        assert_eq!(lending_pool_data.total_bad_debt, debt);
+        
+        // The defaulted loan no longer accrues interest
+        set_clock(due_time + YEAR);
+        accrue_interest(&program_id, Vec::leak(vec![pool.lending_pool.clone()])).unwrap();
+        let accrued_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(accrued_pool_data.total_deposited, lending_pool_data.total_deposited);
+        assert_eq!(
+            load_account::<Loan>(&loan.loan, &program_id).unwrap().debt(accrued_pool_data.borrow_index).unwrap(),
+            debt
+        );
+        liquidate_loan(&program_id, liquidate).unwrap();
+        assert_eq!(load_account::<Loan>(&loan.loan, &program_id).unwrap().status, LoanStatus::Liquidated);
+        
+        // The liquidator repays the debt and takes collateral worth 105% of it;
+        // the rest of the collateral goes back to the borrower
+        let seized = debt + debt * 5 / 100;
+        assert_eq!(token_balance(&liquidate[4]), 100_000 - debt);
+        assert_eq!(token_balance(&liquidate[5]), seized);
+        assert_eq!(token_balance(&loan.collateral_account), 80_000 - seized);
+        assert_eq!(token_balance(&pool.collateral_vault), 0);
+        
+        // The recovery is booked back to lenders, who keep the interest that
+        // accrued while the loan was overdue
+        let total_deposited = lending_pool_data.total_deposited + debt;
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(lending_pool_data.total_borrowed, 0);
+        assert_eq!(lending_pool_data.total_bad_debt, 0);
+        assert_eq!(lending_pool_data.total_deposited, total_deposited);
+        assert!(total_deposited > 100_000);
+        
+        let event = load_account::<LiquidationEvent>(&liquidate[2], &program_id).unwrap();
+        assert_eq!(event.loan, *loan.loan.key);
+        assert_eq!(event.borrower, *loan.borrower.key);
+        assert_eq!(event.liquidator, *liquidate[3].key);
+        assert_eq!(event.amount, debt);
+        assert_eq!(event.collateral_amount, seized);
+        assert_eq!(event.bounty, seized - debt);
+        assert_eq!(event.bad_debt, 0);
+        assert_eq!(event.reason, LiquidationReason::Default);
+    }
+    
+    #[test]
+    fn test_liquidation_returns_collateral_only_to_borrower() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000);
+        let lender = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 100_000).unwrap();
+        let loan = open_loan(&program_id, &pool, 80_000, 100_000, 0);
+        set_clock(1_700_000_000 + 3 * YEAR);
+        
+        // The liquidator cannot keep the borrower's excess collateral
+        let liquidate = liquidate_accounts(&program_id, &pool, &loan, 1_000_000);
+        let mut accounts = liquidate.to_vec();
+        accounts[8] = liquidate[5].clone();
+        assert_eq!(
+            liquidate_loan(&program_id, Vec::leak(accounts)).unwrap_err(),
+            UnityVaultError::BorrowerCollateralAccountMismatch.into()
+        );
+        
+        // Nor redirect it to a borrower account for another mint
+        let mut accounts = liquidate.to_vec();
+        accounts[8] = create_token_account(Pubkey::new_unique(), *pool.token_mint.key, *loan.borrower.key, 0);
+        assert_eq!(
+            liquidate_loan(&program_id, Vec::leak(accounts)).unwrap_err(),
+            UnityVaultError::BorrowerCollateralAccountMismatch.into()
+        );
+        
+        assert_eq!(token_balance(&pool.collateral_vault), 100_000);
+        liquidate_loan(&program_id, liquidate).unwrap();
+        assert!(token_balance(&loan.collateral_account) > 0);
+    }
+    
+    #[test]
+    fn test_liquidate_undercollateralized_loan() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let pool = create_pool(&program_id, 1_000); // 10%
+        let lender = create_lender(&pool, 100_000);
+        deposit(&program_id, deposit_accounts(&pool, &lender), 100_000).unwrap();
+        
+        // Borrowed at the 80% max LTV, the loan crosses the 90% liquidation
+        // threshold once interest passes 10_000
+        let loan = open_loan(&program_id, &pool, 80_000, 100_000, 0);
+        let liquidate = liquidate_accounts(&program_id, &pool, &loan, 1_000_000);
+        
+        set_clock(1_700_000_000 + YEAR);
+        assert_eq!(
+            liquidate_loan(&program_id, liquidate).unwrap_err(),
+            UnityVaultError::LoanNotLiquidatable.into()
+        );
+        
+        // After three years 104_000 is owed against 100_000 of collateral, so
+        // the liquidator repays what the collateral covers after the bonus and
+        // the rest is bad debt
+        set_clock(1_700_000_000 + 3 * YEAR);
+        liquidate_loan(&program_id, liquidate).unwrap();
+        
+        let repaid = 100_000 * 10_000 / 10_500;
+        let seized = repaid * 10_500 / 10_000;
+        assert_eq!(token_balance(&liquidate[4]), 1_000_000 - repaid);
+        assert_eq!(token_balance(&liquidate[5]), seized);
+        assert_eq!(token_balance(&loan.collateral_account), 100_000 - seized);
+        
+        let event = load_account::<LiquidationEvent>(&liquidate[2], &program_id).unwrap();
+        assert_eq!(event.reason, LiquidationReason::Undercollateralized);
+        assert_eq!(event.health_factor_before, 100_000 * 9_000 / 104_000);
+        assert_eq!(event.amount, repaid);
+        assert_eq!(event.bad_debt, 104_000 - repaid);
+        
+        let lending_pool_data = load_account::<LendingPool>(&pool.lending_pool, &program_id).unwrap();
+        assert_eq!(lending_pool_data.total_borrowed, 0);
+        assert_eq!(lending_pool_data.total_bad_debt, 104_000 - repaid);
+        assert_eq!(lending_pool_data.total_deposited, 100_000 + repaid - 80_000);
+    }
+    
+    fn loan_with_principal(principal: u64, borrow_index_snapshot: Decimal) -> Loan {
+        Loan {
+            is_initialized: true,
+            bump: 0,
+            borrower: Pubkey::new_unique(),
+            lending_pool: Pubkey::new_unique(),
+            amount: principal,
+            amount_repaid: 0,
+            collateral_amount: 0,
+            principal,
+            borrow_index_snapshot,
+            start_time: 0,
+            due_time: YEAR,
+            status: LoanStatus::Active,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+    
+    proptest! {
+        #[test]
+        fn prop_loan_debt_exact_for_large_principals(
+            principal in (u64::MAX - 1_000_000)..=u64::MAX,
+            snapshot in WAD..=WAD * 1_000,
+            growth in 1..=100_000_000_000_000_000u128,
+        ) {
+            // principal * index is far beyond u128, but the debt is exactly
+            // principal * growth and fails only when that exceeds u64
+            let loan = loan_with_principal(principal, Decimal(snapshot));
+            let debt = loan.debt(Decimal(snapshot * growth));
+            match (principal as u128).checked_mul(growth).filter(|debt| *debt <= u64::MAX as u128) {
+                Some(expected) => prop_assert_eq!(debt.unwrap() as u128, expected),
+                None => prop_assert_eq!(debt.unwrap_err(), UnityVaultError::MathOverflow.into()),
+            }
+        }
+        
+        #[test]
+        fn prop_loan_debt_rounds_up_at_extreme_indices(
+            principal in any::<u64>(),
+            snapshot in WAD..=u128::MAX / 2,
+        ) {
+            // An index one unit above the snapshot owes at most one more unit
+            let loan = loan_with_principal(principal, Decimal(snapshot));
+            prop_assert_eq!(loan.debt(Decimal(snapshot)).unwrap(), principal);
+            let debt = loan.debt(Decimal(snapshot + 1)).unwrap_or(u64::MAX);
+            prop_assert!(debt == principal || debt == principal.saturating_add(1));
+        }
+    }
+}
@@ -4,38 +4,203 @@ use solana_program::{
     pubkey::Pubkey,
 };
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+use crate::math::{bps_of_floor, mul_div_ceil, mul_div_floor, ratio_bps, to_u64, Decimal, TryMath, BPS};
+use crate::utils::{AccountType, ProgramAccount};
+
+/// Seed for a lending pool PDA: `["lending_pool", authority, token_mint]`.
+pub const LENDING_POOL_SEED: &[u8] = b"lending_pool";
+
+/// Seed for a loan PDA: `["loan", lending_pool, borrower, nonce]`.
+pub const LOAN_SEED: &[u8] = b"loan";
+
+/// Seed for a pool's LP share mint PDA: `["share_mint", lending_pool]`.
+pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
+
+/// Seed for a pool's collateral vault PDA: `["collateral_vault", lending_pool]`.
+pub const COLLATERAL_VAULT_SEED: &[u8] = b"collateral_vault";
+
+/// Seed for a loan's liquidation record PDA: `["liquidation", loan]`.
+pub const LIQUIDATION_EVENT_SEED: &[u8] = b"liquidation";
+
+/// Seed for a pool's interest rate history PDA: `["rate_history", lending_pool]`.
+pub const INTEREST_RATE_HISTORY_SEED: &[u8] = b"rate_history";
+
+/// Number of snapshots an interest rate history keeps before overwriting the oldest.
+pub const MAX_RATE_SNAPSHOTS: usize = 64;
+
+/// Seed for a borrower's credit record PDA: `["credit_record", borrower]`.
+pub const CREDIT_RECORD_SEED: &[u8] = b"credit_record";
+
+/// Number of credit tiers, and of per-tier loan limits on a pool.
+pub const CREDIT_TIER_COUNT: usize = 4;
+
+/// Interest rates are annual and expressed in basis points.
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// `collateral_price` is the value of one collateral base unit in pool token
+/// base units, scaled by this factor.
+pub const PRICE_SCALE: u128 = 1_000_000;
+
+pub fn find_lending_pool_address(program_id: &Pubkey, authority: &Pubkey, token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LENDING_POOL_SEED, authority.as_ref(), token_mint.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_share_mint_address(program_id: &Pubkey, lending_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARE_MINT_SEED, lending_pool.as_ref()], program_id)
+}
+
+pub fn find_collateral_vault_address(program_id: &Pubkey, lending_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLATERAL_VAULT_SEED, lending_pool.as_ref()], program_id)
+}
+
+pub fn find_liquidation_event_address(program_id: &Pubkey, loan: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LIQUIDATION_EVENT_SEED, loan.as_ref()], program_id)
+}
+
+pub fn find_interest_rate_history_address(program_id: &Pubkey, lending_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INTEREST_RATE_HISTORY_SEED, lending_pool.as_ref()], program_id)
+}
+
+pub fn find_credit_record_address(program_id: &Pubkey, borrower: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CREDIT_RECORD_SEED, borrower.as_ref()], program_id)
+}
+
+pub fn find_loan_address(program_id: &Pubkey, lending_pool: &Pubkey, borrower: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOAN_SEED, lending_pool.as_ref(), borrower.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LoanStatus {
     Active,
     Repaid,
     Defaulted,
+    Liquidated,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidationReason {
+    Default,
+    Undercollateralized,
+}
+
+/// Borrower standing derived from `CreditRecord::score`, worst first.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreditTier {
+    Poor,
+    Standard,
+    Good,
+    Excellent,
+}
+
+/// How a pool's annual borrow rate, in basis points, follows its utilization.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterestRateModel {
+    /// The same rate at any utilization.
+    Fixed { rate: u64 },
+    /// Rises by `slope1` from `base_rate` up to `optimal_utilization`, then
+    /// by the steeper `slope2` up to full utilization.
+    Kinked {
+        base_rate: u64,
+        slope1: u64,
+        slope2: u64,
+        optimal_utilization: u64,
+    },
+}
+
+/// Borrow rate of a pool at one point in time.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateSnapshot {
+    pub timestamp: i64,
+    /// Utilization in basis points.
+    pub utilization: u64,
+    /// Annual borrow rate in basis points.
+    pub borrow_rate: u64,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct LendingPoolParams {
-    pub interest_rate: u64,
+    pub interest_rate_model: InterestRateModel,
     pub max_loan_amount: u64,
     pub min_loan_amount: u64,
+    /// Seconds after `due_time` before an unpaid loan can be marked defaulted.
+    pub grace_period: i64,
+    pub collateral_price: u64,
+    /// Maximum loan-to-value at origination, in basis points.
+    pub max_ltv: u64,
+    /// Loan-to-value above which a loan can be liquidated, in basis points.
+    pub liquidation_threshold: u64,
+    /// Extra collateral paid to liquidators, in basis points of the debt repaid.
+    pub liquidation_bonus: u64,
+    /// Require borrowers to have a KYC-verified, active user profile.
+    pub require_verified_borrower: bool,
+    /// Largest loan per credit tier, in basis points of `max_loan_amount`;
+    /// 0 means the pool does not lend to that tier.
+    pub credit_tier_limits: [u64; CREDIT_TIER_COUNT],
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct LoanParams {
     pub amount: u64,
     pub duration: i64,
+    pub nonce: u64,
+    pub collateral_amount: u64,
+}
+
+/// One pool setting a governance proposal can change. The interest rate
+/// model is left out, since changing it must also accrue interest and record
+/// the new rate in the pool's history.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LendingPoolParam {
+    MaxLoanAmount(u64),
+    MinLoanAmount(u64),
+    GracePeriod(i64),
+    CollateralPrice(u64),
+    MaxLtv(u64),
+    LiquidationThreshold(u64),
+    LiquidationBonus(u64),
+    RequireVerifiedBorrower(bool),
+    CreditTierLimits([u64; CREDIT_TIER_COUNT]),
 }
 
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
 pub struct LendingPool {
     pub is_initialized: bool,
+    pub bump: u8,
     pub authority: Pubkey,
     pub token_mint: Pubkey,
     pub token_vault: Pubkey,
-    pub interest_rate: u64,
+    pub share_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_vault: Pubkey,
+    pub collateral_price: u64,
+    pub max_ltv: u64,
+    pub liquidation_threshold: u64,
+    pub liquidation_bonus: u64,
+    pub require_verified_borrower: bool,
+    pub credit_tier_limits: [u64; CREDIT_TIER_COUNT],
+    pub interest_rate_model: InterestRateModel,
     pub max_loan_amount: u64,
     pub min_loan_amount: u64,
+    pub grace_period: i64,
+    /// Debt owed by borrowers on accruing loans, including interest accrued
+    /// up to `last_accrual_ts`. Defaulted loans are written off and excluded.
     pub total_borrowed: u64,
+    /// Liquidity owned by lenders: deposits plus accrued interest, less
+    /// withdrawals and bad debt.
     pub total_deposited: u64,
+    /// Debt written off by defaults and liquidations, net of what
+    /// liquidations of defaulted loans recovered.
+    pub total_bad_debt: u64,
+    /// Cumulative interest factor on borrows, starting at 1.0.
+    pub borrow_index: Decimal,
+    pub last_accrual_ts: i64,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -43,10 +208,16 @@ pub struct LendingPool {
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
 pub struct Loan {
     pub is_initialized: bool,
+    pub bump: u8,
     pub borrower: Pubkey,
     pub lending_pool: Pubkey,
     pub amount: u64,
-    pub interest_rate: u64,
+    pub amount_repaid: u64,
+    pub collateral_amount: u64,
+    /// Debt as of `borrow_index_snapshot`, reset on every repayment.
+    pub principal: u64,
+    /// Pool borrow index when `principal` was last set.
+    pub borrow_index_snapshot: Decimal,
     pub start_time: i64,
     pub due_time: i64,
     pub status: LoanStatus,
@@ -54,26 +225,93 @@ pub struct Loan {
     pub updated_at: i64,
 }
 
+/// Repayment history of a borrower across all pools.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+pub struct CreditRecord {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub borrower: Pubkey,
+    pub loans_repaid: u32,
+    pub loans_defaulted: u32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Ring buffer of a pool's most recent borrow rates, for charting.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+pub struct InterestRateHistory {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub lending_pool: Pubkey,
+    /// Slot the next snapshot is written to once the buffer is full.
+    pub next_index: u16,
+    pub snapshots: Vec<RateSnapshot>,
+}
+
+/// On-chain record of a loan liquidation, one per loan.
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+pub struct LiquidationEvent {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub loan: Pubkey,
+    pub borrower: Pubkey,
+    pub liquidator: Pubkey,
+    pub lending_pool: Pubkey,
+    /// Debt repaid by the liquidator.
+    pub amount: u64,
+    /// Debt the pool could not recover.
+    pub bad_debt: u64,
+    /// Collateral seized by the liquidator, bounty included.
+    pub collateral_amount: u64,
+    /// Part of the seized collateral paid as the liquidation bonus.
+    pub bounty: u64,
+    pub reason: LiquidationReason,
+    /// Health factors in basis points (10_000 = 1.0); `u64::MAX` once no debt remains.
+    pub health_factor_before: u64,
+    pub health_factor_after: u64,
+    pub timestamp: i64,
+}
+
 impl LendingPool {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // authority
         32 + // token_mint
         32 + // token_vault
-        8 + // interest_rate
+        32 + // share_mint
+        32 + // collateral_mint
+        32 + // collateral_vault
+        8 + // collateral_price
+        8 + // max_ltv
+        8 + // liquidation_threshold
+        8 + // liquidation_bonus
+        1 + // require_verified_borrower
+        CREDIT_TIER_COUNT * 8 + // credit_tier_limits
+        InterestRateModel::LEN + // interest_rate_model
         8 + // max_loan_amount
         8 + // min_loan_amount
+        8 + // grace_period
         8 + // total_borrowed
         8 + // total_deposited
+        8 + // total_bad_debt
+        16 + // borrow_index
+        8 + // last_accrual_ts
         8 + // created_at
         8; // updated_at
 }
 
 impl Loan {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // borrower
         32 + // lending_pool
         8 + // amount
-        8 + // interest_rate
+        8 + // amount_repaid
+        8 + // collateral_amount
+        8 + // principal
+        16 + // borrow_index_snapshot
         8 + // start_time
         8 + // due_time
         1 + // status
@@ -81,9 +319,308 @@ impl Loan {
         8; // updated_at
 }
 
+impl InterestRateModel {
+    pub const LEN: usize = 1 + // variant
+        4 * 8; // parameters, zero-padded for smaller variants
+    
+    /// Checks that the model is well formed.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        match *self {
+            InterestRateModel::Fixed { .. } => Ok(()),
+            InterestRateModel::Kinked { optimal_utilization, .. } => {
+                if optimal_utilization == 0 || optimal_utilization > BPS {
+                    return Err(UnityVaultError::InvalidInterestRateModel.into());
+                }
+                Ok(())
+            }
+        }
+    }
+    
+    /// Annual borrow rate in basis points at `utilization` basis points.
+    pub fn borrow_rate(&self, utilization: u64) -> Result<u64, ProgramError> {
+        match *self {
+            InterestRateModel::Fixed { rate } => Ok(rate),
+            InterestRateModel::Kinked { base_rate, slope1, slope2, optimal_utilization } => {
+                let utilization = utilization.min(BPS);
+                if utilization <= optimal_utilization {
+                    to_u64(mul_div_floor(slope1 as u128, utilization as u128, optimal_utilization as u128)?)?
+                        .try_add(base_rate)
+                } else {
+                    to_u64(mul_div_floor(
+                        slope2 as u128,
+                        (utilization - optimal_utilization) as u128,
+                        (BPS - optimal_utilization) as u128,
+                    )?)?
+                    .try_add(base_rate)?
+                    .try_add(slope1)
+                }
+            }
+        }
+    }
+}
+
+impl CreditRecord {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // borrower
+        4 + // loans_repaid
+        4 + // loans_defaulted
+        8 + // created_at
+        8; // updated_at
+    
+    /// Score from 0 to 1000. New borrowers start at 500; each repaid loan
+    /// adds 100 and each default takes away 250.
+    pub fn score(&self) -> u16 {
+        let score = 500 + 100 * self.loans_repaid as i64 - 250 * self.loans_defaulted as i64;
+        score.clamp(0, 1000) as u16
+    }
+    
+    pub fn tier(&self) -> CreditTier {
+        match self.score() {
+            0..=399 => CreditTier::Poor,
+            400..=699 => CreditTier::Standard,
+            700..=899 => CreditTier::Good,
+            _ => CreditTier::Excellent,
+        }
+    }
+}
+
+impl InterestRateHistory {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // lending_pool
+        2 + // next_index
+        2 + // snapshots length
+        MAX_RATE_SNAPSHOTS * (8 + 8 + 8); // snapshots
+    
+    /// Appends `snapshot`, overwriting the oldest one once the buffer is full.
+    pub fn record(&mut self, snapshot: RateSnapshot) {
+        if self.snapshots.len() < MAX_RATE_SNAPSHOTS {
+            self.snapshots.push(snapshot);
+        } else {
+            self.snapshots[self.next_index as usize] = snapshot;
+        }
+        self.next_index = ((self.next_index as usize + 1) % MAX_RATE_SNAPSHOTS) as u16;
+    }
+    
+    /// Snapshots from oldest to newest.
+    pub fn chronological(&self) -> impl Iterator<Item = &RateSnapshot> {
+        let split = if self.snapshots.len() < MAX_RATE_SNAPSHOTS { 0 } else { self.next_index as usize };
+        self.snapshots[split..].iter().chain(self.snapshots[..split].iter())
+    }
+}
+
+impl LendingPool {
+    /// Applies `param`, failing if the pool's settings would leave the
+    /// ranges `init_lending_pool` accepts.
+    pub fn set_param(&mut self, param: LendingPoolParam) -> Result<(), ProgramError> {
+        match param {
+            LendingPoolParam::MaxLoanAmount(amount) => self.max_loan_amount = amount,
+            LendingPoolParam::MinLoanAmount(amount) => self.min_loan_amount = amount,
+            LendingPoolParam::GracePeriod(period) => self.grace_period = period,
+            LendingPoolParam::CollateralPrice(price) => self.collateral_price = price,
+            LendingPoolParam::MaxLtv(ltv) => self.max_ltv = ltv,
+            LendingPoolParam::LiquidationThreshold(threshold) => self.liquidation_threshold = threshold,
+            LendingPoolParam::LiquidationBonus(bonus) => self.liquidation_bonus = bonus,
+            LendingPoolParam::RequireVerifiedBorrower(required) => self.require_verified_borrower = required,
+            LendingPoolParam::CreditTierLimits(limits) => self.credit_tier_limits = limits,
+        }
+        
+        if self.grace_period < 0
+            || self.collateral_price == 0
+            || self.max_ltv > self.liquidation_threshold
+            || self.liquidation_threshold > BPS
+            || self.liquidation_bonus > BPS
+            || self.credit_tier_limits.iter().any(|&limit| limit > BPS)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        
+        Ok(())
+    }
+    
+    /// Seeds for signing as the pool PDA, which owns the vault and the share mint.
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            LENDING_POOL_SEED,
+            self.authority.as_ref(),
+            self.token_mint.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+    
+    /// Value of `collateral_amount` in pool token base units, rounded down.
+    pub fn collateral_value(&self, collateral_amount: u64) -> Result<u64, ProgramError> {
+        to_u64(mul_div_floor(collateral_amount as u128, self.collateral_price as u128, PRICE_SCALE)?)
+    }
+    
+    /// Collateral base units worth `value` pool token base units, rounded up.
+    pub fn collateral_for_value(&self, value: u64) -> Result<u64, ProgramError> {
+        to_u64(mul_div_ceil(value as u128, PRICE_SCALE, self.collateral_price as u128)?)
+    }
+    
+    /// Health factor in basis points: collateral value at the liquidation
+    /// threshold over debt, rounded down. Below 10_000 the loan can be
+    /// liquidated.
+    pub fn health_factor(&self, collateral_amount: u64, debt: u64) -> Result<u64, ProgramError> {
+        if debt == 0 {
+            return Ok(u64::MAX);
+        }
+        let health_factor = mul_div_floor(
+            self.collateral_value(collateral_amount)? as u128,
+            self.liquidation_threshold as u128,
+            debt as u128,
+        )?;
+        Ok(u64::try_from(health_factor).unwrap_or(u64::MAX))
+    }
+    
+    /// Grows the borrow index by the interest accrued since `last_accrual_ts`
+    /// at the current borrow rate, and adds that interest to both the pool's
+    /// debt and its lenders' liquidity.
+    pub fn accrue_interest(&mut self, now: i64) -> Result<(), ProgramError> {
+        let elapsed = now.saturating_sub(self.last_accrual_ts);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        
+        // Interest rounds down, in borrowers' favor at the pool level; each
+        // loan's debt rounds up against the index
+        let period_rate = Decimal::from_bps(self.borrow_rate()?)?
+            .try_mul_int(elapsed as u64)?
+            .try_div_int(SECONDS_PER_YEAR)?;
+        let index_delta = self.borrow_index.try_mul(period_rate)?;
+        let interest = to_u64(mul_div_floor(
+            self.total_borrowed as u128,
+            index_delta.0,
+            self.borrow_index.0,
+        )?)?;
+        
+        self.borrow_index = self.borrow_index.try_add(index_delta)?;
+        self.total_borrowed = self.total_borrowed.try_add(interest)?;
+        self.total_deposited = self.total_deposited.try_add(interest)?;
+        self.last_accrual_ts = now;
+        
+        Ok(())
+    }
+    
+    /// Share of deposits lent out, in basis points.
+    pub fn utilization(&self) -> u64 {
+        if self.total_deposited == 0 {
+            return 0;
+        }
+        ratio_bps(self.total_borrowed, self.total_deposited)
+            .unwrap_or(BPS)
+            .min(BPS)
+    }
+    
+    /// Annual borrow rate in basis points at the pool's current utilization.
+    pub fn borrow_rate(&self) -> Result<u64, ProgramError> {
+        self.interest_rate_model.borrow_rate(self.utilization())
+    }
+    
+    /// Snapshot of the pool's current utilization and borrow rate.
+    pub fn rate_snapshot(&self, timestamp: i64) -> Result<RateSnapshot, ProgramError> {
+        Ok(RateSnapshot {
+            timestamp,
+            utilization: self.utilization(),
+            borrow_rate: self.borrow_rate()?,
+        })
+    }
+    
+    /// Largest loan the pool makes to a borrower in `tier`, or 0 if it does
+    /// not lend to that tier.
+    pub fn credit_limit(&self, tier: CreditTier) -> Result<u64, ProgramError> {
+        bps_of_floor(self.max_loan_amount, self.credit_tier_limits[tier as usize])
+    }
+    
+    /// Liquidity that is not lent out.
+    pub fn available_liquidity(&self) -> u64 {
+        self.total_deposited.saturating_sub(self.total_borrowed)
+    }
+    
+    /// Shares minted for depositing `amount`, given the current share supply,
+    /// rounded down.
+    pub fn shares_for_deposit(&self, amount: u64, share_supply: u64) -> Result<u64, ProgramError> {
+        if share_supply == 0 {
+            return Ok(amount);
+        }
+        // Bad debt can leave shares outstanding with nothing behind them; a
+        // new deposit would then be handed to the holders of those shares
+        if self.total_deposited == 0 {
+            return Err(UnityVaultError::PoolInsolvent.into());
+        }
+        to_u64(mul_div_floor(amount as u128, share_supply as u128, self.total_deposited as u128)?)
+    }
+    
+    /// Liquidity paid out for redeeming `shares`, given the current share
+    /// supply, rounded down.
+    pub fn amount_for_shares(&self, shares: u64, share_supply: u64) -> Result<u64, ProgramError> {
+        to_u64(mul_div_floor(shares as u128, self.total_deposited as u128, share_supply as u128)?)
+    }
+}
+
+impl Loan {
+    /// Amount owed at the pool's `borrow_index`, rounded up. Defaulted loans
+    /// no longer accrue: their debt is frozen in `principal`.
+    pub fn debt(&self, borrow_index: Decimal) -> Result<u64, ProgramError> {
+        if self.status == LoanStatus::Defaulted {
+            return Ok(self.principal);
+        }
+        to_u64(mul_div_ceil(self.principal as u128, borrow_index.0, self.borrow_index_snapshot.0)?)
+    }
+}
+
+impl LiquidationEvent {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // loan
+        32 + // borrower
+        32 + // liquidator
+        32 + // lending_pool
+        8 + // amount
+        8 + // bad_debt
+        8 + // collateral_amount
+        8 + // bounty
+        1 + // reason
+        8 + // health_factor_before
+        8 + // health_factor_after
+        8; // timestamp
+}
+
 impl Sealed for LendingPool {}
+
+impl ProgramAccount for LendingPool {
+    const ACCOUNT_TYPE: AccountType = AccountType::LendingPool;
+}
 impl Sealed for Loan {}
 
+impl ProgramAccount for Loan {
+    const ACCOUNT_TYPE: AccountType = AccountType::Loan;
+}
+
+impl Sealed for InterestRateModel {}
+
+impl Sealed for CreditRecord {}
+
+impl ProgramAccount for CreditRecord {
+    const ACCOUNT_TYPE: AccountType = AccountType::CreditRecord;
+}
+
+impl Sealed for InterestRateHistory {}
+
+impl ProgramAccount for InterestRateHistory {
+    const ACCOUNT_TYPE: AccountType = AccountType::InterestRateHistory;
+}
+
+impl Sealed for LiquidationEvent {}
+
+impl ProgramAccount for LiquidationEvent {
+    const ACCOUNT_TYPE: AccountType = AccountType::LiquidationEvent;
+}
+
 impl IsInitialized for LendingPool {
     fn is_initialized(&self) -> bool {
         self.is_initialized
@@ -96,15 +633,39 @@ impl IsInitialized for Loan {
     }
 }
 
+impl IsInitialized for CreditRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for InterestRateHistory {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for LiquidationEvent {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
 impl Pack for LendingPool {
     const LEN: usize = Self::LEN;
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        dst[offset] = self.bump;
+        offset += 1;
+        
         dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
         offset += 32;
         
@@ -114,21 +675,62 @@ impl Pack for LendingPool {
         dst[offset..offset + 32].copy_from_slice(&self.token_vault.to_bytes());
         offset += 32;
         
-        dst[offset..offset + 8].copy_from_slice(&self.interest_rate.to_le_bytes());
+        dst[offset..offset + 32].copy_from_slice(&self.share_mint.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 32].copy_from_slice(&self.collateral_mint.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 32].copy_from_slice(&self.collateral_vault.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.collateral_price.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.max_ltv.to_le_bytes());
         offset += 8;
         
+        dst[offset..offset + 8].copy_from_slice(&self.liquidation_threshold.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.liquidation_bonus.to_le_bytes());
+        offset += 8;
+        
+        dst[offset] = self.require_verified_borrower as u8;
+        offset += 1;
+        
+        for limit in self.credit_tier_limits {
+            dst[offset..offset + 8].copy_from_slice(&limit.to_le_bytes());
+            offset += 8;
+        }
+        
+        self.interest_rate_model.pack_into_slice(&mut dst[offset..offset + InterestRateModel::LEN]);
+        offset += InterestRateModel::LEN;
+        
         dst[offset..offset + 8].copy_from_slice(&self.max_loan_amount.to_le_bytes());
         offset += 8;
         
         dst[offset..offset + 8].copy_from_slice(&self.min_loan_amount.to_le_bytes());
         offset += 8;
         
+        dst[offset..offset + 8].copy_from_slice(&self.grace_period.to_le_bytes());
+        offset += 8;
+        
         dst[offset..offset + 8].copy_from_slice(&self.total_borrowed.to_le_bytes());
         offset += 8;
         
         dst[offset..offset + 8].copy_from_slice(&self.total_deposited.to_le_bytes());
         offset += 8;
         
+        dst[offset..offset + 8].copy_from_slice(&self.total_bad_debt.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 16].copy_from_slice(&self.borrow_index.0.to_le_bytes());
+        offset += 16;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.last_accrual_ts.to_le_bytes());
+        offset += 8;
+        
         dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
         offset += 8;
         
@@ -136,11 +738,15 @@ impl Pack for LendingPool {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        let bump = src[offset];
+        offset += 1;
+        
         let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| ProgramError::InvalidAccountData)?;
         let authority = Pubkey::from(authority_bytes);
@@ -156,21 +762,69 @@ impl Pack for LendingPool {
         let token_vault = Pubkey::from(token_vault_bytes);
         offset += 32;
         
-        let interest_rate = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        let share_mint_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let share_mint = Pubkey::from(share_mint_bytes);
+        offset += 32;
+        
+        let collateral_mint_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let collateral_mint = Pubkey::from(collateral_mint_bytes);
+        offset += 32;
+        
+        let collateral_vault_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let collateral_vault = Pubkey::from(collateral_vault_bytes);
+        offset += 32;
+        
+        let collateral_price = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let max_ltv = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let liquidation_threshold = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let liquidation_bonus = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
+        let require_verified_borrower = src[offset] != 0;
+        offset += 1;
+        
+        let mut credit_tier_limits = [0u64; CREDIT_TIER_COUNT];
+        for limit in credit_tier_limits.iter_mut() {
+            *limit = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+        }
+        
+        let interest_rate_model = InterestRateModel::unpack_from_slice(&src[offset..offset + InterestRateModel::LEN])?;
+        offset += InterestRateModel::LEN;
+        
         let max_loan_amount = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
         let min_loan_amount = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
+        let grace_period = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
         let total_borrowed = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
         let total_deposited = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
+        let total_bad_debt = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let borrow_index = Decimal(u128::from_le_bytes(src[offset..offset + 16].try_into().unwrap()));
+        offset += 16;
+        
+        let last_accrual_ts = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
         let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
@@ -178,29 +832,233 @@ impl Pack for LendingPool {
         
         Ok(LendingPool {
             is_initialized,
+            bump,
             authority,
             token_mint,
             token_vault,
-            interest_rate,
+            share_mint,
+            collateral_mint,
+            collateral_vault,
+            collateral_price,
+            max_ltv,
+            liquidation_threshold,
+            liquidation_bonus,
+            require_verified_borrower,
+            credit_tier_limits,
+            interest_rate_model,
             max_loan_amount,
             min_loan_amount,
+            grace_period,
             total_borrowed,
             total_deposited,
+            total_bad_debt,
+            borrow_index,
+            last_accrual_ts,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl Pack for InterestRateModel {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (variant, values) = match *self {
+            InterestRateModel::Fixed { rate } => (0, [rate, 0, 0, 0]),
+            InterestRateModel::Kinked { base_rate, slope1, slope2, optimal_utilization } => {
+                (1, [base_rate, slope1, slope2, optimal_utilization])
+            }
+        };
+        
+        dst[0] = variant;
+        let mut offset = 1;
+        for value in values {
+            dst[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+            offset += 8;
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut values = [0u64; 4];
+        let mut offset = 1;
+        for value in values.iter_mut() {
+            *value = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+        }
+        
+        match src[0] {
+            0 => Ok(InterestRateModel::Fixed { rate: values[0] }),
+            1 => Ok(InterestRateModel::Kinked {
+                base_rate: values[0],
+                slope1: values[1],
+                slope2: values[2],
+                optimal_utilization: values[3],
+            }),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+impl Pack for CreditRecord {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        dst[offset..offset + 32].copy_from_slice(&self.borrower.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 4].copy_from_slice(&self.loans_repaid.to_le_bytes());
+        offset += 4;
+        
+        dst[offset..offset + 4].copy_from_slice(&self.loans_defaulted.to_le_bytes());
+        offset += 4;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        let bump = src[offset];
+        offset += 1;
+        
+        let borrower_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let borrower = Pubkey::from(borrower_bytes);
+        offset += 32;
+        
+        let loans_repaid = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        
+        let loans_defaulted = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(CreditRecord {
+            is_initialized,
+            bump,
+            borrower,
+            loans_repaid,
+            loans_defaulted,
             created_at,
             updated_at,
         })
     }
 }
 
+impl Pack for InterestRateHistory {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        dst[offset..offset + 32].copy_from_slice(&self.lending_pool.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 2].copy_from_slice(&self.next_index.to_le_bytes());
+        offset += 2;
+        
+        dst[offset..offset + 2].copy_from_slice(&(self.snapshots.len() as u16).to_le_bytes());
+        offset += 2;
+        
+        for snapshot in &self.snapshots {
+            dst[offset..offset + 8].copy_from_slice(&snapshot.timestamp.to_le_bytes());
+            dst[offset + 8..offset + 16].copy_from_slice(&snapshot.utilization.to_le_bytes());
+            dst[offset + 16..offset + 24].copy_from_slice(&snapshot.borrow_rate.to_le_bytes());
+            offset += 24;
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        let bump = src[offset];
+        offset += 1;
+        
+        let lending_pool_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let lending_pool = Pubkey::from(lending_pool_bytes);
+        offset += 32;
+        
+        let next_index = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
+        let snapshots_len = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap()) as usize;
+        offset += 2;
+        if snapshots_len > MAX_RATE_SNAPSHOTS || next_index as usize >= MAX_RATE_SNAPSHOTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        
+        let mut snapshots = Vec::with_capacity(snapshots_len);
+        for _ in 0..snapshots_len {
+            snapshots.push(RateSnapshot {
+                timestamp: i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap()),
+                utilization: u64::from_le_bytes(src[offset + 8..offset + 16].try_into().unwrap()),
+                borrow_rate: u64::from_le_bytes(src[offset + 16..offset + 24].try_into().unwrap()),
+            });
+            offset += 24;
+        }
+        
+        Ok(InterestRateHistory {
+            is_initialized,
+            bump,
+            lending_pool,
+            next_index,
+            snapshots,
+        })
+    }
+}
+
 impl Pack for Loan {
     const LEN: usize = Self::LEN;
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        dst[offset] = self.bump;
+        offset += 1;
+        
         dst[offset..offset + 32].copy_from_slice(&self.borrower.to_bytes());
         offset += 32;
         
@@ -210,9 +1068,18 @@ impl Pack for Loan {
         dst[offset..offset + 8].copy_from_slice(&self.amount.to_le_bytes());
         offset += 8;
         
-        dst[offset..offset + 8].copy_from_slice(&self.interest_rate.to_le_bytes());
+        dst[offset..offset + 8].copy_from_slice(&self.amount_repaid.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.collateral_amount.to_le_bytes());
         offset += 8;
         
+        dst[offset..offset + 8].copy_from_slice(&self.principal.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 16].copy_from_slice(&self.borrow_index_snapshot.0.to_le_bytes());
+        offset += 16;
+        
         dst[offset..offset + 8].copy_from_slice(&self.start_time.to_le_bytes());
         offset += 8;
         
@@ -229,11 +1096,15 @@ impl Pack for Loan {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        let bump = src[offset];
+        offset += 1;
+        
         let borrower_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| ProgramError::InvalidAccountData)?;
         let borrower = Pubkey::from(borrower_bytes);
@@ -247,9 +1118,18 @@ impl Pack for Loan {
         let amount = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
-        let interest_rate = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        let amount_repaid = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let collateral_amount = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
+        let principal = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let borrow_index_snapshot = Decimal(u128::from_le_bytes(src[offset..offset + 16].try_into().unwrap()));
+        offset += 16;
+        
         let start_time = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
@@ -260,6 +1140,7 @@ impl Pack for Loan {
             0 => LoanStatus::Active,
             1 => LoanStatus::Repaid,
             2 => LoanStatus::Defaulted,
+            3 => LoanStatus::Liquidated,
             _ => return Err(ProgramError::InvalidAccountData),
         };
         offset += 1;
@@ -271,10 +1152,14 @@ impl Pack for Loan {
         
         Ok(Loan {
             is_initialized,
+            bump,
             borrower,
             lending_pool,
             amount,
-            interest_rate,
+            amount_repaid,
+            collateral_amount,
+            principal,
+            borrow_index_snapshot,
             start_time,
             due_time,
             status,
@@ -282,4 +1167,101 @@ impl Pack for Loan {
             updated_at,
         })
     }
-} 
\ No newline at end of file
+}
+
+impl Pack for LiquidationEvent {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        for key in [&self.loan, &self.borrower, &self.liquidator, &self.lending_pool] {
+            dst[offset..offset + 32].copy_from_slice(&key.to_bytes());
+            offset += 32;
+        }
+        
+        for value in [self.amount, self.bad_debt, self.collateral_amount, self.bounty] {
+            dst[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+            offset += 8;
+        }
+        
+        dst[offset] = self.reason as u8;
+        offset += 1;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.health_factor_before.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.health_factor_after.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 8].copy_from_slice(&self.timestamp.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        let bump = src[offset];
+        offset += 1;
+        
+        let mut keys = [Pubkey::default(); 4];
+        for key in keys.iter_mut() {
+            let key_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+            *key = Pubkey::from(key_bytes);
+            offset += 32;
+        }
+        let [loan, borrower, liquidator, lending_pool] = keys;
+        
+        let mut values = [0u64; 4];
+        for value in values.iter_mut() {
+            *value = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+            offset += 8;
+        }
+        let [amount, bad_debt, collateral_amount, bounty] = values;
+        
+        let reason = match src[offset] {
+            0 => LiquidationReason::Default,
+            1 => LiquidationReason::Undercollateralized,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 1;
+        
+        let health_factor_before = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let health_factor_after = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let timestamp = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(LiquidationEvent {
+            is_initialized,
+            bump,
+            loan,
+            borrower,
+            liquidator,
+            lending_pool,
+            amount,
+            bad_debt,
+            collateral_amount,
+            bounty,
+            reason,
+            health_factor_before,
+            health_factor_after,
+            timestamp,
+        })
+    }
+}
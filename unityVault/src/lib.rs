@@ -3,16 +3,24 @@ use solana_program::{
     entrypoint,
     entrypoint::ProgramResult,
     msg,
+    program_error::PrintProgramError,
     pubkey::Pubkey,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
+pub mod error;
 pub mod user;
 pub mod governance;
 pub mod community;
 pub mod lending;
 pub mod tokenization;
+pub mod math;
+pub mod utils;
 
+#[cfg(test)]
+mod test_utils;
+
+pub use error::UnityVaultError;
 
 entrypoint!(process_instruction);
 
@@ -27,7 +35,7 @@ pub fn process_instruction<'a>(
     let instruction = Instruction::try_from_slice(instruction_data)
         .map_err(|_| solana_program::program_error::ProgramError::InvalidInstructionData)?;
     
-    match instruction {
+    let result = match instruction {
         Instruction::User(user_instruction) => match user_instruction {
             UserInstruction::CreateUserProfile(params) => {
                 user::instructions::create_user_profile(program_id, accounts, params)
@@ -35,27 +43,123 @@ pub fn process_instruction<'a>(
             UserInstruction::UpdateUserProfile(params) => {
                 user::instructions::update_user_profile(program_id, accounts, params)
             }
-            UserInstruction::EnableTwoFactor { secret, backup_codes } => {
-                user::instructions::enable_two_factor(program_id, accounts, secret, backup_codes)
+            UserInstruction::EnableTwoFactor(params) => {
+                user::instructions::enable_two_factor(program_id, accounts, params)
+            }
+            UserInstruction::ConsumeBackupCode(code) => {
+                user::instructions::consume_backup_code(program_id, accounts, code)
+            }
+            UserInstruction::DisableTwoFactor { backup_code } => {
+                user::instructions::disable_two_factor(program_id, accounts, backup_code)
+            }
+            UserInstruction::InitKycRegistry { verification_validity } => {
+                user::instructions::init_kyc_registry(program_id, accounts, verification_validity)
+            }
+            UserInstruction::AddKycVerifier(verifier) => {
+                user::instructions::add_kyc_verifier(program_id, accounts, verifier)
+            }
+            UserInstruction::RemoveKycVerifier(verifier) => {
+                user::instructions::remove_kyc_verifier(program_id, accounts, verifier)
+            }
+            UserInstruction::VerifyKyc(params) => {
+                user::instructions::verify_kyc(program_id, accounts, params)
             }
-            UserInstruction::VerifyKyc(kyc_data) => {
-                user::instructions::verify_kyc(program_id, accounts, kyc_data)
+            UserInstruction::RejectKyc { reason } => {
+                user::instructions::reject_kyc(program_id, accounts, reason)
+            }
+            UserInstruction::ExpireKyc => {
+                user::instructions::expire_kyc(program_id, accounts)
             }
         },
         Instruction::Governance(governance_instruction) => match governance_instruction {
-            GovernanceInstruction::CreateProposal(params) => {
-                governance::instructions::create_proposal(program_id, accounts, params)
+            GovernanceInstruction::CreateProposal { nonce, params } => {
+                governance::instructions::create_proposal(program_id, accounts, nonce, params)
             }
             GovernanceInstruction::UpdateProposal(params) => {
                 governance::instructions::update_proposal(program_id, accounts, params)
             }
+            GovernanceInstruction::ActivateProposal => {
+                governance::instructions::activate_proposal(program_id, accounts)
+            }
             GovernanceInstruction::VoteProposal(vote_type) => {
                 governance::instructions::vote_proposal(program_id, accounts, vote_type)
             }
+            GovernanceInstruction::FinalizeProposal => {
+                governance::instructions::finalize_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::CancelProposal => {
+                governance::instructions::cancel_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::ExecuteProposal => {
+                governance::instructions::execute_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::ChangeVote(vote_type) => {
+                governance::instructions::change_vote(program_id, accounts, vote_type)
+            }
+            GovernanceInstruction::RelinquishVote => {
+                governance::instructions::relinquish_vote(program_id, accounts)
+            }
+            GovernanceInstruction::InitRealm => {
+                governance::instructions::init_realm(program_id, accounts)
+            }
+            GovernanceInstruction::DepositGoverningTokens(amount) => {
+                governance::instructions::deposit_governing_tokens(program_id, accounts, amount)
+            }
+            GovernanceInstruction::WithdrawGoverningTokens(amount) => {
+                governance::instructions::withdraw_governing_tokens(program_id, accounts, amount)
+            }
+            GovernanceInstruction::DelegateVotes { expires_at } => {
+                governance::instructions::delegate_votes(program_id, accounts, expires_at)
+            }
+            GovernanceInstruction::RevokeDelegation => {
+                governance::instructions::revoke_delegation(program_id, accounts)
+            }
+            GovernanceInstruction::VoteAsDelegate(vote_type) => {
+                governance::instructions::vote_as_delegate(program_id, accounts, vote_type)
+            }
+            GovernanceInstruction::InsertProposalAction { hold_up_time, action } => {
+                governance::instructions::insert_proposal_action(program_id, accounts, hold_up_time, action)
+            }
+            GovernanceInstruction::SetRealmConfig(config) => {
+                governance::instructions::set_realm_config(program_id, accounts, config)
+            }
+            GovernanceInstruction::VetoProposal => {
+                governance::instructions::veto_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::AddProposalOption(label) => {
+                governance::instructions::add_proposal_option(program_id, accounts, label)
+            }
+            GovernanceInstruction::VoteOptions(choices) => {
+                governance::instructions::vote_options(program_id, accounts, choices)
+            }
+            GovernanceInstruction::InitGovernanceConfig(categories) => {
+                governance::instructions::init_governance_config(program_id, accounts, categories)
+            }
+            GovernanceInstruction::SetCategoryConfig { category, config } => {
+                governance::instructions::set_category_config(program_id, accounts, category, config)
+            }
+            GovernanceInstruction::SupersedeProposal => {
+                governance::instructions::supersede_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::InitTreasuryLedger => {
+                governance::instructions::init_treasury_ledger(program_id, accounts)
+            }
+            GovernanceInstruction::DepositToTreasury { mint, amount } => {
+                governance::instructions::deposit_to_treasury(program_id, accounts, mint, amount)
+            }
+            GovernanceInstruction::ChangeVoteOptions(choices) => {
+                governance::instructions::change_vote_options(program_id, accounts, choices)
+            }
+            GovernanceInstruction::VoteOptionsAsDelegate(choices) => {
+                governance::instructions::vote_options_as_delegate(program_id, accounts, choices)
+            }
+            GovernanceInstruction::AddTreasuryMint(mint) => {
+                governance::instructions::add_treasury_mint(program_id, accounts, mint)
+            }
         },
         Instruction::Community(community_instruction) => match community_instruction {
-            CommunityInstruction::CreateCommunity(params) => {
-                community::instructions::create_community(program_id, accounts, params)
+            CommunityInstruction::CreateCommunity { nonce, params } => {
+                community::instructions::create_community(program_id, accounts, nonce, params)
             }
             CommunityInstruction::UpdateCommunity(params) => {
                 community::instructions::update_community(program_id, accounts, params)
@@ -71,8 +175,29 @@ pub fn process_instruction<'a>(
             LendingInstruction::CreateLoan(params) => {
                 lending::instructions::create_loan(program_id, accounts, params)
             }
-            LendingInstruction::RepayLoan => {
-                lending::instructions::repay_loan(program_id, accounts)
+            LendingInstruction::RepayLoan(amount) => {
+                lending::instructions::repay_loan(program_id, accounts, amount)
+            }
+            LendingInstruction::Deposit(amount) => {
+                lending::instructions::deposit(program_id, accounts, amount)
+            }
+            LendingInstruction::Withdraw(shares) => {
+                lending::instructions::withdraw(program_id, accounts, shares)
+            }
+            LendingInstruction::MarkDefaulted => {
+                lending::instructions::mark_defaulted(program_id, accounts)
+            }
+            LendingInstruction::LiquidateLoan => {
+                lending::instructions::liquidate_loan(program_id, accounts)
+            }
+            LendingInstruction::UpdateInterestRateModel(model) => {
+                lending::instructions::update_interest_rate_model(program_id, accounts, model)
+            }
+            LendingInstruction::RecordInterestRate => {
+                lending::instructions::record_interest_rate(program_id, accounts)
+            }
+            LendingInstruction::AccrueInterest => {
+                lending::instructions::accrue_interest(program_id, accounts)
             }
         },
         Instruction::Tokenization(tokenization_instruction) => match tokenization_instruction {
@@ -86,7 +211,13 @@ pub fn process_instruction<'a>(
                 tokenization::instructions::burn_tokens(program_id, accounts, amount)
             }
         },
+    };
+    
+    if let Err(error) = &result {
+        error.print::<UnityVaultError>();
     }
+    
+    result
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -102,23 +233,75 @@ pub enum Instruction {
 pub enum UserInstruction {
     CreateUserProfile(user::UserProfileParams),
     UpdateUserProfile(user::UserProfileParams),
-    EnableTwoFactor {
-        secret: String,
-        backup_codes: Vec<String>,
+    EnableTwoFactor(user::TwoFactorParams),
+    ConsumeBackupCode(String),
+    DisableTwoFactor {
+        backup_code: Option<String>,
+    },
+    InitKycRegistry {
+        verification_validity: i64,
+    },
+    AddKycVerifier(Pubkey),
+    RemoveKycVerifier(Pubkey),
+    VerifyKyc(user::KycParams),
+    RejectKyc {
+        reason: u16,
     },
-    VerifyKyc(user::KycData),
+    ExpireKyc,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum GovernanceInstruction {
-    CreateProposal(crate::governance::state::ProposalParams),
+    CreateProposal {
+        nonce: u64,
+        params: crate::governance::state::ProposalParams,
+    },
     UpdateProposal(crate::governance::state::ProposalParams),
     VoteProposal(crate::governance::state::VoteType),
+    ActivateProposal,
+    FinalizeProposal,
+    CancelProposal,
+    ExecuteProposal,
+    ChangeVote(crate::governance::state::VoteType),
+    RelinquishVote,
+    InitRealm,
+    DepositGoverningTokens(u64),
+    WithdrawGoverningTokens(u64),
+    DelegateVotes {
+        expires_at: i64,
+    },
+    RevokeDelegation,
+    VoteAsDelegate(crate::governance::state::VoteType),
+    InsertProposalAction {
+        hold_up_time: i64,
+        action: crate::governance::state::Action,
+    },
+    SetRealmConfig(crate::governance::state::RealmConfig),
+    VetoProposal,
+    AddProposalOption(String),
+    VoteOptions(Vec<u8>),
+    InitGovernanceConfig([crate::governance::state::CategoryConfig; crate::governance::state::PROPOSAL_CATEGORY_COUNT]),
+    SetCategoryConfig {
+        category: crate::governance::state::ProposalCategory,
+        config: crate::governance::state::CategoryConfig,
+    },
+    SupersedeProposal,
+    InitTreasuryLedger,
+    DepositToTreasury {
+        mint: Option<Pubkey>,
+        amount: u64,
+    },
+    ChangeVoteOptions(Vec<u8>),
+    VoteOptionsAsDelegate(Vec<u8>),
+    AddTreasuryMint(Pubkey),
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum CommunityInstruction {
-    CreateCommunity(crate::community::state::CommunityParams),
+    CreateCommunity {
+        nonce: u64,
+        params: crate::community::state::CommunityParams,
+    },
     UpdateCommunity(crate::community::state::CommunityParams),
     SuspendCommunity,
 }
@@ -127,7 +310,14 @@ pub enum CommunityInstruction {
 pub enum LendingInstruction {
     InitLendingPool(crate::lending::state::LendingPoolParams),
     CreateLoan(crate::lending::state::LoanParams),
-    RepayLoan,
+    RepayLoan(u64),
+    Deposit(u64),
+    Withdraw(u64),
+    MarkDefaulted,
+    LiquidateLoan,
+    UpdateInterestRateModel(crate::lending::state::InterestRateModel),
+    RecordInterestRate,
+    AccrueInterest,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
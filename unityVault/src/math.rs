@@ -0,0 +1,301 @@
+//! Checked integer and fixed-point arithmetic for financial calculations.
+//!
+//! Nothing here wraps, panics or uses floating point: every operation returns
+//! `UnityVaultError::MathOverflow` on overflow, underflow or division by zero,
+//! and every function that divides says which way it rounds.
+
+use solana_program::program_error::ProgramError;
+use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+
+/// Scale of `Decimal`: 1.0 is `WAD`.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// Basis points in 1.0.
+pub const BPS: u64 = 10_000;
+
+fn overflow() -> ProgramError {
+    UnityVaultError::MathOverflow.into()
+}
+
+/// Checked arithmetic that fails with `MathOverflow` instead of returning `None`.
+pub trait TryMath: Sized {
+    fn try_add(self, rhs: Self) -> Result<Self, ProgramError>;
+    fn try_sub(self, rhs: Self) -> Result<Self, ProgramError>;
+    fn try_mul(self, rhs: Self) -> Result<Self, ProgramError>;
+    /// Division rounded toward zero.
+    fn try_div(self, rhs: Self) -> Result<Self, ProgramError>;
+}
+
+macro_rules! impl_try_math {
+    ($($t:ty),*) => {
+        $(
+            impl TryMath for $t {
+                fn try_add(self, rhs: Self) -> Result<Self, ProgramError> {
+                    self.checked_add(rhs).ok_or_else(overflow)
+                }
+
+                fn try_sub(self, rhs: Self) -> Result<Self, ProgramError> {
+                    self.checked_sub(rhs).ok_or_else(overflow)
+                }
+
+                fn try_mul(self, rhs: Self) -> Result<Self, ProgramError> {
+                    self.checked_mul(rhs).ok_or_else(overflow)
+                }
+
+                fn try_div(self, rhs: Self) -> Result<Self, ProgramError> {
+                    self.checked_div(rhs).ok_or_else(overflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_math!(u32, u64, u128, i64);
+
+/// Narrows to `u64`, failing if the value does not fit.
+pub fn to_u64(value: u128) -> Result<u64, ProgramError> {
+    u64::try_from(value).map_err(|_| overflow())
+}
+
+/// Full 256-bit product of `a` and `b`, as `(high, low)` halves.
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let high_high = a_high * b_high;
+    
+    // Below 3 * 2^64, so this cannot overflow
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    let low = (low_low & LOW) | (middle << 64);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// `a * b / c` and its remainder, with a 256-bit intermediate product so only
+/// a quotient that does not fit in `u128` overflows.
+fn mul_div_rem(a: u128, b: u128, c: u128) -> Result<(u128, u128), ProgramError> {
+    if c == 0 {
+        return Err(overflow());
+    }
+    let (high, low) = widening_mul(a, b);
+    if high == 0 {
+        return Ok((low / c, low % c));
+    }
+    if high >= c {
+        return Err(overflow());
+    }
+    
+    // Shift-subtract long division of `high:low` by `c`; `remainder < c`
+    // throughout, so the quotient fits in 128 bits
+    let mut quotient = 0u128;
+    let mut remainder = high;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1 << bit;
+        }
+    }
+    Ok((quotient, remainder))
+}
+
+/// `a * b / c`, rounded down.
+pub fn mul_div_floor(a: u128, b: u128, c: u128) -> Result<u128, ProgramError> {
+    Ok(mul_div_rem(a, b, c)?.0)
+}
+
+/// `a * b / c`, rounded up.
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Result<u128, ProgramError> {
+    let (quotient, remainder) = mul_div_rem(a, b, c)?;
+    if remainder == 0 {
+        Ok(quotient)
+    } else {
+        quotient.try_add(1)
+    }
+}
+
+/// `bps` basis points of `amount`, rounded down.
+pub fn bps_of_floor(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+    to_u64(mul_div_floor(amount as u128, bps as u128, BPS as u128)?)
+}
+
+/// `bps` basis points of `amount`, rounded up.
+pub fn bps_of_ceil(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+    to_u64(mul_div_ceil(amount as u128, bps as u128, BPS as u128)?)
+}
+
+/// `numerator / denominator` in basis points, rounded down.
+pub fn ratio_bps(numerator: u64, denominator: u64) -> Result<u64, ProgramError> {
+    to_u64(mul_div_floor(numerator as u128, BPS as u128, denominator as u128)?)
+}
+
+/// Unsigned fixed-point number scaled by `WAD`, used for rates and indices.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Decimal(pub u128);
+
+impl Decimal {
+    pub const ONE: Decimal = Decimal(WAD);
+
+    pub fn from_integer(value: u64) -> Result<Self, ProgramError> {
+        Ok(Decimal((value as u128).try_mul(WAD)?))
+    }
+
+    pub fn from_bps(bps: u64) -> Result<Self, ProgramError> {
+        Ok(Decimal(mul_div_floor(bps as u128, WAD, BPS as u128)?))
+    }
+
+    /// `numerator / denominator`, rounded down.
+    pub fn from_ratio(numerator: u64, denominator: u64) -> Result<Self, ProgramError> {
+        Ok(Decimal(mul_div_floor(numerator as u128, WAD, denominator as u128)?))
+    }
+
+    pub fn try_add(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Decimal(self.0.try_add(rhs.0)?))
+    }
+
+    pub fn try_sub(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Decimal(self.0.try_sub(rhs.0)?))
+    }
+
+    /// Product, rounded down.
+    pub fn try_mul(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Decimal(mul_div_floor(self.0, rhs.0, WAD)?))
+    }
+
+    /// Quotient, rounded down.
+    pub fn try_div(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Decimal(mul_div_floor(self.0, WAD, rhs.0)?))
+    }
+
+    pub fn try_mul_int(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(Decimal(self.0.try_mul(rhs as u128)?))
+    }
+
+    /// Quotient, rounded down.
+    pub fn try_div_int(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(Decimal(self.0.try_div(rhs as u128)?))
+    }
+
+    /// `amount` scaled by this number, rounded down.
+    pub fn mul_floor(self, amount: u64) -> Result<u64, ProgramError> {
+        to_u64(mul_div_floor(amount as u128, self.0, WAD)?)
+    }
+
+    /// `amount` scaled by this number, rounded up.
+    pub fn mul_ceil(self, amount: u64) -> Result<u64, ProgramError> {
+        to_u64(mul_div_ceil(amount as u128, self.0, WAD)?)
+    }
+
+    pub fn to_u64_floor(self) -> Result<u64, ProgramError> {
+        to_u64(self.0 / WAD)
+    }
+
+    pub fn to_u64_ceil(self) -> Result<u64, ProgramError> {
+        to_u64(self.0.div_ceil(WAD))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_checked_operations_fail_with_math_overflow() {
+        assert_eq!(u64::MAX.try_add(1).unwrap_err(), UnityVaultError::MathOverflow.into());
+        assert_eq!(0u64.try_sub(1).unwrap_err(), UnityVaultError::MathOverflow.into());
+        assert_eq!(u128::MAX.try_mul(2).unwrap_err(), UnityVaultError::MathOverflow.into());
+        assert_eq!(1i64.try_div(0).unwrap_err(), UnityVaultError::MathOverflow.into());
+        assert_eq!(mul_div_ceil(1, 1, 0).unwrap_err(), UnityVaultError::MathOverflow.into());
+        assert_eq!(to_u64(u64::MAX as u128 + 1).unwrap_err(), UnityVaultError::MathOverflow.into());
+    }
+
+    #[test]
+    fn test_decimal_and_bps() {
+        assert_eq!(Decimal::from_bps(BPS).unwrap(), Decimal::ONE);
+        assert_eq!(Decimal::from_bps(250).unwrap(), Decimal(WAD / 40));
+        assert_eq!(Decimal::from_ratio(1, 3).unwrap().mul_floor(300).unwrap(), 99);
+        assert_eq!(Decimal::from_ratio(1, 3).unwrap().mul_ceil(300).unwrap(), 100);
+        assert_eq!(Decimal::from_integer(3).unwrap().try_div(Decimal::from_integer(2).unwrap()).unwrap(), Decimal(WAD * 3 / 2));
+        assert_eq!(Decimal(WAD * 3 / 2).to_u64_floor().unwrap(), 1);
+        assert_eq!(Decimal(WAD * 3 / 2).to_u64_ceil().unwrap(), 2);
+        assert_eq!(bps_of_floor(999, 5_000).unwrap(), 499);
+        assert_eq!(bps_of_ceil(999, 5_000).unwrap(), 500);
+        assert_eq!(ratio_bps(1, 3).unwrap(), 3_333);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_mul_div_rounding_brackets_exact_result(a in any::<u64>(), b in any::<u64>(), c in 1..=u64::MAX) {
+            let (a, b, c) = (a as u128, b as u128, c as u128);
+            let floor = mul_div_floor(a, b, c).unwrap();
+            let ceil = mul_div_ceil(a, b, c).unwrap();
+            prop_assert!(floor * c <= a * b);
+            prop_assert!(ceil * c >= a * b);
+            prop_assert!(ceil - floor <= 1);
+            prop_assert_eq!(ceil == floor, (a * b) % c == 0);
+        }
+
+        #[test]
+        fn prop_mul_div_exact_beyond_u128_product(a in any::<u128>(), b in 1..=u128::MAX) {
+            // a * b overflows u128 for most inputs, but the quotient does not
+            prop_assert_eq!(mul_div_floor(a, b, b).unwrap(), a);
+            prop_assert_eq!(mul_div_ceil(a, b, b).unwrap(), a);
+            prop_assert_eq!(mul_div_floor(b, a, b).unwrap(), a);
+        }
+
+        #[test]
+        fn prop_mul_div_overflows_only_when_quotient_does(a in any::<u128>(), k in 0..128u32, j in 0..128u32) {
+            // a * 2^k / 2^j is a shift of a, whatever the width of a * 2^k
+            let expected = if k >= j {
+                let shift = k - j;
+                (a.leading_zeros() >= shift).then(|| a << shift)
+            } else {
+                Some(a >> (j - k))
+            };
+            prop_assert_eq!(mul_div_floor(a, 1 << k, 1 << j).ok(), expected);
+            prop_assert_eq!(mul_div_ceil(a, 1 << k, 1 << j).is_ok(), expected.is_some() || k < j);
+        }
+
+        #[test]
+        fn prop_bps_rounding_brackets_exact_result(amount in any::<u64>(), bps in 0..=BPS) {
+            let floor = bps_of_floor(amount, bps).unwrap();
+            let ceil = bps_of_ceil(amount, bps).unwrap();
+            let exact = amount as u128 * bps as u128;
+            prop_assert!(floor as u128 * BPS as u128 <= exact);
+            prop_assert!(ceil as u128 * BPS as u128 >= exact);
+            prop_assert!(ceil <= amount);
+        }
+
+        #[test]
+        fn prop_decimal_mul_rounds_down(a in 0..u64::MAX as u128, b in 0..u64::MAX as u128) {
+            let product = Decimal(a).try_mul(Decimal(b)).unwrap().0;
+            prop_assert!(product * WAD <= a * b);
+            prop_assert!((product + 1) * WAD > a * b);
+        }
+
+        #[test]
+        fn prop_decimal_div_rounds_down(a in 0..WAD * 100, b in 1..WAD * 100) {
+            let quotient = Decimal(a).try_div(Decimal(b)).unwrap().0;
+            prop_assert!(quotient * b <= a * WAD);
+            prop_assert!((quotient + 1) * b > a * WAD);
+        }
+
+        #[test]
+        fn prop_decimal_scaling_brackets_exact_result(amount in any::<u64>(), scale in 0..WAD * 10) {
+            let exact = amount as u128 * scale;
+            prop_assume!(exact / WAD < u64::MAX as u128);
+            let floor = Decimal(scale).mul_floor(amount).unwrap();
+            let ceil = Decimal(scale).mul_ceil(amount).unwrap();
+            prop_assert!(floor as u128 * WAD <= exact);
+            prop_assert!(ceil as u128 * WAD >= exact);
+            prop_assert!(ceil - floor <= 1);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Helpers shared by the instruction unit tests.
+//!
+//! Handlers are called directly with hand-built `AccountInfo`s, so the
+//! sysvars and CPIs they rely on are served by `TestSyscallStubs`: the clock
+//! is read from a per-thread timestamp, SPL Token instructions are executed by
+//! the real token processor, the system program's account creation is emulated
+//! and every other CPI succeeds without effect. Accounts are laid out in
+//! memory as the runtime serializes them, so `AccountInfo::realloc` works.
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::{Clock, Epoch},
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::SystemInstruction,
+    system_program,
+};
+use crate::user::state::{
+    find_user_profile_address, KycData, KycStatus, UserProfile, UserRole, UserStatus,
+};
+use std::cell::Cell;
+use std::sync::Once;
+
+thread_local! {
+    static UNIX_TIMESTAMP: Cell<i64> = const { Cell::new(0) };
+}
+
+static INSTALL_STUBS: Once = Once::new();
+
+struct TestSyscallStubs;
+
+impl SyscallStubs for TestSyscallStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: UNIX_TIMESTAMP.with(Cell::get),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        // Hand the callee the accounts in instruction order, with the signer
+        // flags the caller asked for (PDA signatures included).
+        let mut ordered = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let mut account = account_infos
+                .iter()
+                .find(|account| *account.key == meta.pubkey)
+                .cloned()
+                .expect("account missing from CPI");
+            account.is_signer = meta.is_signer;
+            ordered.push(account);
+        }
+
+        if instruction.program_id == spl_token::id() {
+            spl_token::processor::Processor::process(&instruction.program_id, &ordered, &instruction.data)
+        } else if instruction.program_id == system_program::id() {
+            process_system_instruction(&ordered, &instruction.data)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        limited_deserialize(data, 1024).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => {
+            if accounts[1].lamports() != 0 {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            move_lamports(&accounts[0], &accounts[1], lamports);
+            allocate(&accounts[1], space);
+            accounts[1].assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => move_lamports(&accounts[0], &accounts[1], lamports),
+        SystemInstruction::Allocate { space } => allocate(&accounts[0], space),
+        SystemInstruction::Assign { owner } => accounts[0].assign(&owner),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) {
+    **from.lamports.borrow_mut() -= lamports;
+    **to.lamports.borrow_mut() += lamports;
+}
+
+fn allocate(account: &AccountInfo, space: u64) {
+    *account.data.borrow_mut() = leak_data(vec![0; space as usize]);
+}
+
+/// An account key preceded by the account's data length at entry, where
+/// `AccountInfo::realloc` reads it.
+#[repr(C)]
+struct SerializedKey {
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+/// Leaks `data` behind its length and ahead of the room a realloc may grow
+/// into, which is how the runtime serializes account data.
+fn leak_data(data: Vec<u8>) -> &'static mut [u8] {
+    // u64 words keep the length aligned
+    let words = (8 + data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+    let buffer: &'static mut [u64] = Box::leak(vec![0u64; words].into_boxed_slice());
+    buffer[0] = data.len() as u64;
+    let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(1) as *mut u8, data.len()) };
+    bytes.copy_from_slice(&data);
+    bytes
+}
+
+/// Installs the syscall stubs and sets the clock for the current thread.
+pub fn setup(unix_timestamp: i64) {
+    INSTALL_STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestSyscallStubs));
+    });
+    set_clock(unix_timestamp);
+}
+
+/// Moves the clock seen by `Clock::get()` on the current thread.
+pub fn set_clock(unix_timestamp: i64) {
+    UNIX_TIMESTAMP.with(|ts| ts.set(unix_timestamp));
+}
+
+/// Builds a writable account whose storage is leaked for the rest of the test run.
+pub fn create_test_account<'a>(
+    key: Pubkey,
+    owner: Pubkey,
+    is_signer: bool,
+    data: Vec<u8>,
+) -> AccountInfo<'a> {
+    let serialized_key = Box::leak(Box::new(SerializedKey {
+        original_data_len: data.len() as u32,
+        key,
+    }));
+    AccountInfo::new(
+        &serialized_key.key,
+        is_signer,
+        true,
+        Box::leak(Box::new(1_000_000_000)),
+        leak_data(data),
+        Box::leak(Box::new(owner)),
+        false,
+        Epoch::default(),
+    )
+}
+
+/// Builds an empty system account, as a not-yet-created PDA looks on chain.
+pub fn create_empty_account<'a>(key: Pubkey) -> AccountInfo<'a> {
+    let account = create_test_account(key, system_program::id(), false, vec![]);
+    **account.lamports.borrow_mut() = 0;
+    account
+}
+
+/// Builds an executable program account for `program_id`.
+pub fn create_program_account<'a>(program_id: Pubkey) -> AccountInfo<'a> {
+    let mut account = create_test_account(program_id, Pubkey::default(), false, vec![]);
+    account.executable = true;
+    account.is_writable = false;
+    account
+}
+
+/// Builds an initialized SPL mint.
+pub fn create_mint_account<'a>(key: Pubkey, authority: Pubkey, supply: u64, decimals: u8) -> AccountInfo<'a> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(
+        spl_token::state::Mint {
+            mint_authority: Some(authority).into(),
+            supply,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        },
+        &mut data,
+    )
+    .unwrap();
+    create_test_account(key, spl_token::id(), false, data)
+}
+
+/// Builds an initialized SPL token account holding `amount` of `mint`.
+pub fn create_token_account<'a>(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> AccountInfo<'a> {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account::pack(
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    create_test_account(key, spl_token::id(), false, data)
+}
+
+/// Reads the balance of an SPL token account.
+pub fn token_balance(account: &AccountInfo) -> u64 {
+    spl_token::state::Account::unpack(&account.data.borrow()).unwrap().amount
+}
+
+/// Builds a plain user profile for `authority` at its profile PDA.
+pub fn create_user_profile_account<'a>(program_id: Pubkey, authority: Pubkey) -> AccountInfo<'a> {
+    let mut data = vec![0; UserProfile::LEN];
+    UserProfile {
+        is_initialized: true,
+        bump: 255,
+        authority,
+        full_name: "Test User".to_string(),
+        email: "test@example.com".to_string(),
+        role: UserRole::User,
+        status: UserStatus::Active,
+        two_factor_enabled: false,
+        two_factor_secret_hash: [0; 32],
+        backup_code_salt: [0; 32],
+        two_factor_backup_codes: Vec::new(),
+        second_factor: None,
+        kyc_verified: false,
+        kyc_status: KycStatus::Pending,
+        kyc_data: KycData {
+            document_type: String::new(),
+            document_number: String::new(),
+            verified_at: 0,
+            expires_at: 0,
+            verifier: Pubkey::default(),
+            rejection_reason: 0,
+        },
+        accredited_status: false,
+        created_at: 0,
+        updated_at: 0,
+    }
+    .pack_into_slice(&mut data);
+    let (key, _) = find_user_profile_address(&program_id, &authority);
+    create_test_account(key, program_id, false, data)
+}
@@ -2,24 +2,27 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::Sysvar,
 };
-use std::str::FromStr;
+use crate::error::UnityVaultError;
+use crate::utils::load_account;
 use crate::tokenization::state::TokenInfo;
 
 pub struct CreateTokenContext<'a> {
     pub token_info: &'a AccountInfo<'a>,
     pub mint: &'a AccountInfo<'a>,
+    pub mint_authority: &'a AccountInfo<'a>,
     pub creator_token_account: &'a AccountInfo<'a>,
     pub creator: &'a AccountInfo<'a>,
     pub token_program: &'a AccountInfo<'a>,
+    pub associated_token_program: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
     pub rent: &'a AccountInfo<'a>,
 }
 
 pub struct TransferTokensContext<'a> {
+    pub token_info: &'a AccountInfo<'a>,
+    pub mint: &'a AccountInfo<'a>,
     pub from: &'a AccountInfo<'a>,
     pub to: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
@@ -36,9 +39,8 @@ pub struct BurnTokensContext<'a> {
 
 impl<'a> CreateTokenContext<'a> {
     pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify token info is not initialized
-        let token_info_data = TokenInfo::unpack_unchecked(&self.token_info.data.borrow())?;
-        if token_info_data.is_initialized {
+        // Verify token info has not been created yet
+        if !self.token_info.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
         
@@ -47,13 +49,32 @@ impl<'a> CreateTokenContext<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
         
+        // Verify mint is signer, since it is created as a fresh account
+        if !self.mint.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Verify creator token account is the creator's associated token account
+        let expected_token_account = spl_associated_token_account::get_associated_token_address(
+            self.creator.key,
+            self.mint.key,
+        );
+        if *self.creator_token_account.key != expected_token_account {
+            return Err(UnityVaultError::TokenAccountMismatch.into());
+        }
+        
         // Verify system program
         if self.system_program.key != &solana_program::system_program::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
+        if self.token_program.key != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        // Verify associated token program
+        if self.associated_token_program.key != &spl_associated_token_account::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
@@ -62,14 +83,22 @@ impl<'a> CreateTokenContext<'a> {
 }
 
 impl<'a> TransferTokensContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify token info is an initialized TokenInfo owned by this program
+        let token_info_data = load_account::<TokenInfo>(self.token_info, program_id)?;
+        
+        // Verify mint matches token info
+        if token_info_data.mint != *self.mint.key {
+            return Err(UnityVaultError::MintMismatch.into());
+        }
+        
         // Verify authority is signer
         if !self.authority.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
+        if self.token_program.key != &spl_token::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
@@ -78,11 +107,13 @@ impl<'a> TransferTokensContext<'a> {
 }
 
 impl<'a> BurnTokensContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify token info is initialized
-        let token_info_data = TokenInfo::unpack_unchecked(&self.token_info.data.borrow())?;
-        if !token_info_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify token info is an initialized TokenInfo owned by this program
+        let token_info_data = load_account::<TokenInfo>(self.token_info, program_id)?;
+        
+        // Verify mint matches token info
+        if token_info_data.mint != *self.mint.key {
+            return Err(UnityVaultError::MintMismatch.into());
         }
         
         // Verify authority is signer
@@ -91,7 +122,7 @@ impl<'a> BurnTokensContext<'a> {
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
+        if self.token_program.key != &spl_token::id() {
             return Err(ProgramError::IncorrectProgramId);
         }
         
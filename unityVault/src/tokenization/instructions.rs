@@ -2,13 +2,22 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
     entrypoint::ProgramResult,
-    program_error::ProgramError,
+    program::{invoke, invoke_signed},
+    program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::Sysvar,
 };
+use crate::error::UnityVaultError;
+use spl_token::state::Mint;
+use crate::utils::{create_pda_account, load_account};
 use crate::tokenization::{
-    state::{TokenInfo, TokenParams, TokenStatus},
+    state::{
+        find_mint_authority_address, find_token_info_address, TokenInfo, TokenParams, TokenStatus,
+        MINT_AUTHORITY_SEED, TOKEN_INFO_SEED,
+    },
     context::{CreateTokenContext, TransferTokensContext, BurnTokensContext},
 };
 
@@ -22,20 +31,115 @@ pub fn create_token<'a>(
     let context = CreateTokenContext {
         token_info: next_account_info(account_info_iter)?,
         mint: next_account_info(account_info_iter)?,
+        mint_authority: next_account_info(account_info_iter)?,
         creator_token_account: next_account_info(account_info_iter)?,
         creator: next_account_info(account_info_iter)?,
         token_program: next_account_info(account_info_iter)?,
+        associated_token_program: next_account_info(account_info_iter)?,
         system_program: next_account_info(account_info_iter)?,
         rent: next_account_info(account_info_iter)?,
     };
     
     context.validate(program_id)?;
     
+    // Verify mint authority is the program-derived address for this mint
+    let (mint_authority, mint_authority_bump) = find_mint_authority_address(program_id, context.mint.key);
+    if mint_authority != *context.mint_authority.key {
+        return Err(UnityVaultError::InvalidMintAuthority.into());
+    }
+    
+    // Create the mint account, owned by the token program
+    let rent = Rent::get()?;
+    invoke(
+        &system_instruction::create_account(
+            context.creator.key,
+            context.mint.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[context.creator.clone(), context.mint.clone(), context.system_program.clone()],
+    )?;
+    
+    // Initialize the mint with the PDA as mint and freeze authority
+    invoke(
+        &spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            context.mint.key,
+            &mint_authority,
+            Some(&mint_authority),
+            params.decimals,
+        )?,
+        std::slice::from_ref(context.mint),
+    )?;
+    
+    // Create the creator's associated token account
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            context.creator.key,
+            context.creator.key,
+            context.mint.key,
+            &spl_token::id(),
+        ),
+        &[
+            context.creator.clone(),
+            context.creator_token_account.clone(),
+            context.mint.clone(),
+            context.system_program.clone(),
+            context.token_program.clone(),
+            context.associated_token_program.clone(),
+        ],
+    )?;
+    
+    // Mint the declared supply to the creator
+    invoke_signed(
+        &spl_token::instruction::mint_to_checked(
+            &spl_token::id(),
+            context.mint.key,
+            context.creator_token_account.key,
+            &mint_authority,
+            &[],
+            params.total_supply,
+            params.decimals,
+        )?,
+        &[
+            context.mint.clone(),
+            context.creator_token_account.clone(),
+            context.mint_authority.clone(),
+        ],
+        &[&[MINT_AUTHORITY_SEED, context.mint.key.as_ref(), &[mint_authority_bump]]],
+    )?;
+    
+    // Verify the mint on chain matches what is recorded in TokenInfo
+    let mint_data = Mint::unpack(&context.mint.data.borrow())?;
+    if mint_data.mint_authority != COption::Some(mint_authority)
+        || mint_data.decimals != params.decimals
+        || mint_data.supply != params.total_supply
+    {
+        return Err(UnityVaultError::MintStateMismatch.into());
+    }
+    
+    // Create the token info account at the mint's PDA
+    let (token_info_key, bump) = find_token_info_address(program_id, context.mint.key);
+    if token_info_key != *context.token_info.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    create_pda_account(
+        context.creator,
+        context.token_info,
+        context.system_program,
+        program_id,
+        TokenInfo::LEN,
+        &[TOKEN_INFO_SEED, context.mint.key.as_ref(), &[bump]],
+    )?;
+    
     let clock = Clock::get()?;
     let token_info_data = TokenInfo {
         is_initialized: true,
+        bump,
         creator: *context.creator.key,
         mint: *context.mint.key,
+        mint_authority_bump,
         name: params.name,
         symbol: params.symbol,
         decimals: params.decimals,
@@ -47,21 +151,19 @@ pub fn create_token<'a>(
     
     TokenInfo::pack(token_info_data, &mut context.token_info.data.borrow_mut())?;
     
-    // Initialize mint and mint tokens using CPI calls
-    // This part would need to be implemented using CPI calls to the SPL Token program
-    // Similar to how it's done in the lending program
-    
     Ok(())
 }
 
 pub fn transfer_tokens<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    _amount: u64,
+    amount: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
     let context = TransferTokensContext {
+        token_info: next_account_info(account_info_iter)?,
+        mint: next_account_info(account_info_iter)?,
         from: next_account_info(account_info_iter)?,
         to: next_account_info(account_info_iter)?,
         authority: next_account_info(account_info_iter)?,
@@ -70,9 +172,27 @@ pub fn transfer_tokens<'a>(
     
     context.validate(program_id)?;
     
-    // Transfer tokens using CPI calls
-    // This part would need to be implemented using CPI calls to the SPL Token program
-    // Similar to how it's done in the lending program
+    let token_info_data = load_account::<TokenInfo>(context.token_info, program_id)?;
+    
+    // Transfer tokens from the authority's account
+    invoke(
+        &spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            context.from.key,
+            context.mint.key,
+            context.to.key,
+            context.authority.key,
+            &[],
+            amount,
+            token_info_data.decimals,
+        )?,
+        &[
+            context.from.clone(),
+            context.mint.clone(),
+            context.to.clone(),
+            context.authority.clone(),
+        ],
+    )?;
     
     Ok(())
 }
@@ -94,14 +214,34 @@ pub fn burn_tokens<'a>(
     
     context.validate(program_id)?;
     
-    // Burn tokens using CPI calls
-    // This part would need to be implemented using CPI calls to the SPL Token program
-    // Similar to how it's done in the lending program
+    let mut token_info_data = load_account::<TokenInfo>(context.token_info, program_id)?;
+    
+    // Paused and frozen tokens cannot be burned
+    if token_info_data.status != TokenStatus::Active {
+        return Err(UnityVaultError::TokenNotActive.into());
+    }
+    
+    // Burn tokens from the authority's account
+    invoke(
+        &spl_token::instruction::burn_checked(
+            &spl_token::id(),
+            context.from.key,
+            context.mint.key,
+            context.authority.key,
+            &[],
+            amount,
+            token_info_data.decimals,
+        )?,
+        &[
+            context.from.clone(),
+            context.mint.clone(),
+            context.authority.clone(),
+        ],
+    )?;
     
     // Update token supply
-    let mut token_info_data = TokenInfo::unpack(&context.token_info.data.borrow())?;
     token_info_data.total_supply = token_info_data.total_supply.checked_sub(amount)
-        .ok_or(ProgramError::InvalidArgument)?;
+        .ok_or(UnityVaultError::BurnExceedsSupply)?;
     token_info_data.updated_at = Clock::get()?.unix_timestamp;
     TokenInfo::pack(token_info_data, &mut context.token_info.data.borrow_mut())?;
     
@@ -111,43 +251,48 @@ pub fn burn_tokens<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_program::clock::Epoch;
-    use std::str::FromStr;
-    
-    fn create_test_account<'a>(lamports: u64, owner: &'a Pubkey, data: &'a mut [u8]) -> AccountInfo<'a> {
-        static KEY: Pubkey = Pubkey::new_from_array([0; 32]);
-        static mut LAMPORTS: u64 = 0;
-        unsafe {
-            LAMPORTS = lamports;
-            AccountInfo::new(
-                &KEY,
-                false,
-                true,
-                &mut LAMPORTS,
-                data,
-                owner,
-                false,
-                Epoch::default(),
-            )
-        }
+    use crate::test_utils::{
+        create_empty_account, create_mint_account, create_program_account, create_test_account,
+        create_token_account, setup, token_balance,
+    };
+    
+    fn create_token_info_account<'a>(program_id: Pubkey, mint: Pubkey, status: TokenStatus) -> AccountInfo<'a> {
+        let mut data = vec![0; TokenInfo::LEN];
+        TokenInfo::pack(
+            TokenInfo {
+                is_initialized: true,
+                bump: 255,
+                creator: Pubkey::new_unique(),
+                mint,
+                mint_authority_bump: 255,
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                decimals: 9,
+                total_supply: 1000,
+                status,
+                created_at: 0,
+                updated_at: 0,
+            },
+            &mut data,
+        )
+        .unwrap();
+        create_test_account(Pubkey::new_unique(), program_id, false, data)
     }
     
     #[test]
     fn test_create_token() {
+        setup(1_700_000_000);
         let program_id = Pubkey::new_unique();
         let creator = Pubkey::new_unique();
         let mint = Pubkey::new_unique();
-        let creator_token_account = Pubkey::new_unique();
-        let system_program_id = solana_program::system_program::id();
-        let spl_token_id = Pubkey::from_str(&spl_token::ID.to_string()).unwrap();
-        let rent_id = solana_program::sysvar::rent::id();
-        
-        let mut token_info_data = vec![0; TokenInfo::LEN];
-        let token_info_account = create_test_account(
-            1000000,
-            &program_id,
-            &mut token_info_data,
-        );
+        let (mint_authority, mint_authority_bump) = find_mint_authority_address(&program_id, &mint);
+        let creator_token_account = spl_associated_token_account::get_associated_token_address(&creator, &mint);
+        
+        let (token_info, bump) = find_token_info_address(&program_id, &mint);
+        let token_info_account = create_empty_account(token_info);
+        let mut mint_account = create_empty_account(mint);
+        mint_account.is_signer = true;
+        let creator_token_account = create_token_account(creator_token_account, mint, creator, 0);
         
         let params = TokenParams {
             name: "Test Token".to_string(),
@@ -156,33 +301,152 @@ mod tests {
             total_supply: 1000000000,
         };
         
-        let mut mint_data = vec![];
-        let mut creator_token_account_data = vec![];
-        let mut creator_data = vec![];
-        let mut system_program_data = vec![];
-        let mut spl_token_data = vec![];
-        let mut rent_data = vec![];
-        
         let accounts = vec![
             token_info_account.clone(),
-            create_test_account(1000000, &mint, &mut mint_data),
-            create_test_account(1000000, &creator_token_account, &mut creator_token_account_data),
-            create_test_account(1000000, &creator, &mut creator_data),
-            create_test_account(1000000, &system_program_id, &mut system_program_data),
-            create_test_account(1000000, &spl_token_id, &mut spl_token_data),
-            create_test_account(1000000, &rent_id, &mut rent_data),
+            mint_account.clone(),
+            create_test_account(mint_authority, solana_program::system_program::id(), false, vec![]),
+            creator_token_account.clone(),
+            create_test_account(creator, solana_program::system_program::id(), true, vec![]),
+            create_program_account(spl_token::id()),
+            create_program_account(spl_associated_token_account::id()),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(solana_program::sysvar::rent::id()),
         ];
         
         assert!(create_token(&program_id, &accounts, params).is_ok());
         
+        assert_eq!(*token_info_account.owner, program_id);
         let token_info = TokenInfo::unpack(&token_info_account.data.borrow()).unwrap();
         assert!(token_info.is_initialized);
+        assert_eq!(token_info.bump, bump);
         assert_eq!(token_info.creator, creator);
         assert_eq!(token_info.mint, mint);
+        assert_eq!(token_info.mint_authority_bump, mint_authority_bump);
         assert_eq!(token_info.name, "Test Token");
         assert_eq!(token_info.symbol, "TEST");
         assert_eq!(token_info.decimals, 9);
         assert_eq!(token_info.total_supply, 1000000000);
         assert_eq!(token_info.status, TokenStatus::Active);
+        
+        let mint_data = Mint::unpack(&mint_account.data.borrow()).unwrap();
+        assert_eq!(mint_data.mint_authority, COption::Some(mint_authority));
+        assert_eq!(mint_data.supply, 1000000000);
+        assert_eq!(token_balance(&creator_token_account), 1000000000);
+    }
+    
+    #[test]
+    fn test_create_token_rejects_foreign_mint_authority() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let creator_token_account = spl_associated_token_account::get_associated_token_address(&creator, &mint);
+        let mut mint_account = create_empty_account(mint);
+        mint_account.is_signer = true;
+        
+        let accounts = vec![
+            create_empty_account(find_token_info_address(&program_id, &mint).0),
+            mint_account,
+            create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), false, vec![]),
+            create_token_account(creator_token_account, mint, creator, 0),
+            create_test_account(creator, solana_program::system_program::id(), true, vec![]),
+            create_program_account(spl_token::id()),
+            create_program_account(spl_associated_token_account::id()),
+            create_program_account(solana_program::system_program::id()),
+            create_program_account(solana_program::sysvar::rent::id()),
+        ];
+        
+        let params = TokenParams {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            decimals: 9,
+            total_supply: 1000000000,
+        };
+        
+        assert_eq!(
+            create_token(&program_id, &accounts, params),
+            Err(UnityVaultError::InvalidMintAuthority.into())
+        );
+    }
+    
+    #[test]
+    fn test_transfer_tokens() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        
+        let from_account = create_token_account(Pubkey::new_unique(), mint, owner, 1000);
+        let to_account = create_token_account(Pubkey::new_unique(), mint, Pubkey::new_unique(), 0);
+        
+        let accounts = vec![
+            create_token_info_account(program_id, mint, TokenStatus::Active),
+            create_mint_account(mint, Pubkey::new_unique(), 1000, 9),
+            from_account.clone(),
+            to_account.clone(),
+            create_test_account(owner, solana_program::system_program::id(), true, vec![]),
+            create_program_account(spl_token::id()),
+        ];
+        
+        assert!(transfer_tokens(&program_id, &accounts, 400).is_ok());
+        assert_eq!(token_balance(&from_account), 600);
+        assert_eq!(token_balance(&to_account), 400);
+    }
+    
+    #[test]
+    fn test_burn_tokens() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        
+        let token_info_account = create_token_info_account(program_id, mint, TokenStatus::Active);
+        let mint_account = create_mint_account(mint, Pubkey::new_unique(), 1000, 9);
+        let from_account = create_token_account(Pubkey::new_unique(), mint, owner, 1000);
+        
+        let accounts = vec![
+            token_info_account.clone(),
+            mint_account.clone(),
+            from_account.clone(),
+            create_test_account(owner, solana_program::system_program::id(), true, vec![]),
+            create_program_account(spl_token::id()),
+        ];
+        
+        assert!(burn_tokens(&program_id, &accounts, 250).is_ok());
+        assert_eq!(token_balance(&from_account), 750);
+        assert_eq!(Mint::unpack(&mint_account.data.borrow()).unwrap().supply, 750);
+        
+        let token_info = TokenInfo::unpack(&token_info_account.data.borrow()).unwrap();
+        assert_eq!(token_info.total_supply, 750);
+        
+        // A failed burn leaves the recorded supply untouched
+        assert!(burn_tokens(&program_id, &accounts, 5000).is_err());
+        let token_info = TokenInfo::unpack(&token_info_account.data.borrow()).unwrap();
+        assert_eq!(token_info.total_supply, 750);
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_burn_tokens_rejects_paused_and_frozen() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        
+        for status in [TokenStatus::Paused, TokenStatus::Frozen] {
+            let from_account = create_token_account(Pubkey::new_unique(), mint, owner, 1000);
+            let accounts = vec![
+                create_token_info_account(program_id, mint, status),
+                create_mint_account(mint, Pubkey::new_unique(), 1000, 9),
+                from_account.clone(),
+                create_test_account(owner, solana_program::system_program::id(), true, vec![]),
+                create_program_account(spl_token::id()),
+            ];
+            
+            assert_eq!(
+                burn_tokens(&program_id, &accounts, 100),
+                Err(UnityVaultError::TokenNotActive.into())
+            );
+            assert_eq!(token_balance(&from_account), 1000);
+        }
+    }
+}
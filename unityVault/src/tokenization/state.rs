@@ -4,6 +4,21 @@ use solana_program::{
     pubkey::Pubkey,
 };
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::utils::{AccountType, ProgramAccount};
+
+/// Seed for a token's metadata PDA: `["token_info", mint]`.
+pub const TOKEN_INFO_SEED: &[u8] = b"token_info";
+
+/// Seed for the PDA that holds mint and freeze authority over created tokens.
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+
+pub fn find_token_info_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_INFO_SEED, mint.as_ref()], program_id)
+}
+
+pub fn find_mint_authority_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_AUTHORITY_SEED, mint.as_ref()], program_id)
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenStatus {
@@ -23,8 +38,10 @@ pub struct TokenParams {
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
 pub struct TokenInfo {
     pub is_initialized: bool,
+    pub bump: u8,
     pub creator: Pubkey,
     pub mint: Pubkey,
+    pub mint_authority_bump: u8,
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
@@ -35,9 +52,12 @@ pub struct TokenInfo {
 }
 
 impl TokenInfo {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // creator
         32 + // mint
+        1 + // mint_authority_bump
         4 + 100 + // name (max 100 chars)
         4 + 10 + // symbol (max 10 chars)
         1 + // decimals
@@ -49,6 +69,10 @@ impl TokenInfo {
 
 impl Sealed for TokenInfo {}
 
+impl ProgramAccount for TokenInfo {
+    const ACCOUNT_TYPE: AccountType = AccountType::TokenInfo;
+}
+
 impl IsInitialized for TokenInfo {
     fn is_initialized(&self) -> bool {
         self.is_initialized
@@ -61,15 +85,26 @@ impl Pack for TokenInfo {
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        
+        dst[offset] = self.bump;
+        
+        offset += 1;
+        
         dst[offset..offset + 32].copy_from_slice(&self.creator.to_bytes());
         offset += 32;
         
         dst[offset..offset + 32].copy_from_slice(&self.mint.to_bytes());
         offset += 32;
         
+        dst[offset] = self.mint_authority_bump;
+        offset += 1;
+        
         // Pack name
         let name_bytes = self.name.as_bytes();
         dst[offset..offset + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
@@ -100,11 +135,17 @@ impl Pack for TokenInfo {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        
+        let bump = src[offset];
+        
+        offset += 1;
+        
         let creator_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| ProgramError::InvalidAccountData)?;
         let creator = Pubkey::from(creator_bytes);
@@ -115,6 +156,9 @@ impl Pack for TokenInfo {
         let mint = Pubkey::from(mint_bytes);
         offset += 32;
         
+        let mint_authority_bump = src[offset];
+        offset += 1;
+        
         // Unpack name
         let name_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
         offset += 4;
@@ -150,8 +194,10 @@ impl Pack for TokenInfo {
         
         Ok(TokenInfo {
             is_initialized,
+            bump,
             creator,
             mint,
+            mint_authority_bump,
             name,
             symbol,
             decimals,
@@ -1,5 +1,6 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::Clock,
     entrypoint::ProgramResult,
     program_error::ProgramError,
@@ -7,7 +8,14 @@ use solana_program::{
     pubkey::Pubkey,
     sysvar::Sysvar,
 };
-use crate::user::state::{UserProfile, UserRole, UserStatus, KycStatus, KycData};
+use crate::error::UnityVaultError;
+use crate::user::state::{
+    find_kyc_registry_address, find_user_profile_address, UserProfile, UserRole, UserStatus, KycStatus,
+    KycData, KycParams, KycRegistry, BackupCode, KYC_REGISTRY_SEED, MAX_BACKUP_CODES, MAX_KYC_VERIFIERS,
+    USER_PROFILE_SEED,
+};
+use crate::math::TryMath;
+use crate::utils::{create_pda_account, load_account};
 use borsh::{BorshSerialize, BorshDeserialize};
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -17,8 +25,29 @@ pub struct UserProfileParams {
     pub role: UserRole,
 }
 
+impl UserProfileParams {
+    /// Checks that the string fields fit the profile layout.
+    fn validate(&self) -> ProgramResult {
+        if self.full_name.len() > 100 || self.email.len() > 100 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct TwoFactorParams {
+    /// Hash commitment of the two-factor secret.
+    pub secret_hash: [u8; 32],
+    pub backup_code_salt: [u8; 32],
+    /// `hash_backup_code(backup_code_salt, code)` for each backup code.
+    pub backup_code_hashes: Vec<[u8; 32]>,
+    /// Optional key that must co-sign sensitive operations.
+    pub second_factor: Option<Pubkey>,
+}
+
 pub fn create_user_profile(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     params: UserProfileParams,
 ) -> ProgramResult {
@@ -38,23 +67,47 @@ pub fn create_user_profile(
         return Err(ProgramError::IncorrectProgramId);
     }
     
+    params.validate()?;
+    
+    // Verify user profile is the authority's PDA
+    let (user_profile_key, bump) = find_user_profile_address(program_id, authority.key);
+    if user_profile_key != *user_profile.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Create the user profile account
+    create_pda_account(
+        authority,
+        user_profile,
+        system_program,
+        program_id,
+        UserProfile::LEN,
+        &[USER_PROFILE_SEED, authority.key.as_ref(), &[bump]],
+    )?;
+    
     // Create and initialize user profile
-    let mut user_profile_data = UserProfile {
+    let user_profile_data = UserProfile {
         is_initialized: true,
+        bump,
         authority: *authority.key,
         full_name: params.full_name,
         email: params.email,
         role: params.role,
         status: UserStatus::Active,
         two_factor_enabled: false,
-        two_factor_secret: String::new(),
+        two_factor_secret_hash: [0; 32],
+        backup_code_salt: [0; 32],
         two_factor_backup_codes: Vec::new(),
+        second_factor: None,
         kyc_verified: false,
         kyc_status: KycStatus::Pending,
         kyc_data: KycData {
             document_type: String::new(),
             document_number: String::new(),
             verified_at: 0,
+            expires_at: 0,
+            verifier: Pubkey::default(),
+            rejection_reason: 0,
         },
         accredited_status: false,
         created_at: Clock::get()?.unix_timestamp,
@@ -68,7 +121,7 @@ pub fn create_user_profile(
 }
 
 pub fn update_user_profile(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     params: UserProfileParams,
 ) -> ProgramResult {
@@ -83,11 +136,16 @@ pub fn update_user_profile(
     }
     
     // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
+    let mut user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
     if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+        return Err(UnityVaultError::UserAuthorityMismatch.into());
     }
     
+    // Verify second factor co-signed, if the profile has one
+    user_profile_data.check_second_factor(account_info_iter.next())?;
+    
+    params.validate()?;
+    
     // Update profile
     user_profile_data.full_name = params.full_name;
     user_profile_data.email = params.email;
@@ -101,10 +159,9 @@ pub fn update_user_profile(
 }
 
 pub fn enable_two_factor(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    secret: String,
-    backup_codes: Vec<String>,
+    params: TwoFactorParams,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
@@ -117,15 +174,32 @@ pub fn enable_two_factor(
     }
     
     // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
+    let mut user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
     if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+        return Err(UnityVaultError::UserAuthorityMismatch.into());
+    }
+    
+    if user_profile_data.two_factor_enabled {
+        return Err(UnityVaultError::TwoFactorAlreadyEnabled.into());
+    }
+    if params.backup_code_hashes.len() > MAX_BACKUP_CODES {
+        return Err(UnityVaultError::TooManyBackupCodes.into());
     }
     
     // Enable 2FA
     user_profile_data.two_factor_enabled = true;
-    user_profile_data.two_factor_secret = secret;
-    user_profile_data.two_factor_backup_codes = backup_codes;
+    user_profile_data.two_factor_secret_hash = params.secret_hash;
+    user_profile_data.backup_code_salt = params.backup_code_salt;
+    user_profile_data.two_factor_backup_codes = params
+        .backup_code_hashes
+        .into_iter()
+        .map(|hash| BackupCode { hash, used: false })
+        .collect();
+    user_profile_data.second_factor = params.second_factor;
+    
+    // Verify the second-factor key co-signed, proving it is held
+    user_profile_data.check_second_factor(account_info_iter.next())?;
+    
     user_profile_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
@@ -134,10 +208,10 @@ pub fn enable_two_factor(
     Ok(())
 }
 
-pub fn verify_kyc(
-    _program_id: &Pubkey,
+pub fn consume_backup_code(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    kyc_data: KycData,
+    code: String,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
@@ -150,19 +224,627 @@ pub fn verify_kyc(
     }
     
     // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
+    let mut user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
     if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+        return Err(UnityVaultError::UserAuthorityMismatch.into());
+    }
+    
+    if !user_profile_data.two_factor_enabled {
+        return Err(UnityVaultError::TwoFactorNotEnabled.into());
     }
     
+    user_profile_data.consume_backup_code(code.as_bytes())?;
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Turns two-factor off. Requires either an unused backup code or the
+/// second-factor key's signature.
+pub fn disable_two_factor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    backup_code: Option<String>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let user_profile = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify authority matches
+    let mut user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
+    if user_profile_data.authority != *authority.key {
+        return Err(UnityVaultError::UserAuthorityMismatch.into());
+    }
+    
+    if !user_profile_data.two_factor_enabled {
+        return Err(UnityVaultError::TwoFactorNotEnabled.into());
+    }
+    
+    // Verify the second factor, by backup code or co-signature
+    match backup_code {
+        Some(code) => user_profile_data.consume_backup_code(code.as_bytes())?,
+        None if user_profile_data.second_factor.is_some() => {
+            user_profile_data.check_second_factor(account_info_iter.next())?
+        }
+        None => return Err(UnityVaultError::SecondFactorRequired.into()),
+    }
+    
+    // Disable 2FA
+    user_profile_data.two_factor_enabled = false;
+    user_profile_data.two_factor_secret_hash = [0; 32];
+    user_profile_data.backup_code_salt = [0; 32];
+    user_profile_data.two_factor_backup_codes = Vec::new();
+    user_profile_data.second_factor = None;
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    
+    Ok(())
+}
+
+pub fn init_kyc_registry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    verification_validity: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let kyc_registry = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Only the program's upgrade authority may create the singleton registry
+    verify_upgrade_authority(program_id, program_data, admin.key)?;
+    
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    if verification_validity < 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    
+    // Verify registry is the program's registry PDA
+    let (kyc_registry_key, bump) = find_kyc_registry_address(program_id);
+    if kyc_registry_key != *kyc_registry.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    if !kyc_registry.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    // Create the registry account
+    create_pda_account(
+        admin,
+        kyc_registry,
+        system_program,
+        program_id,
+        KycRegistry::LEN,
+        &[KYC_REGISTRY_SEED, &[bump]],
+    )?;
+    
+    let clock = Clock::get()?;
+    let kyc_registry_data = KycRegistry {
+        is_initialized: true,
+        bump,
+        admin: *admin.key,
+        verifiers: Vec::new(),
+        verification_validity,
+        created_at: clock.unix_timestamp,
+        updated_at: clock.unix_timestamp,
+    };
+    
+    // Pack the data into the account
+    kyc_registry_data.pack_into_slice(&mut kyc_registry.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Checks that `authority` is the upgrade authority recorded in the program's
+/// `ProgramData` account.
+fn verify_upgrade_authority(program_id: &Pubkey, program_data: &AccountInfo, authority: &Pubkey) -> ProgramResult {
+    let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data.key != program_data_key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    if program_data.owner != &bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let data = program_data.data.borrow();
+    let metadata = data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    match bincode::deserialize(metadata).map_err(|_| ProgramError::InvalidAccountData)? {
+        UpgradeableLoaderState::ProgramData { upgrade_authority_address: Some(upgrade_authority), .. }
+            if upgrade_authority == *authority => Ok(()),
+        UpgradeableLoaderState::ProgramData { .. } => Err(UnityVaultError::NotUpgradeAuthority.into()),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+pub fn add_kyc_verifier(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    verifier: Pubkey,
+) -> ProgramResult {
+    let (kyc_registry, mut kyc_registry_data) = load_registry_as_admin(program_id, accounts)?;
+    
+    if kyc_registry_data.is_verifier(&verifier) {
+        return Err(UnityVaultError::KycVerifierAlreadyRegistered.into());
+    }
+    if kyc_registry_data.verifiers.len() >= MAX_KYC_VERIFIERS {
+        return Err(UnityVaultError::KycRegistryFull.into());
+    }
+    
+    kyc_registry_data.verifiers.push(verifier);
+    kyc_registry_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    kyc_registry_data.pack_into_slice(&mut kyc_registry.data.borrow_mut());
+    
+    Ok(())
+}
+
+pub fn remove_kyc_verifier(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    verifier: Pubkey,
+) -> ProgramResult {
+    let (kyc_registry, mut kyc_registry_data) = load_registry_as_admin(program_id, accounts)?;
+    
+    if !kyc_registry_data.is_verifier(&verifier) {
+        return Err(UnityVaultError::KycVerifierNotRegistered.into());
+    }
+    
+    kyc_registry_data.verifiers.retain(|key| *key != verifier);
+    kyc_registry_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    kyc_registry_data.pack_into_slice(&mut kyc_registry.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Loads the KYC registry from `[kyc_registry, admin]`, requiring the admin's signature.
+fn load_registry_as_admin<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(&'a AccountInfo<'b>, KycRegistry), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    
+    let kyc_registry = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify admin matches
+    let kyc_registry_data = load_account::<KycRegistry>(kyc_registry, program_id)?;
+    if kyc_registry_data.admin != *admin.key {
+        return Err(UnityVaultError::KycRegistryAdminMismatch.into());
+    }
+    
+    Ok((kyc_registry, kyc_registry_data))
+}
+
+/// Loads the user profile and registry from `[user_profile, kyc_registry, verifier]`,
+/// requiring the signature of a registered verifier.
+fn load_profile_as_verifier<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(&'a AccountInfo<'b>, UserProfile, KycRegistry, Pubkey), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    
+    let user_profile = next_account_info(account_info_iter)?;
+    let kyc_registry = next_account_info(account_info_iter)?;
+    let verifier = next_account_info(account_info_iter)?;
+    
+    // Verify verifier is signer
+    if !verifier.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify registry is the program's registry PDA
+    if find_kyc_registry_address(program_id).0 != *kyc_registry.key {
+        return Err(UnityVaultError::InvalidAccountAddress.into());
+    }
+    
+    // Verify verifier is registered
+    let kyc_registry_data = load_account::<KycRegistry>(kyc_registry, program_id)?;
+    if !kyc_registry_data.is_verifier(verifier.key) {
+        return Err(UnityVaultError::KycVerifierNotRegistered.into());
+    }
+    
+    let user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
+    
+    Ok((user_profile, user_profile_data, kyc_registry_data, *verifier.key))
+}
+
+pub fn verify_kyc(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: KycParams,
+) -> ProgramResult {
+    let (user_profile, mut user_profile_data, kyc_registry_data, verifier) =
+        load_profile_as_verifier(program_id, accounts)?;
+    
+    // Document fields must fit the profile layout
+    if params.document_type.len() > 100 || params.document_number.len() > 100 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    let expires_at = if kyc_registry_data.verification_validity == 0 {
+        0
+    } else {
+        now.try_add(kyc_registry_data.verification_validity)?
+    };
+    
     // Update KYC status
     user_profile_data.kyc_verified = true;
     user_profile_data.kyc_status = KycStatus::Verified;
-    user_profile_data.kyc_data = kyc_data;
-    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    user_profile_data.kyc_data = KycData {
+        document_type: params.document_type,
+        document_number: params.document_number,
+        verified_at: now,
+        expires_at,
+        verifier,
+        rejection_reason: 0,
+    };
+    user_profile_data.updated_at = now;
+    
+    // Pack the updated data
+    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    
+    Ok(())
+}
+
+pub fn reject_kyc(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reason: u16,
+) -> ProgramResult {
+    let (user_profile, mut user_profile_data, _, verifier) =
+        load_profile_as_verifier(program_id, accounts)?;
+    
+    let now = Clock::get()?.unix_timestamp;
+    
+    // Update KYC status
+    user_profile_data.kyc_verified = false;
+    user_profile_data.kyc_status = KycStatus::Rejected;
+    user_profile_data.kyc_data.verified_at = now;
+    user_profile_data.kyc_data.expires_at = 0;
+    user_profile_data.kyc_data.verifier = verifier;
+    user_profile_data.kyc_data.rejection_reason = reason;
+    user_profile_data.updated_at = now;
+    
+    // Pack the updated data
+    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    
+    Ok(())
+}
+
+/// Reverts an expired verification to `Pending`. Anyone may crank this.
+pub fn expire_kyc(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let user_profile = next_account_info(account_info_iter)?;
+    
+    let mut user_profile_data = load_account::<UserProfile>(user_profile, program_id)?;
+    let now = Clock::get()?.unix_timestamp;
+    if user_profile_data.kyc_status != KycStatus::Verified
+        || user_profile_data.effective_kyc_status(now) != KycStatus::Pending
+    {
+        return Err(UnityVaultError::KycNotExpired.into());
+    }
+    
+    // Update KYC status
+    user_profile_data.kyc_verified = false;
+    user_profile_data.kyc_status = KycStatus::Pending;
+    user_profile_data.updated_at = now;
     
     // Pack the updated data
     user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        create_empty_account, create_program_account, create_test_account, create_user_profile_account, set_clock,
+        setup,
+    };
+    
+    fn kyc_params() -> KycParams {
+        KycParams {
+            document_type: "Passport".to_string(),
+            document_number: "123456789".to_string(),
+        }
+    }
+    
+    /// Builds the program's `ProgramData` account with `upgrade_authority` set.
+    fn create_program_data_account<'a>(program_id: &Pubkey, upgrade_authority: Pubkey) -> AccountInfo<'a> {
+        let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(upgrade_authority),
+        })
+        .unwrap();
+        create_test_account(program_data, bpf_loader_upgradeable::id(), false, data)
+    }
+    
+    /// Creates a registry with one verifier and returns `(registry, admin, verifier)`.
+    fn create_registry<'a>(program_id: &Pubkey, validity: i64) -> (AccountInfo<'a>, AccountInfo<'a>, AccountInfo<'a>) {
+        let registry = create_empty_account(find_kyc_registry_address(program_id).0);
+        let admin = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let program_data = create_program_data_account(program_id, *admin.key);
+        let system_program = create_program_account(solana_program::system_program::id());
+        init_kyc_registry(program_id, &[registry.clone(), admin.clone(), program_data, system_program], validity).unwrap();
+        
+        let verifier = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        add_kyc_verifier(program_id, &[registry.clone(), admin.clone()], *verifier.key).unwrap();
+        (registry, admin, verifier)
+    }
+    
+    #[test]
+    fn test_profile_fields_must_fit_layout() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        let long_params = || UserProfileParams {
+            full_name: "a".repeat(101),
+            email: "user@example.com".to_string(),
+            role: UserRole::User,
+        };
+        
+        let new_profile = create_empty_account(find_user_profile_address(&program_id, authority.key).0);
+        let system_program = create_program_account(solana_program::system_program::id());
+        assert_eq!(
+            create_user_profile(&program_id, &[new_profile.clone(), authority.clone(), system_program], long_params()),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert!(new_profile.data_is_empty());
+        
+        let profile = create_user_profile_account(program_id, *authority.key);
+        assert_eq!(
+            update_user_profile(&program_id, &[profile.clone(), authority.clone()], long_params()),
+            Err(ProgramError::InvalidArgument)
+        );
+        
+        let mut params = long_params();
+        params.full_name = "a".repeat(100);
+        update_user_profile(&program_id, &[profile.clone(), authority], params).unwrap();
+        assert_eq!(load_account::<UserProfile>(&profile, &program_id).unwrap().full_name.len(), 100);
+    }
+    
+    #[test]
+    fn test_init_kyc_registry_requires_upgrade_authority() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let registry = create_empty_account(find_kyc_registry_address(&program_id).0);
+        let program_data = create_program_data_account(&program_id, Pubkey::new_unique());
+        let system_program = create_program_account(solana_program::system_program::id());
+        
+        // A stranger cannot claim the registry ahead of the deployer
+        let stranger = create_test_account(Pubkey::new_unique(), solana_program::system_program::id(), true, vec![]);
+        assert_eq!(
+            init_kyc_registry(&program_id, &[registry.clone(), stranger, program_data, system_program], 0).unwrap_err(),
+            UnityVaultError::NotUpgradeAuthority.into()
+        );
+        assert!(registry.data_is_empty());
+    }
+    
+    #[test]
+    fn test_verify_kyc_requires_registered_verifier() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (registry, _admin, verifier) = create_registry(&program_id, 0);
+        let profile = create_user_profile_account(program_id, Pubkey::new_unique());
+        
+        // An unregistered signer cannot verify
+        let outsider = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        assert_eq!(
+            verify_kyc(&program_id, &[profile.clone(), registry.clone(), outsider], kyc_params()).unwrap_err(),
+            UnityVaultError::KycVerifierNotRegistered.into()
+        );
+        
+        verify_kyc(&program_id, &[profile.clone(), registry, verifier.clone()], kyc_params()).unwrap();
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert_eq!(profile_data.kyc_status, KycStatus::Verified);
+        assert!(profile_data.kyc_verified);
+        assert_eq!(profile_data.kyc_data.verified_at, 1_700_000_000);
+        assert_eq!(profile_data.kyc_data.expires_at, 0);
+        assert_eq!(profile_data.kyc_data.verifier, *verifier.key);
+    }
+    
+    #[test]
+    fn test_registry_admin_manages_verifiers() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (registry, admin, verifier) = create_registry(&program_id, 0);
+        
+        let not_admin = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        assert_eq!(
+            remove_kyc_verifier(&program_id, &[registry.clone(), not_admin], *verifier.key).unwrap_err(),
+            UnityVaultError::KycRegistryAdminMismatch.into()
+        );
+        assert_eq!(
+            add_kyc_verifier(&program_id, &[registry.clone(), admin.clone()], *verifier.key).unwrap_err(),
+            UnityVaultError::KycVerifierAlreadyRegistered.into()
+        );
+        
+        remove_kyc_verifier(&program_id, &[registry.clone(), admin], *verifier.key).unwrap();
+        let profile = create_user_profile_account(program_id, Pubkey::new_unique());
+        assert_eq!(
+            verify_kyc(&program_id, &[profile, registry, verifier], kyc_params()).unwrap_err(),
+            UnityVaultError::KycVerifierNotRegistered.into()
+        );
+    }
+    
+    #[test]
+    fn test_reject_kyc_records_reason() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (registry, _admin, verifier) = create_registry(&program_id, 0);
+        let profile = create_user_profile_account(program_id, Pubkey::new_unique());
+        
+        reject_kyc(&program_id, &[profile.clone(), registry, verifier], 42).unwrap();
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert_eq!(profile_data.kyc_status, KycStatus::Rejected);
+        assert!(!profile_data.kyc_verified);
+        assert_eq!(profile_data.kyc_data.rejection_reason, 42);
+    }
+    
+    #[test]
+    fn test_expire_kyc_reverts_to_pending() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (registry, _admin, verifier) = create_registry(&program_id, 3600);
+        let profile = create_user_profile_account(program_id, Pubkey::new_unique());
+        
+        verify_kyc(&program_id, &[profile.clone(), registry, verifier], kyc_params()).unwrap();
+        assert_eq!(
+            expire_kyc(&program_id, std::slice::from_ref(&profile)).unwrap_err(),
+            UnityVaultError::KycNotExpired.into()
+        );
+        
+        set_clock(1_700_003_600);
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert_eq!(profile_data.effective_kyc_status(1_700_003_600), KycStatus::Pending);
+        
+        expire_kyc(&program_id, std::slice::from_ref(&profile)).unwrap();
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert_eq!(profile_data.kyc_status, KycStatus::Pending);
+        assert!(!profile_data.kyc_verified);
+    }
+    
+    fn two_factor_params(salt: [u8; 32], codes: &[&str], second_factor: Option<Pubkey>) -> TwoFactorParams {
+        TwoFactorParams {
+            secret_hash: solana_program::hash::hash(b"totp secret").to_bytes(),
+            backup_code_salt: salt,
+            backup_code_hashes: codes
+                .iter()
+                .map(|code| crate::user::state::hash_backup_code(&salt, code.as_bytes()))
+                .collect(),
+            second_factor,
+        }
+    }
+    
+    #[test]
+    fn test_enable_two_factor_stores_only_hashes() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        let profile = create_user_profile_account(program_id, *authority.key);
+        
+        let params = two_factor_params([7; 32], &["alpha-1234", "bravo-5678"], None);
+        enable_two_factor(&program_id, &[profile.clone(), authority.clone()], params).unwrap();
+        
+        let data = profile.data.borrow();
+        assert!(!data.windows(10).any(|window| window == b"alpha-1234"));
+        drop(data);
+        
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert!(profile_data.two_factor_enabled);
+        assert_eq!(profile_data.two_factor_backup_codes.len(), 2);
+        
+        let params = two_factor_params([7; 32], &[], None);
+        assert_eq!(
+            enable_two_factor(&program_id, &[profile, authority], params).unwrap_err(),
+            UnityVaultError::TwoFactorAlreadyEnabled.into()
+        );
+    }
+    
+    #[test]
+    fn test_consume_backup_code_once() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        let profile = create_user_profile_account(program_id, *authority.key);
+        let accounts = [profile.clone(), authority];
+        
+        let params = two_factor_params([7; 32], &["alpha-1234", "bravo-5678"], None);
+        enable_two_factor(&program_id, &accounts, params).unwrap();
+        
+        assert_eq!(
+            consume_backup_code(&program_id, &accounts, "wrong".to_string()).unwrap_err(),
+            UnityVaultError::InvalidBackupCode.into()
+        );
+        consume_backup_code(&program_id, &accounts, "bravo-5678".to_string()).unwrap();
+        assert_eq!(
+            consume_backup_code(&program_id, &accounts, "bravo-5678".to_string()).unwrap_err(),
+            UnityVaultError::InvalidBackupCode.into()
+        );
+        
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert!(!profile_data.two_factor_backup_codes[0].used);
+        assert!(profile_data.two_factor_backup_codes[1].used);
+        
+        // A backup code also disables 2FA
+        disable_two_factor(&program_id, &accounts, Some("alpha-1234".to_string())).unwrap();
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert!(!profile_data.two_factor_enabled);
+        assert!(profile_data.two_factor_backup_codes.is_empty());
+    }
+    
+    #[test]
+    fn test_second_factor_co_signs_sensitive_operations() {
+        setup(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let authority = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        let mut second_factor = create_test_account(Pubkey::new_unique(), Pubkey::default(), true, vec![]);
+        let profile = create_user_profile_account(program_id, *authority.key);
+        
+        let params = two_factor_params([7; 32], &[], Some(*second_factor.key));
+        enable_two_factor(&program_id, &[profile.clone(), authority.clone(), second_factor.clone()], params).unwrap();
+        
+        let update = || UserProfileParams {
+            full_name: "Updated User".to_string(),
+            email: "updated@example.com".to_string(),
+            role: UserRole::User,
+        };
+        assert_eq!(
+            update_user_profile(&program_id, &[profile.clone(), authority.clone()], update()).unwrap_err(),
+            UnityVaultError::SecondFactorRequired.into()
+        );
+        update_user_profile(&program_id, &[profile.clone(), authority.clone(), second_factor.clone()], update()).unwrap();
+        
+        second_factor.is_signer = false;
+        assert_eq!(
+            disable_two_factor(&program_id, &[profile.clone(), authority.clone(), second_factor.clone()], None).unwrap_err(),
+            UnityVaultError::SecondFactorRequired.into()
+        );
+        second_factor.is_signer = true;
+        disable_two_factor(&program_id, &[profile.clone(), authority, second_factor], None).unwrap();
+        
+        let profile_data = load_account::<UserProfile>(&profile, &program_id).unwrap();
+        assert!(!profile_data.two_factor_enabled);
+        assert_eq!(profile_data.second_factor, None);
+    }
+}
@@ -1,9 +1,40 @@
 use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
 use std::convert::TryInto;
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+use crate::utils::{AccountType, ProgramAccount};
+
+/// Seed for a user's profile PDA: `["user", authority]`.
+pub const USER_PROFILE_SEED: &[u8] = b"user";
+
+/// Seed for the singleton KYC verifier registry PDA: `["kyc_registry"]`.
+pub const KYC_REGISTRY_SEED: &[u8] = b"kyc_registry";
+
+/// Maximum number of verifiers a KYC registry can hold.
+pub const MAX_KYC_VERIFIERS: usize = 10;
+
+/// Maximum number of two-factor backup codes a profile can hold.
+pub const MAX_BACKUP_CODES: usize = 10;
+
+pub fn find_user_profile_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_PROFILE_SEED, authority.as_ref()], program_id)
+}
+
+pub fn find_kyc_registry_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[KYC_REGISTRY_SEED], program_id)
+}
+
+/// Salted hash of a two-factor backup code, as stored in `BackupCode::hash`.
+pub fn hash_backup_code(salt: &[u8; 32], code: &[u8]) -> [u8; 32] {
+    hashv(&[salt, code]).to_bytes()
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum UserRole {
@@ -31,19 +62,51 @@ pub struct KycData {
     pub document_type: String,
     pub document_number: String,
     pub verified_at: i64,
+    pub expires_at: i64,
+    pub verifier: Pubkey,
+    pub rejection_reason: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BackupCode {
+    pub hash: [u8; 32],
+    pub used: bool,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct KycParams {
+    pub document_type: String,
+    pub document_number: String,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct KycRegistry {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub verifiers: Vec<Pubkey>,
+    /// Seconds a verification stays valid; 0 means it never expires.
+    pub verification_validity: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct UserProfile {
     pub is_initialized: bool,
+    pub bump: u8,
     pub authority: Pubkey,
     pub full_name: String,
     pub email: String,
     pub role: UserRole,
     pub status: UserStatus,
     pub two_factor_enabled: bool,
-    pub two_factor_secret: String,
-    pub two_factor_backup_codes: Vec<String>,
+    /// Hash commitment of the two-factor secret; the secret itself never goes on chain.
+    pub two_factor_secret_hash: [u8; 32],
+    pub backup_code_salt: [u8; 32],
+    pub two_factor_backup_codes: Vec<BackupCode>,
+    /// Key that must co-sign sensitive operations while two-factor is enabled.
+    pub second_factor: Option<Pubkey>,
     pub kyc_verified: bool,
     pub kyc_status: KycStatus,
     pub kyc_data: KycData,
@@ -53,27 +116,76 @@ pub struct UserProfile {
 }
 
 impl UserProfile {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
         32 + // authority
         4 + 100 + // full_name (max 100 chars)
         4 + 100 + // email (max 100 chars)
         1 + // role
         1 + // status
         1 + // two_factor_enabled
-        4 + 100 + // two_factor_secret (max 100 chars)
-        4 + 10 * 50 + // two_factor_backup_codes (max 10 codes, 50 chars each)
+        32 + // two_factor_secret_hash
+        32 + // backup_code_salt
+        4 + MAX_BACKUP_CODES * (32 + 1) + // two_factor_backup_codes
+        1 + 32 + // second_factor
         1 + // kyc_verified
         1 + // kyc_status
         4 + 100 + // kyc_data.document_type (max 100 chars)
         4 + 100 + // kyc_data.document_number (max 100 chars)
         8 + // kyc_data.verified_at
+        8 + // kyc_data.expires_at
+        32 + // kyc_data.verifier
+        2 + // kyc_data.rejection_reason
         1 + // accredited_status
         8 + // created_at
         8; // updated_at
 }
 
+impl UserProfile {
+    /// KYC status as of `now`: a verification past its expiry counts as pending.
+    pub fn effective_kyc_status(&self, now: i64) -> KycStatus {
+        if self.kyc_status == KycStatus::Verified
+            && self.kyc_data.expires_at != 0
+            && now >= self.kyc_data.expires_at
+        {
+            return KycStatus::Pending;
+        }
+        self.kyc_status
+    }
+    
+    /// Requires `signer` to be the profile's second-factor key, if one is set.
+    pub fn check_second_factor(&self, signer: Option<&AccountInfo>) -> ProgramResult {
+        let second_factor = match self.second_factor {
+            Some(key) if self.two_factor_enabled => key,
+            _ => return Ok(()),
+        };
+        
+        match signer {
+            Some(signer) if *signer.key == second_factor && signer.is_signer => Ok(()),
+            _ => Err(UnityVaultError::SecondFactorRequired.into()),
+        }
+    }
+    
+    /// Marks the unused backup code whose hash matches `code` as used.
+    pub fn consume_backup_code(&mut self, code: &[u8]) -> ProgramResult {
+        let hash = hash_backup_code(&self.backup_code_salt, code);
+        let backup_code = self
+            .two_factor_backup_codes
+            .iter_mut()
+            .find(|backup_code| !backup_code.used && backup_code.hash == hash)
+            .ok_or(UnityVaultError::InvalidBackupCode)?;
+        backup_code.used = true;
+        Ok(())
+    }
+}
+
 impl Sealed for UserProfile {}
 
+impl ProgramAccount for UserProfile {
+    const ACCOUNT_TYPE: AccountType = AccountType::UserProfile;
+}
+
 impl IsInitialized for UserProfile {
     fn is_initialized(&self) -> bool {
         self.is_initialized
@@ -86,10 +198,18 @@ impl Pack for UserProfile {
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut offset = 0;
         
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
         // Pack is_initialized
         dst[offset] = self.is_initialized as u8;
         offset += 1;
         
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
         // Pack authority
         dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
         offset += 32;
@@ -120,24 +240,37 @@ impl Pack for UserProfile {
         dst[offset] = self.two_factor_enabled as u8;
         offset += 1;
         
-        // Pack two_factor_secret
-        let secret_bytes = self.two_factor_secret.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(secret_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + secret_bytes.len()].copy_from_slice(secret_bytes);
-        offset += secret_bytes.len();
+        // Pack two_factor_secret_hash
+        dst[offset..offset + 32].copy_from_slice(&self.two_factor_secret_hash);
+        offset += 32;
+        
+        // Pack backup_code_salt
+        dst[offset..offset + 32].copy_from_slice(&self.backup_code_salt);
+        offset += 32;
         
         // Pack two_factor_backup_codes
         dst[offset..offset + 4].copy_from_slice(&(self.two_factor_backup_codes.len() as u32).to_le_bytes());
         offset += 4;
         for code in &self.two_factor_backup_codes {
-            let code_bytes = code.as_bytes();
-            dst[offset..offset + 4].copy_from_slice(&(code_bytes.len() as u32).to_le_bytes());
-            offset += 4;
-            dst[offset..offset + code_bytes.len()].copy_from_slice(code_bytes);
-            offset += code_bytes.len();
+            dst[offset..offset + 32].copy_from_slice(&code.hash);
+            offset += 32;
+            dst[offset] = code.used as u8;
+            offset += 1;
         }
         
+        // Pack second_factor
+        match self.second_factor {
+            Some(key) => {
+                dst[offset] = 1;
+                dst[offset + 1..offset + 33].copy_from_slice(&key.to_bytes());
+            }
+            None => {
+                dst[offset] = 0;
+                dst[offset + 1..offset + 33].fill(0);
+            }
+        }
+        offset += 33;
+        
         // Pack kyc_verified
         dst[offset] = self.kyc_verified as u8;
         offset += 1;
@@ -162,6 +295,15 @@ impl Pack for UserProfile {
         dst[offset..offset + 8].copy_from_slice(&self.kyc_data.verified_at.to_le_bytes());
         offset += 8;
         
+        dst[offset..offset + 8].copy_from_slice(&self.kyc_data.expires_at.to_le_bytes());
+        offset += 8;
+        
+        dst[offset..offset + 32].copy_from_slice(&self.kyc_data.verifier.to_bytes());
+        offset += 32;
+        
+        dst[offset..offset + 2].copy_from_slice(&self.kyc_data.rejection_reason.to_le_bytes());
+        offset += 2;
+        
         // Pack accredited_status
         dst[offset] = self.accredited_status as u8;
         offset += 1;
@@ -173,12 +315,17 @@ impl Pack for UserProfile {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut offset = 0;
+        // The account type byte is checked by load_account
+        let mut offset = 1;
         
         // Unpack is_initialized
         let is_initialized = src[offset] != 0;
         offset += 1;
         
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
         // Unpack authority
         let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
             .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
@@ -221,26 +368,37 @@ impl Pack for UserProfile {
         let two_factor_enabled = src[offset] != 0;
         offset += 1;
         
-        // Unpack two_factor_secret
-        let secret_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let two_factor_secret = String::from_utf8(src[offset..offset + secret_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += secret_len;
+        // Unpack two_factor_secret_hash
+        let two_factor_secret_hash: [u8; 32] = src[offset..offset + 32].try_into().unwrap();
+        offset += 32;
+        
+        // Unpack backup_code_salt
+        let backup_code_salt: [u8; 32] = src[offset..offset + 32].try_into().unwrap();
+        offset += 32;
         
         // Unpack two_factor_backup_codes
         let codes_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
         offset += 4;
+        if codes_len > MAX_BACKUP_CODES {
+            return Err(ProgramError::InvalidAccountData);
+        }
         let mut two_factor_backup_codes = Vec::with_capacity(codes_len);
         for _ in 0..codes_len {
-            let code_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-            offset += 4;
-            let code = String::from_utf8(src[offset..offset + code_len].to_vec())
-                .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-            offset += code_len;
-            two_factor_backup_codes.push(code);
+            let hash: [u8; 32] = src[offset..offset + 32].try_into().unwrap();
+            offset += 32;
+            let used = src[offset] != 0;
+            offset += 1;
+            two_factor_backup_codes.push(BackupCode { hash, used });
         }
         
+        // Unpack second_factor
+        let second_factor = match src[offset] {
+            0 => None,
+            1 => Some(Pubkey::from(<[u8; 32]>::try_from(&src[offset + 1..offset + 33]).unwrap())),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        offset += 33;
+        
         // Unpack kyc_verified
         let kyc_verified = src[offset] != 0;
         offset += 1;
@@ -270,6 +428,17 @@ impl Pack for UserProfile {
         let verified_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
         offset += 8;
         
+        let expires_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        let verifier_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
+        let verifier = Pubkey::from(verifier_bytes);
+        offset += 32;
+        
+        let rejection_reason = u16::from_le_bytes(src[offset..offset + 2].try_into().unwrap());
+        offset += 2;
+        
         // Unpack accredited_status
         let accredited_status = src[offset] != 0;
         offset += 1;
@@ -281,24 +450,151 @@ impl Pack for UserProfile {
         
         Ok(UserProfile {
             is_initialized,
+            bump,
             authority,
             full_name,
             email,
             role,
             status,
             two_factor_enabled,
-            two_factor_secret,
+            two_factor_secret_hash,
+            backup_code_salt,
             two_factor_backup_codes,
+            second_factor,
             kyc_verified,
             kyc_status,
             kyc_data: KycData {
                 document_type,
                 document_number,
                 verified_at,
+                expires_at,
+                verifier,
+                rejection_reason,
             },
             accredited_status,
             created_at,
             updated_at,
         })
     }
-} 
\ No newline at end of file
+} 
+impl KycRegistry {
+    pub const LEN: usize = 1 + // account_type
+        1 + // is_initialized
+        1 + // bump
+        32 + // admin
+        4 + MAX_KYC_VERIFIERS * 32 + // verifiers
+        8 + // verification_validity
+        8 + // created_at
+        8; // updated_at
+    
+    pub fn is_verifier(&self, key: &Pubkey) -> bool {
+        self.verifiers.contains(key)
+    }
+}
+
+impl Sealed for KycRegistry {}
+
+impl ProgramAccount for KycRegistry {
+    const ACCOUNT_TYPE: AccountType = AccountType::KycRegistry;
+}
+
+impl IsInitialized for KycRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for KycRegistry {
+    const LEN: usize = Self::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        
+        // Pack account type
+        dst[offset] = Self::ACCOUNT_TYPE as u8;
+        offset += 1;
+        
+        // Pack is_initialized
+        dst[offset] = self.is_initialized as u8;
+        offset += 1;
+        
+        // Pack bump
+        dst[offset] = self.bump;
+        offset += 1;
+        
+        // Pack admin
+        dst[offset..offset + 32].copy_from_slice(&self.admin.to_bytes());
+        offset += 32;
+        
+        // Pack verifiers
+        dst[offset..offset + 4].copy_from_slice(&(self.verifiers.len() as u32).to_le_bytes());
+        offset += 4;
+        for verifier in &self.verifiers {
+            dst[offset..offset + 32].copy_from_slice(&verifier.to_bytes());
+            offset += 32;
+        }
+        offset += (MAX_KYC_VERIFIERS - self.verifiers.len()) * 32;
+        
+        // Pack verification_validity
+        dst[offset..offset + 8].copy_from_slice(&self.verification_validity.to_le_bytes());
+        offset += 8;
+        
+        // Pack timestamps
+        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
+        offset += 8;
+        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
+        // The account type byte is checked by load_account
+        let mut offset = 1;
+        
+        // Unpack is_initialized
+        let is_initialized = src[offset] != 0;
+        offset += 1;
+        
+        // Unpack bump
+        let bump = src[offset];
+        offset += 1;
+        
+        // Unpack admin
+        let admin_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
+        let admin = Pubkey::from(admin_bytes);
+        offset += 32;
+        
+        // Unpack verifiers
+        let verifiers_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        if verifiers_len > MAX_KYC_VERIFIERS {
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+        let mut verifiers = Vec::with_capacity(verifiers_len);
+        for _ in 0..verifiers_len {
+            let verifier_bytes: [u8; 32] = src[offset..offset + 32].try_into()
+                .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
+            verifiers.push(Pubkey::from(verifier_bytes));
+            offset += 32;
+        }
+        offset += (MAX_KYC_VERIFIERS - verifiers_len) * 32;
+        
+        // Unpack verification_validity
+        let verification_validity = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        
+        // Unpack timestamps
+        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        
+        Ok(KycRegistry {
+            is_initialized,
+            bump,
+            admin,
+            verifiers,
+            verification_validity,
+            created_at,
+            updated_at,
+        })
+    }
+}
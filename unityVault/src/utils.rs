@@ -0,0 +1,230 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+
+/// Discriminator stored in the first byte of every program-owned account.
+///
+/// Values are part of the on-chain layout and must never be reordered.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Uninitialized,
+    UserProfile,
+    Proposal,
+    Community,
+    LendingPool,
+    Loan,
+    TokenInfo,
+    KycRegistry,
+    LiquidationEvent,
+    InterestRateHistory,
+    CreditRecord,
+    VoteRecord,
+    Realm,
+    TokenOwnerRecord,
+    Delegation,
+    ProposalAction,
+    ProposalOptions,
+    GovernanceConfig,
+    TreasuryLedger,
+}
+
+/// A `Pack` state struct stored in an account owned by this program.
+pub trait ProgramAccount: Pack + IsInitialized {
+    const ACCOUNT_TYPE: AccountType;
+}
+
+/// Loads an initialized `T` from `account`, checking that the account is owned
+/// by this program, has the size of `T` and carries `T`'s discriminator.
+pub fn load_account<T: ProgramAccount>(account: &AccountInfo, program_id: &Pubkey) -> Result<T, ProgramError> {
+    if account.owner != program_id {
+        return Err(UnityVaultError::InvalidAccountOwner.into());
+    }
+    
+    let data = account.data.borrow();
+    if data.len() != T::LEN {
+        return Err(UnityVaultError::InvalidAccountSize.into());
+    }
+    if data[0] != T::ACCOUNT_TYPE as u8 {
+        return Err(UnityVaultError::InvalidAccountType.into());
+    }
+    
+    T::unpack(&data)
+}
+
+/// Creates a rent-exempt, program-owned account at a program-derived address.
+///
+/// Accounts that were already funded (for example by someone sending lamports
+/// to the address ahead of time) are topped up, allocated and assigned instead,
+/// since `create_account` refuses accounts with a non-zero balance.
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(new_account.lamports());
+
+    if new_account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                new_account.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), new_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        );
+    }
+
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, new_account.key, required_lamports),
+            &[payer.clone(), new_account.clone(), system_program.clone()],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(new_account.key, space as u64),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(new_account.key, program_id),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Grows a program-owned account to `new_len` bytes, with `payer` topping up
+/// its rent exemption.
+pub fn resize_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, required_lamports),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    account.realloc(new_len, true)
+}
+
+/// Closes a program-owned account, moving its rent to `destination` and
+/// zeroing its data so it can no longer be loaded.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(UnityVaultError::MathOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+    account.data.borrow_mut().fill(0);
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_test_account;
+    use crate::tokenization::state::{TokenInfo, TokenStatus};
+    
+    fn token_info_data() -> Vec<u8> {
+        let mut data = vec![0; TokenInfo::LEN];
+        TokenInfo::pack(
+            TokenInfo {
+                is_initialized: true,
+                bump: 255,
+                creator: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                mint_authority_bump: 255,
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                decimals: 9,
+                total_supply: 1000,
+                status: TokenStatus::Active,
+                created_at: 0,
+                updated_at: 0,
+            },
+            &mut data,
+        )
+        .unwrap();
+        data
+    }
+    
+    #[test]
+    fn test_load_account() {
+        let program_id = Pubkey::new_unique();
+        let account = create_test_account(Pubkey::new_unique(), program_id, false, token_info_data());
+        
+        let token_info = load_account::<TokenInfo>(&account, &program_id).unwrap();
+        assert_eq!(token_info.symbol, "TEST");
+    }
+    
+    #[test]
+    fn test_load_account_rejects_foreign_owner() {
+        let program_id = Pubkey::new_unique();
+        let account = create_test_account(Pubkey::new_unique(), Pubkey::new_unique(), false, token_info_data());
+        
+        assert_eq!(
+            load_account::<TokenInfo>(&account, &program_id).unwrap_err(),
+            UnityVaultError::InvalidAccountOwner.into()
+        );
+    }
+    
+    #[test]
+    fn test_load_account_rejects_wrong_type() {
+        let program_id = Pubkey::new_unique();
+        let mut data = token_info_data();
+        data[0] = AccountType::LendingPool as u8;
+        let account = create_test_account(Pubkey::new_unique(), program_id, false, data);
+        
+        assert_eq!(
+            load_account::<TokenInfo>(&account, &program_id).unwrap_err(),
+            UnityVaultError::InvalidAccountType.into()
+        );
+    }
+    
+    #[test]
+    fn test_load_account_rejects_wrong_size_and_uninitialized() {
+        let program_id = Pubkey::new_unique();
+        let account = create_test_account(Pubkey::new_unique(), program_id, false, vec![0; TokenInfo::LEN + 1]);
+        assert_eq!(
+            load_account::<TokenInfo>(&account, &program_id).unwrap_err(),
+            UnityVaultError::InvalidAccountSize.into()
+        );
+        
+        let mut data = vec![0; TokenInfo::LEN];
+        data[0] = AccountType::TokenInfo as u8;
+        let account = create_test_account(Pubkey::new_unique(), program_id, false, data);
+        assert_eq!(
+            load_account::<TokenInfo>(&account, &program_id).unwrap_err(),
+            ProgramError::UninitializedAccount
+        );
+    }
+}